@@ -1 +1,42 @@
-// Disables a feature.
\ No newline at end of file
+// Disables a feature.
+// disable <feature> [message]
+
+use super::features::{default_refusal, Feature};
+use crate::data::features::store::{DisabledFeature, FeatureStore};
+
+/// Turns a feature off: its commands are refused and its background work stops.
+#[poise::command(slash_command, prefix_command, rename = "disable", guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn disable(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Feature to turn off"] feature: Feature,
+    #[rest]
+    #[description = "Reply to its commands (default: \"<Feature> commands are disabled on this server.\")"]
+    message: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let message = message.map(|m| m.trim().to_string()).filter(|m| !m.is_empty());
+    if message.as_ref().is_some_and(|m| m.chars().count() > 2000) {
+        ctx.say("The message can't be longer than 2000 characters.").await?;
+        return Ok(());
+    }
+    let store = FeatureStore::open(&ctx.data().db)?;
+    let mut features = store.get(guild_id.get())?;
+    let name = poise::ChoiceParameter::name(&feature);
+    // Disabling again only changes the message
+    let updated = features.disabled(feature.key()).is_some();
+    features.disabled.retain(|d| d.feature != feature.key());
+    features.disabled.push(DisabledFeature {
+        feature: feature.key().to_string(),
+        message: message.clone(),
+        disabled_by: ctx.author().id.get(),
+        disabled_at: chrono::Utc::now().timestamp(),
+    });
+    store.set(guild_id.get(), &features)?;
+    let reply = message.unwrap_or_else(|| default_refusal(feature));
+    if updated {
+        ctx.say(format!("{} was already disabled, its commands now reply with:\n> {}", name, reply)).await?;
+    } else {
+        ctx.say(format!("{} is disabled. Its commands reply with:\n> {}", name, reply)).await?;
+    }
+    Ok(())
+}
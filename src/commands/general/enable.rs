@@ -1 +1,49 @@
-// Enables a feature.
\ No newline at end of file
+// Enables a feature.
+// enable [feature]
+
+use super::features::{Feature, ALL};
+use crate::commands::information::utils::COLOR;
+use crate::data::features::store::FeatureStore;
+use poise::serenity_prelude as serenity;
+
+/// Turns a feature back on, or shows which features are on.
+#[poise::command(slash_command, prefix_command, rename = "enable", guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn enable(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Feature to turn on (default: show all features)"] feature: Option<Feature>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = FeatureStore::open(&ctx.data().db)?;
+    let mut features = store.get(guild_id.get())?;
+
+    let Some(feature) = feature else {
+        let lines: Vec<String> = ALL
+            .iter()
+            .map(|f| {
+                let name = poise::ChoiceParameter::name(f);
+                match features.disabled(f.key()) {
+                    Some(d) => format!("❌ **{}** - disabled by <@{}> <t:{}:R>", name, d.disabled_by, d.disabled_at),
+                    None => format!("✅ **{}**", name),
+                }
+            })
+            .collect();
+        let embed = serenity::CreateEmbed::default()
+            .title("Features")
+            .description(lines.join("\n"))
+            .footer(serenity::CreateEmbedFooter::new("Use /enable <feature> or /disable <feature> to change them."))
+            .color(COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    };
+
+    let before = features.disabled.len();
+    features.disabled.retain(|d| d.feature != feature.key());
+    let name = poise::ChoiceParameter::name(&feature);
+    if features.disabled.len() == before {
+        ctx.say(format!("{} is already enabled.", name)).await?;
+        return Ok(());
+    }
+    store.set(guild_id.get(), &features)?;
+    ctx.say(format!("{} is enabled again.", name)).await?;
+    Ok(())
+}
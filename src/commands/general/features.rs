@@ -0,0 +1,99 @@
+// Per-guild feature flags: the features commands are grouped into, whether they are turned on and
+// the check that refuses commands of disabled features.
+
+use crate::data::features::store::FeatureStore;
+use poise::serenity_prelude as serenity;
+
+/// A feature that can be turned off. Names match the help categories of its commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Feature {
+    Valorant,
+    Information,
+    Logging,
+    #[name = "Reaction roles"]
+    ReactionRoles,
+    #[name = "Join roles"]
+    JoinRoles,
+    Messages,
+    Moderation,
+    #[name = "Social notifications"]
+    SocialNotifications,
+}
+
+pub(crate) const ALL: [Feature; 8] = [
+    Feature::Valorant,
+    Feature::Information,
+    Feature::Logging,
+    Feature::ReactionRoles,
+    Feature::JoinRoles,
+    Feature::Messages,
+    Feature::Moderation,
+    Feature::SocialNotifications,
+];
+
+impl Feature {
+    /// Stored in the database, so it must not change.
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Feature::Valorant => "valorant",
+            Feature::Information => "information",
+            Feature::Logging => "logging",
+            Feature::ReactionRoles => "reaction_roles",
+            Feature::JoinRoles => "join_roles",
+            Feature::Messages => "messages",
+            Feature::Moderation => "moderation",
+            Feature::SocialNotifications => "social_notifications",
+        }
+    }
+
+    /// The feature of a help category. General commands (like enable) can't be turned off.
+    pub(crate) fn of_category(category: &str) -> Option<Self> {
+        ALL.into_iter().find(|f| poise::ChoiceParameter::name(f) == category)
+    }
+}
+
+/// Whether a feature is on in a guild. Background tasks and event handlers skip guilds that
+/// turned theirs off. Read errors count as on, so a broken entry doesn't silently stop features.
+pub(crate) fn enabled(db: &sled::Db, guild_id: u64, feature: Feature) -> bool {
+    match FeatureStore::open(db).and_then(|s| s.get(guild_id)) {
+        Ok(features) => features.disabled(feature.key()).is_none(),
+        Err(e) => {
+            eprintln!("reading the features of guild {} failed: {}", guild_id, e);
+            true
+        }
+    }
+}
+
+pub(crate) fn default_refusal(feature: Feature) -> String {
+    format!("{} commands are disabled on this server.", poise::ChoiceParameter::name(&feature))
+}
+
+/// What to tell someone using a component of a disabled feature, None while it is on. Like
+/// [`enabled`], read errors count as on.
+pub(crate) fn refusal(db: &sled::Db, guild_id: u64, feature: Feature) -> Option<String> {
+    let features = FeatureStore::open(db).and_then(|s| s.get(guild_id)).ok()?;
+    let disabled = features.disabled(feature.key())?;
+    Some(disabled.message.clone().unwrap_or_else(|| default_refusal(feature)))
+}
+
+/// Global command check: refuses commands whose feature is disabled in the guild, with the
+/// message given when it was disabled.
+pub async fn command_check(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<bool, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(true) };
+    // Subcommands have no category of their own
+    let top = ctx.parent_commands().first().copied().unwrap_or(ctx.command());
+    let Some(feature) = top.category.as_deref().and_then(Feature::of_category) else { return Ok(true) };
+    let features = FeatureStore::open(&ctx.data().db)?.get(guild_id.get())?;
+    let Some(disabled) = features.disabled(feature.key()) else { return Ok(true) };
+    let message = disabled.message.clone().unwrap_or_else(|| default_refusal(feature));
+    ctx.send(poise::CreateReply::default().content(message).ephemeral(true)).await?;
+    Ok(false)
+}
+
+/// Help categories turned off in a guild, to hide their commands.
+pub(crate) fn disabled_categories(db: &sled::Db, guild_id: serenity::GuildId) -> Vec<&'static str> {
+    ALL.into_iter()
+        .filter(|f| !enabled(db, guild_id.get(), *f))
+        .map(|f| poise::ChoiceParameter::name(&f))
+        .collect()
+}
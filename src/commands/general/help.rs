@@ -1,2 +1,364 @@
 // Lists all commands or features available.
-// help <commands|features>
\ No newline at end of file
+// help [command|feature]
+
+use super::features::disabled_categories;
+use crate::commands::information::utils::{join_limited, COLOR};
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+
+type Command = poise::Command<crate::Data, crate::Error>;
+
+const PAGE_SIZE: usize = 15;
+const OTHER: &str = "Other";
+
+/// A command or subcommand with its full name, like `template create`.
+struct Entry<'a> {
+    path: String,
+    command: &'a Command,
+    /// Permissions needed for this command and every parent of it.
+    permissions: serenity::Permissions,
+    guild_only: bool,
+    /// Category of the top level command, subcommands don't have one themselves.
+    category: &'a str,
+    top_level: bool,
+}
+
+/// Every command and subcommand that can be run, depth first.
+fn entries(commands: &[Command]) -> Vec<Entry<'_>> {
+    fn walk<'a>(out: &mut Vec<Entry<'a>>, command: &'a Command, parent: Option<&Entry<'a>>) {
+        let path = match parent {
+            Some(p) => format!("{} {}", p.path, command.name),
+            None => display_name(command).to_string(),
+        };
+        let entry = Entry {
+            path,
+            command,
+            permissions: command.required_permissions | parent.map(|p| p.permissions).unwrap_or_default(),
+            guild_only: command.guild_only || parent.is_some_and(|p| p.guild_only),
+            category: parent.map(|p| p.category).unwrap_or_else(|| command.category.as_deref().unwrap_or(OTHER)),
+            top_level: parent.is_none(),
+        };
+        for sub in &command.subcommands {
+            walk(out, sub, Some(&entry));
+        }
+        out.push(entry);
+    }
+    let mut out = Vec::new();
+    for command in commands {
+        walk(&mut out, command, None);
+    }
+    out
+}
+
+fn display_name(command: &Command) -> &str {
+    match (&command.slash_action, &command.context_menu_name) {
+        (None, Some(menu)) => menu,
+        _ => &command.name,
+    }
+}
+
+/// Permissions of the caller in this channel, None outside of servers.
+fn caller_permissions(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Option<serenity::Permissions> {
+    let guild_id = ctx.guild_id()?;
+    // Discord resolves the permissions of slash command users, overwrites included
+    if let poise::Context::Application(a) = ctx
+        && let Some(permissions) = a.interaction.member.as_ref().and_then(|m| m.permissions)
+    {
+        return Some(permissions);
+    }
+    let guild = ctx.cache().guild(guild_id)?;
+    let member = guild.members.get(&ctx.author().id)?;
+    if let Some(channel) = guild.channels.get(&ctx.channel_id()) {
+        return Some(guild.user_permissions_in(channel, member));
+    }
+    // Threads aren't in the channel list, fall back to the server-wide permissions
+    if guild.owner_id == member.user.id {
+        return Some(serenity::Permissions::all());
+    }
+    let everyone = guild.roles.get(&serenity::RoleId::new(guild_id.get())).map(|r| r.permissions).unwrap_or_default();
+    let permissions = member.roles.iter().filter_map(|r| guild.roles.get(r)).fold(everyone, |p, r| p | r.permissions);
+    Some(if permissions.administrator() { serenity::Permissions::all() } else { permissions })
+}
+
+/// What decides which commands the caller sees.
+struct Caller {
+    /// None outside of servers.
+    permissions: Option<serenity::Permissions>,
+    /// Categories of the features disabled in this server.
+    disabled: Vec<&'static str>,
+}
+
+impl Caller {
+    fn load(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Self {
+        Self {
+            permissions: caller_permissions(ctx),
+            disabled: ctx.guild_id().map(|g| disabled_categories(&ctx.data().db, g)).unwrap_or_default(),
+        }
+    }
+}
+
+/// Whether the caller can use a command here. Server commands are hidden in DMs, commands
+/// without enough permissions or of disabled features everywhere.
+fn usable(ctx: poise::Context<'_, crate::Data, crate::Error>, entry: &Entry, caller: &Caller) -> bool {
+    if entry.command.hide_in_help || caller.disabled.contains(&entry.category) {
+        return false;
+    }
+    if entry.command.owners_only && !ctx.framework().options().owners.contains(&ctx.author().id) {
+        return false;
+    }
+    match caller.permissions {
+        Some(p) => p.contains(entry.permissions),
+        None => !entry.guild_only,
+    }
+}
+
+/// The kind of a slash command parameter, read back from the option poise builds for it.
+fn parameter_kind(parameter: &poise::CommandParameter<crate::Data, crate::Error>) -> Option<serenity::CommandOptionType> {
+    let setter = parameter.type_setter?;
+    let option = setter(serenity::CreateCommandOption::new(serenity::CommandOptionType::String, "x", "x"));
+    let kind = serde_json::to_value(option).ok()?.get("type")?.as_u64()?;
+    Some(serenity::CommandOptionType::from(kind as u8))
+}
+
+/// A made-up value for a parameter to build examples with.
+fn example_value(parameter: &poise::CommandParameter<crate::Data, crate::Error>, author: &serenity::User) -> String {
+    if let Some(choice) = parameter.choices.first() {
+        return choice.name.to_lowercase();
+    }
+    let name = parameter.name.as_str();
+    match parameter_kind(parameter) {
+        Some(serenity::CommandOptionType::User | serenity::CommandOptionType::Mentionable) => format!("@{}", author.name),
+        Some(serenity::CommandOptionType::Channel) => "#general".to_string(),
+        Some(serenity::CommandOptionType::Role) => "@Members".to_string(),
+        Some(serenity::CommandOptionType::Integer) => "10".to_string(),
+        Some(serenity::CommandOptionType::Number) => "2.5".to_string(),
+        Some(serenity::CommandOptionType::Boolean) => "true".to_string(),
+        Some(serenity::CommandOptionType::Attachment) => "(file)".to_string(),
+        _ if name.contains("duration") || name.contains("time") => "1h".to_string(),
+        _ if name.contains("reason") => "spamming".to_string(),
+        _ if name.contains("id") => "123456789012345678".to_string(),
+        _ => name.replace('_', " "),
+    }
+}
+
+fn usage(entry: &Entry) -> String {
+    let mut out = format!("/{}", entry.path);
+    if !entry.command.subcommands.is_empty() {
+        let subs: Vec<&str> = entry.command.subcommands.iter().map(|s| s.name.as_str()).collect();
+        let list = format!("<{}>", subs.join("|"));
+        out.push(' ');
+        out.push_str(&if entry.command.subcommand_required { list } else { format!("[{}]", &list[1..list.len() - 1]) });
+    }
+    for p in &entry.command.parameters {
+        out.push_str(&if p.required { format!(" <{}>", p.name) } else { format!(" [{}]", p.name) });
+    }
+    out
+}
+
+fn examples(entry: &Entry, author: &serenity::User) -> Vec<String> {
+    let params = &entry.command.parameters;
+    let build = |all: bool| {
+        let mut out = format!("/{}", entry.path);
+        for p in params.iter().filter(|p| all || p.required) {
+            out.push(' ');
+            out.push_str(&example_value(p, author));
+        }
+        out
+    };
+    let mut out = vec![build(false)];
+    if params.iter().any(|p| !p.required) {
+        out.push(build(true));
+    }
+    out
+}
+
+fn overview(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    entries: &[Entry],
+    caller: &Caller,
+) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title("Commands")
+        .description("Use `/help <command>` for details about a command, or `/help <feature>` for the commands of a feature.")
+        .color(COLOR);
+    // Categories in the order the commands are registered
+    let mut categories: Vec<&str> = Vec::new();
+    for e in entries.iter().filter(|e| e.top_level) {
+        if !categories.contains(&e.category) {
+            categories.push(e.category);
+        }
+    }
+    for c in categories {
+        let names: Vec<String> = entries
+            .iter()
+            .filter(|e| e.top_level && e.category == c && usable(ctx, e, caller))
+            .map(|e| format!("`{}`", e.path))
+            .collect();
+        if !names.is_empty() {
+            embed = embed.field(c, join_limited(&names, " ", 1024), false);
+        }
+    }
+    embed
+}
+
+fn feature_pages(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    entries: &[Entry],
+    caller: &Caller,
+    feature: &str,
+) -> Vec<serenity::CreateEmbed> {
+    // Commands with subcommands are listed by their subcommands
+    let lines: Vec<String> = entries
+        .iter()
+        .filter(|e| e.category == feature && e.command.subcommands.is_empty() && usable(ctx, e, caller))
+        .map(|e| format!("`/{}` - {}", e.path, e.command.description.as_deref().unwrap_or("No description")))
+        .collect();
+    lines
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            serenity::CreateEmbed::default()
+                .title(format!("{} commands", feature))
+                .description(chunk.join("\n"))
+                .footer(serenity::CreateEmbedFooter::new(format!("{} command(s), use /help <command> for details.", lines.len())))
+                .color(COLOR)
+        })
+        .collect()
+}
+
+fn details(ctx: poise::Context<'_, crate::Data, crate::Error>, entry: &Entry) -> serenity::CreateEmbed {
+    let command = entry.command;
+    let mut description = command.description.clone().unwrap_or_else(|| "No description.".to_string());
+    if let Some(help) = &command.help_text {
+        description.push_str("\n\n");
+        description.push_str(help);
+    }
+    let menu_only = command.slash_action.is_none() && command.prefix_action.is_none();
+    let mut embed = serenity::CreateEmbed::default()
+        .title(if menu_only { entry.path.clone() } else { format!("/{}", entry.path) })
+        .description(description)
+        .color(COLOR);
+
+    if menu_only {
+        let target = match command.context_menu_action {
+            Some(poise::ContextMenuCommandAction::Message(_)) => "a message",
+            _ => "a user",
+        };
+        embed = embed.field("Usage", format!("Right-click {} and pick it under Apps.", target), false);
+    } else {
+        embed = embed.field("Usage", format!("`{}`", usage(entry)), false);
+    }
+    if !command.parameters.is_empty() && !menu_only {
+        let lines: Vec<String> = command
+            .parameters
+            .iter()
+            .map(|p| {
+                let mut line = format!("`{}`", p.name);
+                if !p.required {
+                    line.push_str(" (optional)");
+                }
+                line.push_str(&format!(" - {}", p.description.as_deref().unwrap_or("No description")));
+                if !p.choices.is_empty() {
+                    let choices: Vec<&str> = p.choices.iter().map(|c| c.name.as_str()).collect();
+                    line.push_str(&format!(" ({})", choices.join(", ")));
+                }
+                line
+            })
+            .collect();
+        embed = embed.field("Parameters", join_limited(&lines, "\n", 1024), false);
+    }
+    if !command.subcommands.is_empty() {
+        let lines: Vec<String> = command
+            .subcommands
+            .iter()
+            .map(|s| format!("`{}` - {}", s.name, s.description.as_deref().unwrap_or("No description")))
+            .collect();
+        embed = embed.field("Subcommands", join_limited(&lines, "\n", 1024), false);
+    } else if !menu_only {
+        let examples: Vec<String> = examples(entry, ctx.author()).iter().map(|e| format!("`{}`", e)).collect();
+        embed = embed.field("Examples", examples.join("\n"), false);
+    }
+    if !entry.permissions.is_empty() {
+        let names: Vec<String> = entry.permissions.iter_names().map(|(name, _)| format!("`{}`", name.to_lowercase())).collect();
+        embed = embed.field("Required permissions", names.join(", "), true);
+    }
+    if entry.guild_only {
+        embed = embed.field("Servers only", "Yes", true);
+    }
+    embed.footer(serenity::CreateEmbedFooter::new(entry.category))
+}
+
+async fn autocomplete_query<'a>(ctx: poise::Context<'a, crate::Data, crate::Error>, partial: &'a str) -> Vec<String> {
+    let caller = Caller::load(ctx);
+    let commands = &ctx.framework().options().commands;
+    let entries = entries(commands);
+    let partial = partial.to_lowercase();
+    let mut out: Vec<String> = Vec::new();
+    for e in &entries {
+        if !out.iter().any(|c| c == e.category) && e.category.to_lowercase().starts_with(&partial) {
+            out.push(e.category.to_string());
+        }
+    }
+    out.extend(
+        entries
+            .iter()
+            .filter(|e| e.path.to_lowercase().contains(&partial) && usable(ctx, e, &caller))
+            .map(|e| e.path.clone()),
+    );
+    out.truncate(25);
+    out
+}
+
+/// Lists the commands you can use, or explains one command or feature.
+#[poise::command(slash_command, prefix_command, rename = "help")]
+pub async fn help(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Command or feature (default: all commands)"]
+    #[autocomplete = "autocomplete_query"]
+    query: Option<String>,
+) -> Result<(), crate::Error> {
+    let caller = Caller::load(ctx);
+    let commands = &ctx.framework().options().commands;
+    let entries = entries(commands);
+    let query = query.map(|q| q.trim().trim_start_matches('/').to_lowercase()).unwrap_or_default();
+
+    if query.is_empty() || query == "commands" {
+        ctx.send(poise::CreateReply::default().embed(overview(ctx, &entries, &caller))).await?;
+        return Ok(());
+    }
+    if query == "features" {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for e in entries.iter().filter(|e| e.command.subcommands.is_empty() && usable(ctx, e, &caller)) {
+            match counts.iter_mut().find(|(c, _)| *c == e.category) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((e.category, 1)),
+            }
+        }
+        let lines: Vec<String> = counts.iter().map(|(c, n)| format!("**{}** - {} command(s)", c, n)).collect();
+        let embed = serenity::CreateEmbed::default()
+            .title("Features")
+            .description(lines.join("\n"))
+            .footer(serenity::CreateEmbedFooter::new("Use /help <feature> to list its commands."))
+            .color(COLOR);
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let command = entries.iter().find(|e| e.path.to_lowercase() == query);
+    if let Some(entry) = command.filter(|e| usable(ctx, e, &caller)) {
+        ctx.send(poise::CreateReply::default().embed(details(ctx, entry))).await?;
+        return Ok(());
+    }
+    let feature = entries.iter().map(|e| e.category).find(|c| c.to_lowercase() == query);
+    if let Some(feature) = feature {
+        let pages = feature_pages(ctx, &entries, &caller, feature);
+        if pages.is_empty() {
+            ctx.say(format!("You can't use any {} commands here.", feature)).await?;
+        } else {
+            paginate_embeds(ctx, pages).await?;
+        }
+        return Ok(());
+    }
+    ctx.say(format!("There is no command or feature called `{}` that you can use here.", query)).await?;
+    Ok(())
+}
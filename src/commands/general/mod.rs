@@ -1,7 +1,8 @@
 mod ping;
-mod help;
+pub mod help;
 mod info;
-mod enable;
-mod disable;
+pub mod enable;
+pub mod disable;
 mod config;
-mod permissions;
\ No newline at end of file
+mod permissions;
+pub(crate) mod features;
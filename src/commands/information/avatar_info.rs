@@ -1 +1,32 @@
-// Displays a user's avatar.
\ No newline at end of file
+// Displays a user's avatar.
+// avatar [user]
+
+use super::media::{show, MediaVariant};
+use poise::serenity_prelude as serenity;
+
+/// Shows someone's avatar, with buttons for the global and server avatar.
+#[poise::command(slash_command, prefix_command, rename = "avatar")]
+pub async fn avatar_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User (default: you)"] user: Option<serenity::User>,
+) -> Result<(), crate::Error> {
+    let user = user.unwrap_or_else(|| ctx.author().clone());
+    let mut variants = Vec::new();
+    match user.avatar {
+        Some(hash) => variants.push(MediaVariant::cdn("Global", &format!("avatars/{}", user.id), &hash.to_string())),
+        None => variants.push(MediaVariant::fixed("Global", user.default_avatar_url())),
+    }
+    if let Some(guild_id) = ctx.guild_id() {
+        let cached = ctx.cache().guild(guild_id).and_then(|g| g.members.get(&user.id).map(|m| m.avatar));
+        let avatar = match cached {
+            Some(avatar) => avatar,
+            // Users who aren't in the server simply have no server avatar
+            None => guild_id.member(ctx, user.id).await.ok().and_then(|m| m.avatar),
+        };
+        if let Some(hash) = avatar {
+            let path = format!("guilds/{}/users/{}/avatars", guild_id, user.id);
+            variants.push(MediaVariant::cdn("Server", &path, &hash.to_string()));
+        }
+    }
+    show(ctx, format!("{}'s avatar", user.name), variants, "").await
+}
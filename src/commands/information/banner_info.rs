@@ -1 +1,26 @@
-// Displays a user's banner.
\ No newline at end of file
+// Displays a user's banner.
+// banner [user]
+
+use super::media::{show, MediaVariant};
+use poise::serenity_prelude as serenity;
+
+/// Shows someone's profile banner.
+#[poise::command(slash_command, prefix_command, rename = "banner")]
+pub async fn banner_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User (default: you)"] user: Option<serenity::User>,
+) -> Result<(), crate::Error> {
+    let id = user.map(|u| u.id).unwrap_or(ctx.author().id);
+    // Banners are only sent when fetching the user directly
+    let user = ctx.http().get_user(id).await?;
+    let variants = user
+        .banner
+        .map(|hash| MediaVariant::cdn("Banner", &format!("banners/{}", user.id), &hash.to_string()))
+        .into_iter()
+        .collect();
+    let missing = match user.accent_colour {
+        Some(colour) => format!("{} has no banner, only the accent color #{:06X}.", user.name, colour.0),
+        None => format!("{} has no banner.", user.name),
+    };
+    show(ctx, format!("{}'s banner", user.name), variants, &missing).await
+}
@@ -1 +1,95 @@
-// Gets information about all channels / a specific channel.
\ No newline at end of file
+// Gets information about all channels / a specific channel.
+// channelinfo [channel]
+
+use super::utils::{channels, sort_channels, timestamp, yes_no, COLOR};
+use crate::commands::moderation::utils::format_duration;
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 25;
+
+pub(crate) fn kind_name(kind: serenity::ChannelType) -> &'static str {
+    match kind {
+        serenity::ChannelType::Text => "Text",
+        serenity::ChannelType::Voice => "Voice",
+        serenity::ChannelType::Category => "Category",
+        serenity::ChannelType::News => "Announcement",
+        serenity::ChannelType::Stage => "Stage",
+        serenity::ChannelType::Forum => "Forum",
+        serenity::ChannelType::PublicThread | serenity::ChannelType::PrivateThread | serenity::ChannelType::NewsThread => "Thread",
+        _ => "Other",
+    }
+}
+
+fn channel_embed(channel: &serenity::GuildChannel, parent: Option<&str>) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("#{}", channel.name))
+        .field("Channel", format!("<#{}>\n`{}`", channel.id, channel.id), true)
+        .field("Type", kind_name(channel.kind), true)
+        .field("Position", channel.position.to_string(), true)
+        .field("Created", timestamp(channel.id.created_at()), false)
+        .color(COLOR);
+    if let Some(parent) = parent {
+        embed = embed.field("Category", parent, true);
+    }
+    if let Some(topic) = channel.topic.as_deref().filter(|t| !t.is_empty()) {
+        embed = embed.description(topic);
+    }
+    match channel.kind {
+        serenity::ChannelType::Voice | serenity::ChannelType::Stage => {
+            if let Some(bitrate) = channel.bitrate {
+                embed = embed.field("Bitrate", format!("{} kbps", bitrate / 1000), true);
+            }
+            let limit = channel.user_limit.filter(|l| *l > 0).map(|l| l.to_string()).unwrap_or_else(|| "None".to_string());
+            embed = embed.field("User limit", limit, true);
+        }
+        serenity::ChannelType::Category => {}
+        _ => {
+            embed = embed.field("NSFW", yes_no(channel.nsfw), true);
+            let slow = channel.rate_limit_per_user.filter(|s| *s > 0).map(|s| format_duration(s as u64)).unwrap_or_else(|| "Off".to_string());
+            embed = embed.field("Slow mode", slow, true);
+        }
+    }
+    embed.field("Permission overwrites", channel.permission_overwrites.len().to_string(), true)
+}
+
+/// Shows a channel, or every channel of this server.
+#[poise::command(slash_command, prefix_command, rename = "channelinfo", guild_only)]
+pub async fn channel_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel (default: all channels)"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let mut all = channels(ctx.serenity_context(), guild_id).await?;
+
+    if let Some(channel) = channel {
+        if channel.guild_id != guild_id {
+            ctx.say("That channel is not part of this server.").await?;
+            return Ok(());
+        }
+        let parent = channel.parent_id.and_then(|p| all.iter().find(|c| c.id == p)).map(|c| c.name.clone());
+        ctx.send(poise::CreateReply::default().embed(channel_embed(&channel, parent.as_deref()))).await?;
+        return Ok(());
+    }
+
+    sort_channels(&mut all);
+    let lines: Vec<String> = all
+        .iter()
+        .map(|c| match c.kind {
+            serenity::ChannelType::Category => format!("**{}**", c.name.to_uppercase()),
+            kind if c.parent_id.is_some() => format!("\u{2003}<#{}> - {}", c.id, kind_name(kind)),
+            kind => format!("<#{}> - {}", c.id, kind_name(kind)),
+        })
+        .collect();
+    let pages = lines
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            serenity::CreateEmbed::default()
+                .title(format!("Channels ({})", all.iter().filter(|c| c.kind != serenity::ChannelType::Category).count()))
+                .description(chunk.join("\n"))
+                .color(COLOR)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
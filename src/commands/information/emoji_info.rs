@@ -1 +1,83 @@
-// Gets information about all emojis / a specific emoji.
\ No newline at end of file
+// Gets information about all emojis / a specific emoji.
+// emojiinfo [emoji]
+
+use super::utils::{emojis, timestamp, yes_no, COLOR};
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 20;
+
+/// Reads a custom emoji written in a message (`<:name:id>`), a raw ID or a name.
+fn find<'a>(all: &'a [serenity::Emoji], input: &str) -> Option<&'a serenity::Emoji> {
+    let input = input.trim();
+    let id = input
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .rsplit(':')
+        .next()
+        .and_then(|id| id.parse::<u64>().ok());
+    match id {
+        Some(id) => all.iter().find(|e| e.id.get() == id),
+        None => all.iter().find(|e| e.name.eq_ignore_ascii_case(input.trim_matches(':'))),
+    }
+}
+
+/// Shows a custom emoji, or every emoji of this server.
+#[poise::command(slash_command, prefix_command, rename = "emojiinfo", guild_only)]
+pub async fn emoji_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Emoji, its ID or name (default: all emojis)"] emoji: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let mut all = emojis(ctx.serenity_context(), guild_id).await?;
+
+    if let Some(input) = emoji {
+        let Some(e) = find(&all, &input) else {
+            ctx.say(format!("`{}` is not an emoji of this server.", input)).await?;
+            return Ok(());
+        };
+        let mut embed = serenity::CreateEmbed::default()
+            .title(format!(":{}:", e.name))
+            .thumbnail(e.url())
+            .field("Emoji", format!("{}\n`{}`", e, e.id), true)
+            .field("Animated", yes_no(e.animated), true)
+            .field("Managed by an integration", yes_no(e.managed), true)
+            .field("Available", yes_no(e.available), true)
+            .field("Created", timestamp(e.id.created_at()), false)
+            .field("Link", format!("[Download]({})", e.url()), true)
+            .color(COLOR);
+        if !e.roles.is_empty() {
+            let roles: Vec<String> = e.roles.iter().map(|r| format!("<@&{}>", r)).collect();
+            embed = embed.field("Limited to", roles.join(" "), false);
+        }
+        if let Some(user) = &e.user {
+            embed = embed.field("Added by", format!("<@{}>", user.id), true);
+        }
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    if all.is_empty() {
+        ctx.say("This server has no custom emojis.").await?;
+        return Ok(());
+    }
+    all.sort_by(|a, b| a.animated.cmp(&b.animated).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    let animated = all.iter().filter(|e| e.animated).count();
+    let summary = format!("{} static, {} animated", all.len() - animated, animated);
+    let pages = all
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|e| format!("{} `:{}:`{}", e, e.name, if e.animated { " (animated)" } else { "" }))
+                .collect();
+            serenity::CreateEmbed::default()
+                .title(format!("Emojis ({})", all.len()))
+                .description(lines.join("\n"))
+                .footer(serenity::CreateEmbedFooter::new(&summary))
+                .color(COLOR)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
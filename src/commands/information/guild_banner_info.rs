@@ -1 +1,17 @@
-// Displays a guild's banner.
\ No newline at end of file
+// Displays a guild's banner.
+// guildbanner
+
+use super::media::{show, GuildImages, MediaVariant};
+
+/// Shows this server's banner.
+#[poise::command(slash_command, prefix_command, rename = "guildbanner", guild_only)]
+pub async fn guild_banner_info(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let images = GuildImages::load(ctx.serenity_context(), guild_id).await?;
+    let variants = images
+        .banner
+        .map(|hash| MediaVariant::cdn("Banner", &format!("banners/{}", guild_id), &hash))
+        .into_iter()
+        .collect();
+    show(ctx, format!("{}'s banner", images.name), variants, "This server has no banner.").await
+}
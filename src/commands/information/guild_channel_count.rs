@@ -1 +1,32 @@
-// Displays the guild's channel count.
\ No newline at end of file
+// Displays the guild's channel count.
+// channelcount
+
+use super::channel_info::kind_name;
+use super::utils::{channels, COLOR};
+use poise::serenity_prelude as serenity;
+use std::collections::BTreeMap;
+
+/// Shows how many channels of each type this server has.
+#[poise::command(slash_command, prefix_command, rename = "channelcount", guild_only)]
+pub async fn guild_channel_count(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let all = channels(ctx.serenity_context(), guild_id).await?;
+    let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+    for c in &all {
+        *kinds.entry(kind_name(c.kind)).or_default() += 1;
+    }
+    let categories = kinds.get("Category").copied().unwrap_or(0);
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("{} channels", all.len() - categories))
+        .color(COLOR);
+    for (kind, count) in kinds {
+        embed = embed.field(kind, count.to_string(), true);
+    }
+    // Threads aren't part of the channel list, only active ones are cached
+    let threads = ctx.cache().guild(guild_id).map(|g| g.threads.len());
+    if let Some(threads) = threads.filter(|t| *t > 0) {
+        embed = embed.field("Active threads", threads.to_string(), true);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1 +1,17 @@
-// Displays a guild's icon.
\ No newline at end of file
+// Displays a guild's icon.
+// guildicon
+
+use super::media::{show, GuildImages, MediaVariant};
+
+/// Shows this server's icon.
+#[poise::command(slash_command, prefix_command, rename = "guildicon", guild_only)]
+pub async fn guild_icon_info(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let images = GuildImages::load(ctx.serenity_context(), guild_id).await?;
+    let variants = images
+        .icon
+        .map(|hash| MediaVariant::cdn("Icon", &format!("icons/{}", guild_id), &hash))
+        .into_iter()
+        .collect();
+    show(ctx, format!("{}'s icon", images.name), variants, "This server has no icon.").await
+}
@@ -1 +1,121 @@
-// Gets information about a guild.
\ No newline at end of file
+// Gets information about a guild.
+// guildinfo
+
+use super::utils::{join_limited, timestamp, COLOR};
+use poise::serenity_prelude as serenity;
+
+/// What the guild embed shows, read from the cached guild or from HTTP.
+struct Summary {
+    id: serenity::GuildId,
+    name: String,
+    description: Option<String>,
+    owner_id: serenity::UserId,
+    icon: Option<String>,
+    banner: Option<String>,
+    members: Option<u64>,
+    online: Option<u64>,
+    boosts: u64,
+    tier: serenity::PremiumTier,
+    verification: serenity::VerificationLevel,
+    roles: usize,
+    emojis: (usize, usize),
+    stickers: usize,
+    /// Text, voice and category channels, when known.
+    channels: Option<(usize, usize, usize)>,
+    features: Vec<String>,
+    vanity: Option<String>,
+}
+
+// Guild and PartialGuild share these fields but no trait
+macro_rules! summary {
+    ($g:expr, $members:expr, $online:expr, $channels:expr) => {
+        Summary {
+            id: $g.id,
+            name: $g.name.clone(),
+            description: $g.description.clone(),
+            owner_id: $g.owner_id,
+            icon: $g.icon_url(),
+            banner: $g.banner_url(),
+            members: $members,
+            online: $online,
+            boosts: $g.premium_subscription_count.unwrap_or(0),
+            tier: $g.premium_tier,
+            verification: $g.verification_level,
+            roles: $g.roles.len(),
+            emojis: ($g.emojis.values().filter(|e| !e.animated).count(), $g.emojis.values().filter(|e| e.animated).count()),
+            stickers: $g.stickers.len(),
+            channels: $channels,
+            features: $g.features.clone(),
+            vanity: $g.vanity_url_code.clone(),
+        }
+    };
+}
+
+fn count_channels<'a>(channels: impl Iterator<Item = &'a serenity::GuildChannel>) -> (usize, usize, usize) {
+    let (mut text, mut voice, mut categories) = (0, 0, 0);
+    for c in channels {
+        match c.kind {
+            serenity::ChannelType::Category => categories += 1,
+            serenity::ChannelType::Voice | serenity::ChannelType::Stage => voice += 1,
+            _ => text += 1,
+        }
+    }
+    (text, voice, categories)
+}
+
+async fn load(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Summary, crate::Error> {
+    let cached = ctx.cache.guild(guild_id).map(|g| {
+        let online = g.presences.values().filter(|p| p.status != serenity::OnlineStatus::Offline).count() as u64;
+        summary!(g, Some(g.member_count), (online > 0).then_some(online), Some(count_channels(g.channels.values())))
+    });
+    if let Some(s) = cached {
+        return Ok(s);
+    }
+    let g = ctx.http.get_guild_with_counts(guild_id).await?;
+    let channels = guild_id.channels(&ctx.http).await.ok().map(|c| count_channels(c.values()));
+    Ok(summary!(g, g.approximate_member_count, g.approximate_presence_count, channels))
+}
+
+/// Shows information about this server.
+#[poise::command(slash_command, prefix_command, rename = "guildinfo", guild_only)]
+pub async fn guild_info(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let s = load(ctx.serenity_context(), guild_id).await?;
+
+    let count = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_else(|| "Unknown".to_string());
+    let mut embed = serenity::CreateEmbed::default()
+        .title(&s.name)
+        .field("Owner", format!("<@{}>", s.owner_id), true)
+        .field("ID", format!("`{}`", s.id), true)
+        .field("Created", timestamp(s.id.created_at()), false)
+        .field("Members", count(s.members), true)
+        .field("Online", count(s.online), true)
+        .field("Boosts", format!("{} (level {})", s.boosts, u8::from(s.tier)), true)
+        .field("Verification", format!("{:?}", s.verification), true)
+        .field("Roles", s.roles.to_string(), true)
+        .field("Emojis", format!("{} static, {} animated", s.emojis.0, s.emojis.1), true)
+        .field("Stickers", s.stickers.to_string(), true)
+        .color(COLOR);
+    if let Some((text, voice, categories)) = s.channels {
+        embed = embed.field("Channels", format!("{} text, {} voice, {} categories", text, voice, categories), true);
+    }
+    if let Some(code) = &s.vanity {
+        embed = embed.field("Vanity URL", format!("discord.gg/{}", code), true);
+    }
+    if let Some(d) = &s.description {
+        embed = embed.description(d);
+    }
+    if let Some(icon) = &s.icon {
+        embed = embed.thumbnail(icon);
+    }
+    if let Some(banner) = &s.banner {
+        embed = embed.image(banner);
+    }
+    if !s.features.is_empty() {
+        let mut features: Vec<String> = s.features.iter().map(|f| format!("`{}`", f.to_lowercase())).collect();
+        features.sort();
+        embed = embed.field("Features", join_limited(&features, ", ", 1024), false);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1 +1,59 @@
-// Displays the guild's member count.
\ No newline at end of file
+// Displays the guild's member count.
+// membercount
+
+use super::member_stats::{today, MemberCounts};
+use super::utils::COLOR;
+use crate::data::member_stats::store::MemberStatsStore;
+use poise::serenity_prelude as serenity;
+
+const PERIODS: [i64; 3] = [7, 30, 90];
+
+fn signed(n: i64) -> String {
+    if n > 0 { format!("+{}", n) } else { n.to_string() }
+}
+
+/// Growth over the last `days` days from the daily stats, or why it isn't known.
+fn growth(store: &MemberStatsStore, guild_id: u64, total: u64, days: i64) -> Result<String, crate::Error> {
+    let start = today() - days;
+    let history = store.days(guild_id, start, today())?;
+    // The oldest count in the period, which is the start of the period unless tracking began later
+    let Some(base) = history.iter().find(|d| d.members.is_some() && d.day < today()) else {
+        return Ok("Not tracked yet".to_string());
+    };
+    let base_members = base.members.unwrap_or(0);
+    let change = total as i64 - base_members as i64;
+    let joins: u64 = history.iter().filter(|d| d.day > base.day).map(|d| d.joins).sum();
+    let leaves: u64 = history.iter().filter(|d| d.day > base.day).map(|d| d.leaves).sum();
+    let mut out = signed(change);
+    if base_members > 0 {
+        out.push_str(&format!(" ({:+.1}%)", change as f64 * 100.0 / base_members as f64));
+    }
+    out.push_str(&format!("\n{} joined, {} left", joins, leaves));
+    if base.day > start {
+        out.push_str(&format!("\nsince <t:{}:d>", base.day * 86_400));
+    }
+    Ok(out)
+}
+
+/// Shows how many members this server has and how that changed lately.
+#[poise::command(slash_command, prefix_command, rename = "membercount", guild_only)]
+pub async fn guild_member_count(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let counts = MemberCounts::load(ctx.serenity_context(), guild_id).await?;
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("{} members", counts.name))
+        .field("Members", counts.total.to_string(), true)
+        .color(COLOR);
+    if let (Some(humans), Some(bots)) = (counts.humans, counts.bots) {
+        embed = embed.field("Humans", humans.to_string(), true).field("Bots", bots.to_string(), true);
+    }
+    if let Some(online) = counts.online {
+        embed = embed.field("Online", online.to_string(), true);
+    }
+    let store = MemberStatsStore::open(&ctx.data().db)?;
+    for days in PERIODS {
+        embed = embed.field(format!("Last {} days", days), growth(&store, guild_id.get(), counts.total, days)?, true);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1 +1,21 @@
-// Displays the guild's discovery splash.
\ No newline at end of file
+// Displays the guild's discovery splash.
+// guildsplash
+
+use super::media::{show, GuildImages, MediaVariant};
+
+/// Shows this server's invite splash and discovery splash.
+#[poise::command(slash_command, prefix_command, rename = "guildsplash", guild_only)]
+pub async fn guild_splash_info(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let images = GuildImages::load(ctx.serenity_context(), guild_id).await?;
+    // Either splash needs a boost level or discovery, so any of them may be missing
+    let mut variants = Vec::new();
+    if let Some(hash) = &images.splash {
+        variants.push(MediaVariant::cdn("Invite splash", &format!("splashes/{}", guild_id), hash));
+    }
+    if let Some(hash) = &images.discovery_splash {
+        variants.push(MediaVariant::cdn("Discovery splash", &format!("discovery-splashes/{}", guild_id), hash));
+    }
+    let title = format!("{}'s splash", images.name);
+    show(ctx, title, variants, "This server has no invite or discovery splash.").await
+}
@@ -0,0 +1,219 @@
+// The image viewer shared by the avatar, banner, icon and splash commands: buttons switch between
+// variants (like global and server avatars), menus pick the size and format.
+
+use super::utils::COLOR;
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const CDN: &str = "https://cdn.discordapp.com";
+const SIZES: [u16; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+const DEFAULT_SIZE: u16 = 1024;
+
+/// One image the viewer can switch to.
+pub(crate) struct MediaVariant {
+    pub label: String,
+    /// CDN URL without extension and size, or the full URL of a fixed image.
+    pub base: String,
+    pub animated: bool,
+    /// Default avatars exist only as one PNG, so sizes and formats don't apply.
+    pub fixed: bool,
+}
+
+impl MediaVariant {
+    /// An image on Discord's CDN, e.g. `avatars/<user id>` with its hash.
+    pub(crate) fn cdn(label: &str, path: &str, hash: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            base: format!("{}/{}/{}", CDN, path, hash),
+            animated: hash.starts_with("a_"),
+            fixed: false,
+        }
+    }
+
+    pub(crate) fn fixed(label: &str, url: String) -> Self {
+        Self { label: label.to_string(), base: url, animated: false, fixed: true }
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        if self.animated { &["gif", "png", "webp", "jpg"] } else { &["png", "webp", "jpg"] }
+    }
+
+    fn url(&self, format: &str, size: u16) -> String {
+        if self.fixed { self.base.clone() } else { format!("{}.{}?size={}", self.base, format, size) }
+    }
+}
+
+struct Viewer {
+    title: String,
+    variants: Vec<MediaVariant>,
+    current: usize,
+    size: u16,
+    format: &'static str,
+}
+
+impl Viewer {
+    fn variant(&self) -> &MediaVariant {
+        &self.variants[self.current]
+    }
+
+    fn select(&mut self, index: usize) {
+        self.current = index;
+        let formats = self.variant().formats();
+        // Animated images start as GIF, switching to a still image drops the GIF format
+        if !formats.contains(&self.format) || self.variant().animated {
+            self.format = formats[0];
+        }
+    }
+
+    fn embed(&self) -> serenity::CreateEmbed {
+        let v = self.variant();
+        let url = v.url(self.format, self.size);
+        let title = if self.variants.len() > 1 { format!("{} - {}", self.title, v.label) } else { self.title.clone() };
+        let downloads = if v.fixed {
+            format!("[Download]({})", url)
+        } else {
+            let links: Vec<String> = v.formats().iter().map(|f| format!("[{}]({})", f.to_uppercase(), v.url(f, self.size))).collect();
+            format!("Download: {}", links.join(" | "))
+        };
+        let mut embed = serenity::CreateEmbed::default().title(title).description(downloads).image(url).color(COLOR);
+        if !v.fixed {
+            embed = embed.footer(serenity::CreateEmbedFooter::new(format!("{}px, {}", self.size, self.format.to_uppercase())));
+        }
+        embed
+    }
+
+    fn components(&self, id: u64) -> Vec<serenity::CreateActionRow> {
+        let v = self.variant();
+        let mut rows = Vec::new();
+        if self.variants.len() > 1 {
+            let buttons = self
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    serenity::CreateButton::new(format!("{}_variant_{}", id, i))
+                        .label(&variant.label)
+                        .style(if i == self.current { serenity::ButtonStyle::Primary } else { serenity::ButtonStyle::Secondary })
+                        .disabled(i == self.current)
+                })
+                .collect();
+            rows.push(serenity::CreateActionRow::Buttons(buttons));
+        }
+        if !v.fixed {
+            let sizes = SIZES
+                .iter()
+                .map(|s| serenity::CreateSelectMenuOption::new(format!("{}px", s), s.to_string()).default_selection(*s == self.size))
+                .collect();
+            rows.push(serenity::CreateActionRow::SelectMenu(serenity::CreateSelectMenu::new(
+                format!("{}_size", id),
+                serenity::CreateSelectMenuKind::String { options: sizes },
+            )));
+            let formats = v
+                .formats()
+                .iter()
+                .map(|f| serenity::CreateSelectMenuOption::new(f.to_uppercase(), *f).default_selection(*f == self.format))
+                .collect();
+            rows.push(serenity::CreateActionRow::SelectMenu(serenity::CreateSelectMenu::new(
+                format!("{}_format", id),
+                serenity::CreateSelectMenuKind::String { options: formats },
+            )));
+        }
+        rows.push(serenity::CreateActionRow::Buttons(vec![
+            serenity::CreateButton::new_link(v.url(self.format, self.size)).label("Open original"),
+        ]));
+        rows
+    }
+}
+
+/// Shows images in the viewer. Replies with `missing` when there is no image at all. Only the
+/// command author can use the controls, which are removed after ten minutes without use.
+pub(crate) async fn show(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    title: String,
+    variants: Vec<MediaVariant>,
+    missing: &str,
+) -> Result<(), crate::Error> {
+    if variants.is_empty() {
+        ctx.say(missing).await?;
+        return Ok(());
+    }
+    let mut viewer = Viewer { title, variants, current: 0, size: DEFAULT_SIZE, format: "png" };
+    viewer.select(0);
+
+    let id = ctx.id();
+    let reply = ctx
+        .send(poise::CreateReply::default().embed(viewer.embed()).components(viewer.components(id)))
+        .await?;
+    while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.starts_with(&format!("{}_", id)))
+        .timeout(Duration::from_secs(600))
+        .await
+    {
+        let action = press.data.custom_id.trim_start_matches(&format!("{}_", id)).to_string();
+        let value = match &press.data.kind {
+            serenity::ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+            _ => None,
+        };
+        match (action.as_str(), value) {
+            ("size", Some(v)) => viewer.size = v.parse().unwrap_or(DEFAULT_SIZE),
+            ("format", Some(v)) => {
+                if let Some(f) = viewer.variant().formats().iter().find(|f| **f == v) {
+                    viewer.format = f;
+                }
+            }
+            (a, _) => {
+                if let Some(i) = a.strip_prefix("variant_").and_then(|i| i.parse::<usize>().ok()).filter(|i| *i < viewer.variants.len()) {
+                    viewer.select(i);
+                }
+            }
+        }
+        press
+            .create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new().embed(viewer.embed()).components(viewer.components(id)),
+                ),
+            )
+            .await?;
+    }
+
+    // Keeps the link to the image, the other controls stop working
+    let link = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new_link(viewer.variant().url(viewer.format, viewer.size)).label("Open original"),
+    ]);
+    reply.edit(ctx, poise::CreateReply::default().embed(viewer.embed()).components(vec![link])).await?;
+    Ok(())
+}
+
+/// The image hashes of a guild, from the cache or the API.
+pub(crate) struct GuildImages {
+    pub name: String,
+    pub icon: Option<String>,
+    pub banner: Option<String>,
+    pub splash: Option<String>,
+    pub discovery_splash: Option<String>,
+}
+
+impl GuildImages {
+    pub(crate) async fn load(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Self, crate::Error> {
+        let cached = ctx.cache.guild(guild_id).map(|g| Self {
+            name: g.name.clone(),
+            icon: g.icon.map(|h| h.to_string()),
+            banner: g.banner.clone(),
+            splash: g.splash.map(|h| h.to_string()),
+            discovery_splash: g.discovery_splash.map(|h| h.to_string()),
+        });
+        if let Some(images) = cached {
+            return Ok(images);
+        }
+        let g = ctx.http.get_guild(guild_id).await?;
+        Ok(Self {
+            name: g.name,
+            icon: g.icon.map(|h| h.to_string()),
+            banner: g.banner,
+            splash: g.splash.map(|h| h.to_string()),
+            discovery_splash: g.discovery_splash.map(|h| h.to_string()),
+        })
+    }
+}
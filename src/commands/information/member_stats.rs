@@ -0,0 +1,99 @@
+// Records joins, leaves and daily member counts, and keeps the stat channels' names up to date.
+
+use crate::data::member_stats::store::{MemberStatsStore, StatChannel, StatKind};
+use poise::serenity_prelude as serenity;
+
+/// Current member numbers of a guild.
+pub(crate) struct MemberCounts {
+    pub name: String,
+    pub total: u64,
+    /// Humans and bots can only be told apart when every member is cached.
+    pub humans: Option<u64>,
+    pub bots: Option<u64>,
+    pub online: Option<u64>,
+}
+
+impl MemberCounts {
+    pub(crate) fn cached(cache: &serenity::Cache, guild_id: serenity::GuildId) -> Option<Self> {
+        let g = cache.guild(guild_id)?;
+        let bots = (g.members.len() as u64 >= g.member_count).then(|| g.members.values().filter(|m| m.user.bot).count() as u64);
+        let online = g
+            .presences
+            .values()
+            .filter(|p| !matches!(p.status, serenity::OnlineStatus::Offline | serenity::OnlineStatus::Invisible))
+            .count() as u64;
+        Some(Self {
+            name: g.name.clone(),
+            total: g.member_count,
+            humans: bots.map(|b| g.member_count.saturating_sub(b)),
+            bots,
+            online: Some(online),
+        })
+    }
+
+    pub(crate) async fn load(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Self, crate::Error> {
+        if let Some(counts) = Self::cached(&ctx.cache, guild_id) {
+            return Ok(counts);
+        }
+        let g = ctx.http.get_guild_with_counts(guild_id).await?;
+        Ok(Self {
+            name: g.name,
+            total: g.approximate_member_count.unwrap_or(0),
+            humans: None,
+            bots: None,
+            online: g.approximate_presence_count,
+        })
+    }
+
+    pub(crate) fn get(&self, kind: StatKind) -> Option<u64> {
+        match kind {
+            StatKind::Members => Some(self.total),
+            StatKind::Humans => self.humans,
+            StatKind::Bots => self.bots,
+            StatKind::Online => self.online,
+        }
+    }
+}
+
+/// Days since the Unix epoch, in UTC.
+pub(crate) fn today() -> i64 {
+    chrono::Utc::now().timestamp().div_euclid(86_400)
+}
+
+/// Saves today's counts of a guild, overwriting the ones saved earlier today.
+pub(crate) fn record_counts(store: &MemberStatsStore, guild_id: u64, counts: &MemberCounts) -> Result<(), crate::Error> {
+    store.update_day(guild_id, today(), |day| {
+        day.members = Some(counts.total);
+        // Keeps the last full count of the day when the cache isn't complete anymore
+        if counts.bots.is_some() {
+            day.humans = counts.humans;
+            day.bots = counts.bots;
+        }
+    })
+}
+
+pub async fn handle_join(member: &serenity::Member, data: &crate::Data) -> Result<(), crate::Error> {
+    let store = MemberStatsStore::open(&data.db)?;
+    store.update_day(member.guild_id.get(), today(), |day| day.joins += 1)
+}
+
+pub async fn handle_leave(guild_id: serenity::GuildId, data: &crate::Data) -> Result<(), crate::Error> {
+    let store = MemberStatsStore::open(&data.db)?;
+    store.update_day(guild_id.get(), today(), |day| day.leaves += 1)
+}
+
+pub(crate) fn kind_name(kind: StatKind) -> &'static str {
+    match kind {
+        StatKind::Members => "Members",
+        StatKind::Humans => "Humans",
+        StatKind::Bots => "Bots",
+        StatKind::Online => "Online",
+    }
+}
+
+/// The name a stat channel should have, None while its count isn't known.
+pub(crate) fn channel_name(channel: &StatChannel, counts: &MemberCounts) -> Option<String> {
+    let count = counts.get(channel.kind)?;
+    let name: String = channel.format.replace("{count}", &count.to_string()).chars().take(100).collect();
+    Some(name)
+}
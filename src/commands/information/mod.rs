@@ -0,0 +1,18 @@
+pub mod avatar_info;
+pub mod banner_info;
+pub mod channel_info;
+pub mod emoji_info;
+pub mod guild_banner_info;
+pub mod guild_channel_count;
+pub mod guild_icon_info;
+pub mod guild_info;
+pub mod guild_member_count;
+pub mod guild_splash_info;
+pub(crate) mod media;
+pub mod member_stats;
+pub mod role_info;
+pub mod stat_channels;
+pub mod sticker_info;
+pub mod sticker_pack_info;
+pub mod user_info;
+pub(crate) mod utils;
@@ -1 +1,91 @@
-// Gets information about all roles / a specific role.
\ No newline at end of file
+// Gets information about all roles / a specific role.
+// roleinfo [role]
+
+use super::utils::{cached_members, join_limited, roles, timestamp, yes_no, COLOR};
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+
+const PAGE_SIZE: usize = 20;
+
+/// Shows a role, or every role of this server with its member count.
+#[poise::command(slash_command, prefix_command, rename = "roleinfo", guild_only)]
+pub async fn role_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Role (default: all roles)"] role: Option<serenity::Role>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    ctx.defer().await?;
+    let (members, total) = cached_members(ctx.serenity_context(), guild_id);
+    let complete = members.len() as u64 >= total;
+    let mut counts: HashMap<serenity::RoleId, usize> = HashMap::new();
+    for m in &members {
+        for r in &m.roles {
+            *counts.entry(*r).or_default() += 1;
+        }
+    }
+
+    if let Some(role) = role {
+        // Everyone has @everyone without it being listed on the member
+        let count = if role.id.get() == guild_id.get() {
+            total.to_string()
+        } else {
+            let count = counts.get(&role.id).copied().unwrap_or(0);
+            if complete { count.to_string() } else { format!("{} of {} cached members", count, members.len()) }
+        };
+        let permissions: Vec<String> = if role.permissions.administrator() {
+            vec!["`Administrator` (all permissions)".to_string()]
+        } else {
+            role.permissions.iter_names().map(|(name, _)| format!("`{}`", name.to_lowercase())).collect()
+        };
+        let mut embed = serenity::CreateEmbed::default()
+            .title(&role.name)
+            .field("Role", format!("<@&{}>\n`{}`", role.id, role.id), true)
+            .field("Members", count, true)
+            .field("Color", format!("#{:06X}", role.colour.0), true)
+            .field("Position", role.position.to_string(), true)
+            .field("Shown separately", yes_no(role.hoist), true)
+            .field("Mentionable", yes_no(role.mentionable), true)
+            .field("Managed by an integration", yes_no(role.managed), true)
+            .field("Created", timestamp(role.id.created_at()), false)
+            .field("Permissions", join_limited(&permissions, ", ", 1024), false)
+            .color(if role.colour.0 == 0 { COLOR } else { role.colour.0 });
+        if let Some(icon) = role.icon_url() {
+            embed = embed.thumbnail(icon);
+        }
+        if let Some(emoji) = &role.unicode_emoji {
+            embed = embed.field("Emoji", emoji, true);
+        }
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    let mut all = roles(ctx.serenity_context(), guild_id).await?;
+    all.retain(|r| r.id.get() != guild_id.get());
+    all.sort_by_key(|r| std::cmp::Reverse(r.position));
+    if all.is_empty() {
+        ctx.say("This server has no roles besides @everyone.").await?;
+        return Ok(());
+    }
+    let footer = if complete {
+        format!("{} members in total", total)
+    } else {
+        format!("Counted from {} of {} members, the others aren't cached", members.len(), total)
+    };
+    let pages = all
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|r| format!("<@&{}> - {} member(s)", r.id, counts.get(&r.id).copied().unwrap_or(0)))
+                .collect();
+            serenity::CreateEmbed::default()
+                .title(format!("Roles ({})", all.len()))
+                .description(lines.join("\n"))
+                .footer(serenity::CreateEmbedFooter::new(&footer))
+                .color(COLOR)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+// Voice channels whose names show live member counts.
+// statchannels add <members|humans|bots|online> [name]
+// statchannels remove <channel>
+// statchannels list
+
+use super::member_stats::{channel_name, kind_name, MemberCounts};
+use super::utils::COLOR;
+use crate::data::member_stats::store::{MemberStatsStore, StatChannel, StatKind};
+use poise::serenity_prelude as serenity;
+
+// Discord allows renaming a channel twice every ten minutes
+pub(crate) const RENAME_EVERY: i64 = 300;
+const MAX_CHANNELS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Counter {
+    Members,
+    Humans,
+    Bots,
+    Online,
+}
+
+impl From<Counter> for StatKind {
+    fn from(c: Counter) -> Self {
+        match c {
+            Counter::Members => StatKind::Members,
+            Counter::Humans => StatKind::Humans,
+            Counter::Bots => StatKind::Bots,
+            Counter::Online => StatKind::Online,
+        }
+    }
+}
+
+/// Keeps voice channels named after the member counts of this server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "statchannels",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    subcommands("add", "remove", "list"),
+    subcommand_required
+)]
+pub async fn stat_channels(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Creates a voice channel showing a count. Nobody can join it.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "What to count"] counter: Counter,
+    #[rest]
+    #[description = "Channel name, {count} is replaced by the number (default: \"<Counter>: {count}\")"]
+    name: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let kind = StatKind::from(counter);
+    let format = name.map(|n| n.trim().to_string()).unwrap_or_else(|| format!("{}: {{count}}", kind_name(kind)));
+    if !format.contains("{count}") {
+        ctx.say("The name has to contain `{count}`.").await?;
+        return Ok(());
+    }
+    if format.chars().count() > 90 {
+        ctx.say("The name can't be longer than 90 characters.").await?;
+        return Ok(());
+    }
+    let store = MemberStatsStore::open(&ctx.data().db)?;
+    let mut channels = store.channels(guild_id.get())?;
+    if channels.len() >= MAX_CHANNELS {
+        ctx.say(format!("This server already has {} stat channels, remove one first.", MAX_CHANNELS)).await?;
+        return Ok(());
+    }
+
+    let counts = MemberCounts::load(ctx.serenity_context(), guild_id).await?;
+    let mut stat = StatChannel { channel_id: 0, kind, format, renamed_at: 0 };
+    let Some(initial) = channel_name(&stat, &counts) else {
+        ctx.say(format!(
+            "{} can't be counted right now since not every member is cached. Try again later.",
+            kind_name(kind)
+        ))
+        .await?;
+        return Ok(());
+    };
+    let everyone = serenity::PermissionOverwrite {
+        allow: serenity::Permissions::VIEW_CHANNEL,
+        deny: serenity::Permissions::CONNECT,
+        kind: serenity::PermissionOverwriteType::Role(serenity::RoleId::new(guild_id.get())),
+    };
+    let channel = guild_id
+        .create_channel(
+            ctx.http(),
+            serenity::CreateChannel::new(initial)
+                .kind(serenity::ChannelType::Voice)
+                .position(0)
+                .permissions(vec![everyone])
+                .audit_log_reason("Stat channel"),
+        )
+        .await?;
+    stat.channel_id = channel.id.get();
+    stat.renamed_at = chrono::Utc::now().timestamp();
+    channels.push(stat);
+    store.set_channels(guild_id.get(), &channels)?;
+    ctx.say(format!(
+        "Created <#{}>. Its name updates every few minutes, Discord doesn't allow renaming channels more often.",
+        channel.id
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Stops updating a stat channel. The channel itself is kept.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Stat channel"]
+    #[channel_types("Voice")]
+    channel: serenity::GuildChannel,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = MemberStatsStore::open(&ctx.data().db)?;
+    let mut channels = store.channels(guild_id.get())?;
+    let before = channels.len();
+    channels.retain(|c| c.channel_id != channel.id.get());
+    if channels.len() == before {
+        ctx.say(format!("<#{}> isn't a stat channel.", channel.id)).await?;
+        return Ok(());
+    }
+    store.set_channels(guild_id.get(), &channels)?;
+    ctx.say(format!("<#{}> isn't updated anymore, delete it if you don't need it.", channel.id)).await?;
+    Ok(())
+}
+
+/// Lists the stat channels of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let channels = MemberStatsStore::open(&ctx.data().db)?.channels(guild_id.get())?;
+    if channels.is_empty() {
+        ctx.say("This server has no stat channels.").await?;
+        return Ok(());
+    }
+    let lines: Vec<String> = channels
+        .iter()
+        .map(|c| format!("<#{}> - {}, named `{}`", c.channel_id, kind_name(c.kind), c.format))
+        .collect();
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Stat channels ({})", channels.len()))
+        .description(lines.join("\n"))
+        .color(COLOR);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
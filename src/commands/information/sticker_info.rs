@@ -1 +1,88 @@
-// Gets information about all stickers / a specific sticker.
\ No newline at end of file
+// Gets information about all stickers / a specific sticker.
+// stickerinfo [sticker]
+
+use super::utils::{stickers, timestamp, yes_no, COLOR};
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 20;
+
+pub(crate) fn format_name(format: serenity::StickerFormatType) -> &'static str {
+    match format {
+        serenity::StickerFormatType::Png => "PNG",
+        serenity::StickerFormatType::Apng => "APNG",
+        serenity::StickerFormatType::Lottie => "Lottie",
+        serenity::StickerFormatType::Gif => "GIF",
+        _ => "Unknown",
+    }
+}
+
+/// Details of one sticker, shared with the sticker pack command.
+pub(crate) fn sticker_embed(sticker: &serenity::Sticker) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(&sticker.name)
+        .field("ID", format!("`{}`", sticker.id), true)
+        .field("Format", format_name(sticker.format_type), true)
+        .field("Available", yes_no(sticker.available), true)
+        .field("Created", timestamp(sticker.id.created_at()), false)
+        .color(COLOR);
+    if let Some(d) = sticker.description.as_deref().filter(|d| !d.is_empty()) {
+        embed = embed.description(d);
+    }
+    if !sticker.tags.is_empty() {
+        embed = embed.field("Related emoji", sticker.tags.join(", "), true);
+    }
+    if let Some(user) = &sticker.user {
+        embed = embed.field("Added by", format!("<@{}>", user.id), true);
+    }
+    // Lottie stickers have no image Discord can show in an embed
+    if let Some(url) = sticker.image_url() {
+        embed = embed.thumbnail(&url).field("Link", format!("[Download]({})", url), true);
+    }
+    embed
+}
+
+/// Shows a sticker, or every sticker of this server.
+#[poise::command(slash_command, prefix_command, rename = "stickerinfo", guild_only)]
+pub async fn sticker_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Sticker name or ID (default: all stickers)"] sticker: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let mut all = stickers(ctx.serenity_context(), guild_id).await?;
+
+    if let Some(input) = sticker {
+        let input = input.trim();
+        let found = match input.parse::<u64>() {
+            Ok(id) => all.iter().find(|s| s.id.get() == id),
+            Err(_) => all.iter().find(|s| s.name.eq_ignore_ascii_case(input)),
+        };
+        let Some(s) = found else {
+            ctx.say(format!("`{}` is not a sticker of this server.", input)).await?;
+            return Ok(());
+        };
+        ctx.send(poise::CreateReply::default().embed(sticker_embed(s))).await?;
+        return Ok(());
+    }
+
+    if all.is_empty() {
+        ctx.say("This server has no stickers.").await?;
+        return Ok(());
+    }
+    all.sort_by_key(|s| s.name.to_lowercase());
+    let pages = all
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|s| format!("**{}** `{}` - {}", s.name, s.id, format_name(s.format_type)))
+                .collect();
+            serenity::CreateEmbed::default()
+                .title(format!("Stickers ({})", all.len()))
+                .description(lines.join("\n"))
+                .color(COLOR)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
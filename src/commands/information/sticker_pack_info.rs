@@ -1 +1,63 @@
-// Gets information about all sticker packs / a specific sticker pack.
\ No newline at end of file
+// Gets information about all sticker packs / a specific sticker pack.
+// stickerpackinfo [pack]
+
+use super::utils::{join_limited, COLOR};
+use crate::commands::pagination::paginate_embeds;
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 15;
+
+/// Shows one of Discord's sticker packs, or all of them.
+#[poise::command(slash_command, prefix_command, rename = "stickerpackinfo")]
+pub async fn sticker_pack_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Pack name or ID (default: all packs)"] pack: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer().await?;
+    let mut packs = ctx.http().get_nitro_stickers().await?;
+
+    if let Some(input) = pack {
+        let input = input.trim();
+        let found = match input.parse::<u64>() {
+            Ok(id) => packs.iter().find(|p| p.id.get() == id),
+            Err(_) => packs
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(input))
+                .or_else(|| packs.iter().find(|p| p.name.to_lowercase().contains(&input.to_lowercase()))),
+        };
+        let Some(p) = found else {
+            ctx.say(format!("There is no sticker pack called `{}`.", input)).await?;
+            return Ok(());
+        };
+        let names: Vec<String> = p.stickers.iter().map(|s| s.name.clone()).collect();
+        let mut embed = serenity::CreateEmbed::default()
+            .title(&p.name)
+            .description(&p.description)
+            .field("ID", format!("`{}`", p.id), true)
+            .field(format!("Stickers ({})", p.stickers.len()), join_limited(&names, ", ", 1024), false)
+            .image(p.banner_url())
+            .color(COLOR);
+        if let Some(url) = p.cover_sticker().and_then(|s| s.image_url()) {
+            embed = embed.thumbnail(url);
+        }
+        ctx.send(poise::CreateReply::default().embed(embed)).await?;
+        return Ok(());
+    }
+
+    packs.sort_by_key(|p| p.name.to_lowercase());
+    let pages = packs
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|p| format!("**{}** `{}` - {} sticker(s)", p.name, p.id, p.stickers.len()))
+                .collect();
+            serenity::CreateEmbed::default()
+                .title(format!("Sticker packs ({})", packs.len()))
+                .description(lines.join("\n"))
+                .color(COLOR)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
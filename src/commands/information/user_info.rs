@@ -1 +1,127 @@
-// Gets information about a user.
\ No newline at end of file
+// Gets information about a user.
+// userinfo [user]
+
+use super::utils::{join_limited, timestamp, yes_no, COLOR};
+use poise::serenity_prelude as serenity;
+
+// Permissions worth pointing out, in the order they are listed
+const KEY_PERMISSIONS: [(serenity::Permissions, &str); 10] = [
+    (serenity::Permissions::ADMINISTRATOR, "Administrator"),
+    (serenity::Permissions::MANAGE_GUILD, "Manage Server"),
+    (serenity::Permissions::MANAGE_ROLES, "Manage Roles"),
+    (serenity::Permissions::MANAGE_CHANNELS, "Manage Channels"),
+    (serenity::Permissions::MANAGE_MESSAGES, "Manage Messages"),
+    (serenity::Permissions::MANAGE_WEBHOOKS, "Manage Webhooks"),
+    (serenity::Permissions::BAN_MEMBERS, "Ban Members"),
+    (serenity::Permissions::KICK_MEMBERS, "Kick Members"),
+    (serenity::Permissions::MODERATE_MEMBERS, "Timeout Members"),
+    (serenity::Permissions::MENTION_EVERYONE, "Mention Everyone"),
+];
+
+/// The user embed, with member details when the user is in the guild. The user is fetched again
+/// over HTTP because only that includes their banner and accent color.
+pub(crate) async fn user_embed(
+    ctx: &serenity::Context,
+    guild_id: Option<serenity::GuildId>,
+    user: &serenity::User,
+) -> Result<serenity::CreateEmbed, crate::Error> {
+    let user = ctx.http.get_user(user.id).await.unwrap_or_else(|_| user.clone());
+    let mut embed = serenity::CreateEmbed::default()
+        .title(match &user.global_name {
+            Some(display) => format!("{} ({})", display, user.name),
+            None => user.name.clone(),
+        })
+        .thumbnail(user.face())
+        .field("User", format!("<@{}>\n`{}`", user.id, user.id), true)
+        .field("Bot", yes_no(user.bot), true)
+        .field("Created", timestamp(user.id.created_at()), false)
+        .color(user.accent_colour.map(|c| c.0).unwrap_or(COLOR));
+    if let Some(banner) = user.banner_url() {
+        embed = embed.image(banner);
+    }
+    if let Some(flags) = user.public_flags.filter(|f| !f.is_empty()) {
+        let names: Vec<String> = flags.iter_names().map(|(name, _)| format!("`{}`", name.to_lowercase())).collect();
+        embed = embed.field("Badges", names.join(", "), false);
+    }
+
+    let Some(guild_id) = guild_id else { return Ok(embed) };
+    let cached = ctx.cache.guild(guild_id).and_then(|g| {
+        let member = g.members.get(&user.id)?.clone();
+        let mut roles: Vec<&serenity::Role> = member.roles.iter().filter_map(|r| g.roles.get(r)).collect();
+        roles.sort_by_key(|r| std::cmp::Reverse(r.position));
+        // Server-wide permissions: @everyone plus every role, the owner has all of them
+        let everyone = g.roles.get(&serenity::RoleId::new(guild_id.get())).map(|r| r.permissions).unwrap_or_default();
+        let permissions = if g.owner_id == member.user.id {
+            serenity::Permissions::all()
+        } else {
+            roles.iter().fold(everyone, |p, r| p | r.permissions)
+        };
+        let roles: Vec<String> = roles.iter().map(|r| format!("<@&{}>", r.id)).collect();
+        Some((member, roles, Some(permissions)))
+    });
+    let (member, roles, permissions) = match cached {
+        Some(c) => c,
+        None => match guild_id.member(ctx, user.id).await {
+            Ok(m) => {
+                let roles = m.roles.iter().map(|r| format!("<@&{}>", r)).collect();
+                (m, roles, None)
+            }
+            // Not a member of this server
+            Err(_) => return Ok(embed),
+        },
+    };
+
+    if let Some(nick) = &member.nick {
+        embed = embed.field("Nickname", nick, true);
+    }
+    if let Some(joined) = member.joined_at {
+        embed = embed.field("Joined", timestamp(joined), false);
+    }
+    if let Some(since) = member.premium_since {
+        embed = embed.field("Boosting since", timestamp(since), false);
+    }
+    if let Some(until) = member.communication_disabled_until.filter(|u| u.unix_timestamp() > chrono::Utc::now().timestamp()) {
+        embed = embed.field("Timed out until", timestamp(until), false);
+    }
+    if member.pending {
+        embed = embed.field("Membership screening", "Not passed yet", true);
+    }
+    embed = embed.field(format!("Roles ({})", roles.len()), join_limited(&roles, " ", 1024), false);
+    if let Some(permissions) = permissions {
+        let key: Vec<String> = if permissions.administrator() {
+            vec!["Administrator".to_string()]
+        } else {
+            KEY_PERMISSIONS.iter().filter(|(p, _)| permissions.contains(*p)).map(|(_, n)| n.to_string()).collect()
+        };
+        if !key.is_empty() {
+            embed = embed.field("Key permissions", key.join(", "), false);
+        }
+    }
+    if let Some(avatar) = member.avatar_url() {
+        embed = embed.thumbnail(avatar);
+    }
+    Ok(embed)
+}
+
+/// Shows information about a user and their membership in this server.
+#[poise::command(slash_command, prefix_command, rename = "userinfo")]
+pub async fn user_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User (default: you)"] user: Option<serenity::User>,
+) -> Result<(), crate::Error> {
+    let user = user.as_ref().unwrap_or(ctx.author());
+    let embed = user_embed(ctx.serenity_context(), ctx.guild_id(), user).await?;
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Shows information about a user.
+#[poise::command(context_menu_command = "User Info")]
+pub async fn user_info_menu(
+    ctx: poise::ApplicationContext<'_, crate::Data, crate::Error>,
+    #[description = "User to show"] user: serenity::User,
+) -> Result<(), crate::Error> {
+    let embed = user_embed(ctx.serenity_context(), ctx.interaction.guild_id, &user).await?;
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
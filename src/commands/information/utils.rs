@@ -0,0 +1,92 @@
+// Shared helpers of the information commands: guild data from the cache with an HTTP fallback and
+// common formatting.
+
+use poise::serenity_prelude as serenity;
+
+pub(crate) const COLOR: u32 = 0x3B82F6;
+
+/// A Discord timestamp shown as a full date and as a relative time.
+pub(crate) fn timestamp(t: serenity::Timestamp) -> String {
+    format!("<t:{0}:F> (<t:{0}:R>)", t.unix_timestamp())
+}
+
+pub(crate) fn yes_no(b: bool) -> &'static str {
+    if b { "Yes" } else { "No" }
+}
+
+/// Joins items until the text would exceed `max` characters, then says how many were left out.
+pub(crate) fn join_limited(items: &[String], separator: &str, max: usize) -> String {
+    let mut out = String::new();
+    for (i, item) in items.iter().enumerate() {
+        let extra = if out.is_empty() { item.len() } else { separator.len() + item.len() };
+        // Leaves room for the "and N more" note
+        if out.len() + extra > max.saturating_sub(20) {
+            out.push_str(&format!(" and {} more", items.len() - i));
+            return out;
+        }
+        if !out.is_empty() {
+            out.push_str(separator);
+        }
+        out.push_str(item);
+    }
+    if out.is_empty() { "None".to_string() } else { out }
+}
+
+pub(crate) async fn roles(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Vec<serenity::Role>, serenity::Error> {
+    let cached = ctx.cache.guild(guild_id).map(|g| g.roles.values().cloned().collect());
+    match cached {
+        Some(r) => Ok(r),
+        None => ctx.http.get_guild_roles(guild_id).await,
+    }
+}
+
+pub(crate) async fn channels(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Vec<serenity::GuildChannel>, serenity::Error> {
+    let cached = ctx.cache.guild(guild_id).map(|g| g.channels.values().cloned().collect());
+    match cached {
+        Some(c) => Ok(c),
+        None => Ok(guild_id.channels(&ctx.http).await?.into_values().collect()),
+    }
+}
+
+pub(crate) async fn emojis(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Vec<serenity::Emoji>, serenity::Error> {
+    let cached = ctx.cache.guild(guild_id).map(|g| g.emojis.values().cloned().collect());
+    match cached {
+        Some(e) => Ok(e),
+        None => guild_id.emojis(&ctx.http).await,
+    }
+}
+
+pub(crate) async fn stickers(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Vec<serenity::Sticker>, serenity::Error> {
+    let cached = ctx.cache.guild(guild_id).map(|g| g.stickers.values().cloned().collect());
+    match cached {
+        Some(s) => Ok(s),
+        None => guild_id.stickers(&ctx.http).await,
+    }
+}
+
+/// The cached members of a guild and its total member count. Only small guilds (or those Discord
+/// sent the member list for) are fully cached; fetching every member is too slow for a command.
+pub(crate) fn cached_members(ctx: &serenity::Context, guild_id: serenity::GuildId) -> (Vec<serenity::Member>, u64) {
+    ctx.cache
+        .guild(guild_id)
+        .map(|g| (g.members.values().cloned().collect(), g.member_count))
+        .unwrap_or_default()
+}
+
+/// Sorts channels the way Discord lists them: categories by position, each followed by its channels.
+pub(crate) fn sort_channels(channels: &mut [serenity::GuildChannel]) {
+    let categories: std::collections::HashMap<serenity::ChannelId, u16> = channels
+        .iter()
+        .filter(|c| c.kind == serenity::ChannelType::Category)
+        .map(|c| (c.id, c.position))
+        .collect();
+    channels.sort_by_key(|c| {
+        let category = match c.kind {
+            serenity::ChannelType::Category => Some(c.position),
+            _ => c.parent_id.and_then(|p| categories.get(&p).copied()),
+        };
+        // Channels without a category come first, voice channels after text channels
+        let voice = matches!(c.kind, serenity::ChannelType::Voice | serenity::ChannelType::Stage);
+        (category.map(|p| p as i32).unwrap_or(-1), c.kind != serenity::ChannelType::Category, voice, c.position, c.id)
+    });
+}
@@ -0,0 +1,89 @@
+// Hands out join roles when members arrive, once they passed membership screening or after the
+// configured delay.
+
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::reaction_roles::roles::update_roles;
+use crate::data::join_roles::store::{JoinRoleStore, PendingJoinRoles};
+use poise::serenity_prelude as serenity;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+pub(crate) const REASON: &str = "Join roles";
+
+/// Guilds with a `joinroles sync` running, so the same backfill doesn't run twice at once.
+#[derive(Default)]
+pub struct SyncJobs {
+    running: Mutex<HashSet<u64>>,
+}
+
+impl SyncJobs {
+    /// Marks a sync of the guild as running. False when one already is.
+    pub(crate) fn start(&self, guild_id: u64) -> bool {
+        self.running.lock().unwrap().insert(guild_id)
+    }
+
+    pub(crate) fn finish(&self, guild_id: u64) {
+        self.running.lock().unwrap().remove(&guild_id);
+    }
+}
+
+/// Gives a member the join roles they don't have yet.
+pub(crate) async fn give_roles(http: &serenity::Http, guild_id: serenity::GuildId, member: &serenity::Member, roles: &[u64]) {
+    let missing: Vec<u64> = roles.iter().filter(|r| !member.roles.iter().any(|m| m.get() == **r)).copied().collect();
+    if !missing.is_empty() {
+        update_roles(http, guild_id, member.user.id, &missing, &[], REASON).await;
+    }
+}
+
+/// Gives a new member their join roles, or queues them until screening passes or the delay is over.
+pub async fn handle_join(ctx: &serenity::Context, member: &serenity::Member, data: &crate::Data) -> Result<(), crate::Error> {
+    if !enabled(&data.db, member.guild_id.get(), Feature::JoinRoles) {
+        return Ok(());
+    }
+    let store = JoinRoleStore::open(&data.db)?;
+    let config = store.config(member.guild_id.get())?;
+    if config.roles(member.user.bot).is_empty() {
+        return Ok(());
+    }
+    let screening = config.after_screening && !member.user.bot && member.pending;
+    if !screening && config.delay_secs.is_none() {
+        give_roles(&ctx.http, member.guild_id, member, config.roles(member.user.bot)).await;
+        return Ok(());
+    }
+    let due_at = if screening { None } else { config.delay_secs.map(|d| chrono::Utc::now().timestamp() + d as i64) };
+    store.set_pending(&PendingJoinRoles {
+        guild_id: member.guild_id.get(),
+        user_id: member.user.id.get(),
+        due_at,
+    })?;
+    Ok(())
+}
+
+/// Starts the delay, or gives the roles right away, once a queued member passed screening.
+pub async fn handle_update(ctx: &serenity::Context, event: &serenity::GuildMemberUpdateEvent, data: &crate::Data) -> Result<(), crate::Error> {
+    // Stays queued while join roles are disabled, the join roles task picks it up once they're back
+    if event.pending || !enabled(&data.db, event.guild_id.get(), Feature::JoinRoles) {
+        return Ok(());
+    }
+    let store = JoinRoleStore::open(&data.db)?;
+    let Some(mut pending) = store.pending(event.guild_id.get(), event.user.id.get())? else { return Ok(()) };
+    if pending.due_at.is_some() {
+        return Ok(());
+    }
+    let config = store.config(event.guild_id.get())?;
+    if let Some(delay) = config.delay_secs {
+        pending.due_at = Some(chrono::Utc::now().timestamp() + delay as i64);
+        store.set_pending(&pending)?;
+        return Ok(());
+    }
+    store.clear_pending(event.guild_id.get(), event.user.id.get())?;
+    let member = event.guild_id.member(ctx, event.user.id).await?;
+    give_roles(&ctx.http, event.guild_id, &member, config.roles(event.user.bot)).await;
+    Ok(())
+}
+
+/// Forgets a queued member who left before getting their roles.
+pub async fn handle_leave(guild_id: serenity::GuildId, user: &serenity::User, data: &crate::Data) -> Result<(), crate::Error> {
+    JoinRoleStore::open(&data.db)?.clear_pending(guild_id.get(), user.id.get())?;
+    Ok(())
+}
@@ -1,2 +1,247 @@
 // Manage Join Roles.
-// joinroles [sync|[user] <add|remove> <role1> [role2] ...]
\ No newline at end of file
+// joinroles [sync|[user] <add|remove> <role1> [role2] ...]
+// joinroles <screening <on|off>|delay <duration|off>|show>
+
+use super::join::give_roles;
+use crate::commands::moderation::utils::{fetch_members, format_duration, parse_duration};
+use crate::commands::reaction_roles::roles::{check_assignable, parse_role_ids};
+use crate::data::join_roles::store::{JoinRoleConfig, JoinRoleStore};
+use poise::serenity_prelude as serenity;
+use std::time::{Duration, Instant};
+
+// Pause between members during a sync so role updates don't eat the whole rate limit
+const SYNC_PAUSE: Duration = Duration::from_millis(500);
+const PROGRESS_EVERY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum MemberKind {
+    Humans,
+    Bots,
+}
+
+fn role_set(config: &mut JoinRoleConfig, kind: Option<MemberKind>) -> &mut Vec<u64> {
+    match kind.unwrap_or(MemberKind::Humans) {
+        MemberKind::Humans => &mut config.human_roles,
+        MemberKind::Bots => &mut config.bot_roles,
+    }
+}
+
+fn kind_name(kind: Option<MemberKind>) -> &'static str {
+    match kind.unwrap_or(MemberKind::Humans) {
+        MemberKind::Humans => "members",
+        MemberKind::Bots => "bots",
+    }
+}
+
+fn mentions(roles: &[u64]) -> String {
+    if roles.is_empty() {
+        return "none".to_string();
+    }
+    roles.iter().map(|r| format!("<@&{}>", r)).collect::<Vec<_>>().join(", ")
+}
+
+/// Gives roles to members (and bots) when they join.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "joinroles",
+    guild_only,
+    required_permissions = "MANAGE_ROLES",
+    subcommands("add", "remove", "screening", "delay", "show", "sync"),
+    subcommand_required
+)]
+pub async fn join_roles(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Adds roles given to new members or bots.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Whose join roles to change (default: humans)"] kind: Option<MemberKind>,
+    #[rest]
+    #[description = "Roles to give on join"]
+    roles: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (ids, invalid) = parse_role_ids(&roles);
+    if !invalid.is_empty() {
+        ctx.say(format!("Couldn't read `{}` as roles.", invalid.join("`, `"))).await?;
+        return Ok(());
+    }
+    if ids.is_empty() {
+        ctx.say("Name at least one role.").await?;
+        return Ok(());
+    }
+    if let Err(problem) = check_assignable(ctx.serenity_context(), guild_id, &ids) {
+        ctx.say(problem).await?;
+        return Ok(());
+    }
+    let store = JoinRoleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let set = role_set(&mut config, kind);
+    for id in ids {
+        if !set.contains(&id) {
+            set.push(id);
+        }
+    }
+    let text = format!("New {} now get {}.", kind_name(kind), mentions(set));
+    store.set_config(guild_id.get(), &config)?;
+    ctx.send(poise::CreateReply::default().content(text).allowed_mentions(serenity::CreateAllowedMentions::new())).await?;
+    Ok(())
+}
+
+/// Removes roles given to new members or bots.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Whose join roles to change (default: humans)"] kind: Option<MemberKind>,
+    #[rest]
+    #[description = "Roles to stop giving on join"]
+    roles: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (ids, invalid) = parse_role_ids(&roles);
+    if !invalid.is_empty() {
+        ctx.say(format!("Couldn't read `{}` as roles.", invalid.join("`, `"))).await?;
+        return Ok(());
+    }
+    let store = JoinRoleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let set = role_set(&mut config, kind);
+    let before = set.len();
+    set.retain(|r| !ids.contains(r));
+    if set.len() == before {
+        ctx.say(format!("None of these roles are given to new {}.", kind_name(kind))).await?;
+        return Ok(());
+    }
+    let text = format!("New {} now get {}.", kind_name(kind), mentions(set));
+    store.set_config(guild_id.get(), &config)?;
+    ctx.send(poise::CreateReply::default().content(text).allowed_mentions(serenity::CreateAllowedMentions::new())).await?;
+    Ok(())
+}
+
+/// Waits until new members passed membership screening before giving their roles.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn screening(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Whether roles wait for membership screening"] enabled: bool,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = JoinRoleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.after_screening = enabled;
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(if enabled {
+        "New members get their roles once they passed membership screening."
+    } else {
+        "New members get their roles without waiting for membership screening."
+    })
+    .await?;
+    Ok(())
+}
+
+/// Waits a while after the join (or screening) before giving the roles.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn delay(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Delay like 10m or 1h, or off"] duration: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let delay = if duration.trim().eq_ignore_ascii_case("off") {
+        None
+    } else {
+        match parse_duration(&duration) {
+            Some(d) => Some(d.as_secs()),
+            None => {
+                ctx.say(format!("`{}` is not a duration. Use something like `10m`, `1h` or `off`.", duration)).await?;
+                return Ok(());
+            }
+        }
+    };
+    let store = JoinRoleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.delay_secs = delay;
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(match delay {
+        Some(secs) => format!("Join roles are given {} after joining.", format_duration(secs)),
+        None => "Join roles are given right away.".to_string(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Shows the join roles of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn show(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let config = JoinRoleStore::open(&ctx.data().db)?.config(guild_id.get())?;
+    let embed = serenity::CreateEmbed::default()
+        .title("Join roles")
+        .field("Members", mentions(&config.human_roles), false)
+        .field("Bots", mentions(&config.bot_roles), false)
+        .field("Wait for screening", if config.after_screening { "on" } else { "off" }, true)
+        .field("Delay", config.delay_secs.map(format_duration).unwrap_or_else(|| "none".to_string()), true)
+        .color(0x3B82F6);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Gives the join roles to every current member who lacks them.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn sync(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = JoinRoleStore::open(&ctx.data().db)?;
+    let config = store.config(guild_id.get())?;
+    if config.human_roles.is_empty() && config.bot_roles.is_empty() {
+        ctx.say("There are no join roles to sync.").await?;
+        return Ok(());
+    }
+    let all: Vec<u64> = config.human_roles.iter().chain(&config.bot_roles).copied().collect();
+    if let Err(problem) = check_assignable(ctx.serenity_context(), guild_id, &all) {
+        ctx.say(problem).await?;
+        return Ok(());
+    }
+    if !ctx.data().join_role_syncs.start(guild_id.get()) {
+        ctx.say("A sync is already running in this server.").await?;
+        return Ok(());
+    }
+    let result = run_sync(ctx, guild_id, &store, &config).await;
+    ctx.data().join_role_syncs.finish(guild_id.get());
+    result
+}
+
+async fn run_sync(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    guild_id: serenity::GuildId,
+    store: &JoinRoleStore,
+    config: &JoinRoleConfig,
+) -> Result<(), crate::Error> {
+    let progress = ctx.say("Fetching members...").await?;
+    let members = fetch_members(ctx.http(), guild_id).await?;
+    // Members still waiting on screening or their delay get their roles from the join roles task
+    let missing: Vec<&serenity::Member> = members
+        .iter()
+        .filter(|m| !(config.after_screening && !m.user.bot && m.pending))
+        .filter(|m| store.pending(guild_id.get(), m.user.id.get()).ok().flatten().is_none())
+        .filter(|m| config.roles(m.user.bot).iter().any(|r| !m.roles.iter().any(|h| h.get() == *r)))
+        .collect();
+
+    let mut last_update = Instant::now();
+    for (i, member) in missing.iter().enumerate() {
+        if last_update.elapsed() >= PROGRESS_EVERY {
+            last_update = Instant::now();
+            let text = format!("Syncing join roles... {}/{} member(s)", i, missing.len());
+            let _ = progress.edit(ctx, poise::CreateReply::default().content(text)).await;
+        }
+        give_roles(ctx.http(), guild_id, member, config.roles(member.user.bot)).await;
+        tokio::time::sleep(SYNC_PAUSE).await;
+    }
+
+    let text = format!("Synced join roles: {} of {} member(s) were missing roles.", missing.len(), members.len());
+    // Interaction replies can't be edited anymore after 15 minutes
+    if progress.edit(ctx, poise::CreateReply::default().content(text.clone())).await.is_err() {
+        ctx.channel_id().say(ctx.http(), text).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod join;
+#[allow(clippy::module_inception)]
+pub mod join_roles;
@@ -0,0 +1,39 @@
+// Looks up who was behind an event in the guild audit log, for actions done in the Discord client
+// instead of through the bot.
+
+use poise::serenity_prelude as serenity;
+use serenity::audit_log::Action;
+use std::time::Duration;
+
+// Audit log entries can show up a moment after the gateway event
+const AUDIT_DELAY: Duration = Duration::from_millis(1500);
+// Entries older than this are about an earlier event
+const MAX_ENTRY_AGE_SECS: i64 = 30;
+
+/// The moderator and reason of an audit log entry.
+pub(crate) struct Attribution {
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+}
+
+impl Attribution {
+    /// Adds the moderator and reason fields to a log embed.
+    pub fn fields(&self, embed: serenity::CreateEmbed) -> serenity::CreateEmbed {
+        embed
+            .field("Moderator", format!("<@{}>", self.moderator_id), true)
+            .field("Reason", self.reason.as_deref().unwrap_or("No reason provided"), true)
+    }
+}
+
+/// The most recent audit log entry of this kind about the target. None when there's no fresh entry
+/// or the bot may not view the audit log.
+pub(crate) async fn find_entry(ctx: &serenity::Context, guild_id: serenity::GuildId, action: Action, target_id: u64) -> Option<Attribution> {
+    tokio::time::sleep(AUDIT_DELAY).await;
+    let logs = guild_id.audit_logs(&ctx.http, Some(action), None, None, Some(10)).await.ok()?;
+    let now = chrono::Utc::now().timestamp();
+    logs.entries
+        .into_iter()
+        .filter(|e| now - e.id.created_at().unix_timestamp() <= MAX_ENTRY_AGE_SECS)
+        .find(|e| e.target_id.is_some_and(|t| t.get() == target_id))
+        .map(|e| Attribution { moderator_id: e.user_id.get(), reason: e.reason })
+}
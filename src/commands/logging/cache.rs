@@ -0,0 +1,74 @@
+// Bounded cache of recent guild messages, so deleted and edited messages can be logged with the
+// content they had. Discord only sends the ids of deleted messages.
+
+use poise::serenity_prelude as serenity;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+// Oldest messages are dropped once this many are cached
+const CAPACITY: usize = 10_000;
+
+#[derive(Clone, Debug)]
+pub struct CachedMessage {
+    pub channel_id: u64,
+    pub author_id: u64,
+    pub author_name: String,
+    pub author_bot: bool,
+    pub content: String,
+    pub attachments: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Default)]
+pub struct MessageCache {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    messages: HashMap<u64, CachedMessage>,
+    order: VecDeque<u64>,
+}
+
+impl MessageCache {
+    pub fn insert(&self, msg: &serenity::Message) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.messages.insert(
+            msg.id.get(),
+            CachedMessage {
+                channel_id: msg.channel_id.get(),
+                author_id: msg.author.id.get(),
+                author_name: msg.author.name.clone(),
+                author_bot: msg.author.bot,
+                content: msg.content.clone(),
+                attachments: msg.attachments.iter().map(|a| a.url.clone()).collect(),
+                created_at: msg.timestamp.unix_timestamp(),
+            },
+        );
+        inner.order.push_back(msg.id.get());
+        while inner.order.len() > CAPACITY {
+            if let Some(old) = inner.order.pop_front() {
+                inner.messages.remove(&old);
+            }
+        }
+    }
+
+    /// Stores the new content of an edited message and returns the message as it was before.
+    pub fn update(&self, id: u64, content: &str) -> Option<CachedMessage> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = inner.messages.get_mut(&id)?;
+        let before = cached.clone();
+        cached.content = content.to_string();
+        Some(before)
+    }
+
+    pub fn remove(&self, id: u64) -> Option<CachedMessage> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let removed = inner.messages.remove(&id)?;
+        // A stale id left in the order would count against the capacity until it ages out
+        if let Some(pos) = inner.order.iter().position(|o| *o == id) {
+            inner.order.remove(pos);
+        }
+        Some(removed)
+    }
+}
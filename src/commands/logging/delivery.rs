@@ -0,0 +1,60 @@
+// Routing of log entries: which channel a log type goes to, and whether the user,
+// their roles or the channel involved are on the guild's ignore lists.
+
+use crate::commands::general::features::{enabled, Feature};
+use crate::data::logging::store::{LogConfig, LogStore, LogType};
+use poise::serenity_prelude as serenity;
+
+/// What a log entry is about, checked against the ignore lists.
+#[derive(Default)]
+pub(crate) struct Subject {
+    pub user: Option<u64>,
+    pub channel: Option<u64>,
+}
+
+/// The channel an entry goes to, or None when the type isn't routed or the subject is ignored.
+pub(crate) fn route(ctx: &serenity::Context, config: &LogConfig, guild_id: u64, log_type: LogType, subject: &Subject) -> Option<u64> {
+    let channel = *config.channels.get(&log_type)?;
+    if subject.channel.is_some_and(|c| config.ignored_channels.contains(&c)) {
+        return None;
+    }
+    if let Some(user) = subject.user {
+        if config.ignored_users.contains(&user) {
+            return None;
+        }
+        if !config.ignored_roles.is_empty() {
+            let roles: Vec<u64> = ctx
+                .cache
+                .guild(serenity::GuildId::new(guild_id))
+                .and_then(|g| g.members.get(&serenity::UserId::new(user)).map(|m| m.roles.iter().map(|r| r.get()).collect()))
+                .unwrap_or_default();
+            if roles.iter().any(|r| config.ignored_roles.contains(r)) {
+                return None;
+            }
+        }
+    }
+    Some(channel)
+}
+
+/// Queues a log entry if logging is enabled, its type is routed and its subject isn't ignored. The
+/// log delivery task sends it shortly after, batched with other entries for the same channel.
+pub(crate) async fn send_log(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: u64,
+    log_type: LogType,
+    subject: Subject,
+    embed: serenity::CreateEmbed,
+) -> Result<(), crate::Error> {
+    if !enabled(db, guild_id, Feature::Logging) {
+        return Ok(());
+    }
+    let store = LogStore::open(db)?;
+    let config = store.config(guild_id)?;
+    let Some(channel) = route(ctx, &config, guild_id, log_type, &subject) else { return Ok(()) };
+    let embed = embed
+        .footer(serenity::CreateEmbedFooter::new(log_type.as_str()))
+        .timestamp(serenity::Timestamp::now());
+    store.enqueue(guild_id, channel, log_type, serde_json::to_value(embed)?)?;
+    Ok(())
+}
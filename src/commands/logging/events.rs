@@ -0,0 +1,406 @@
+// Turns gateway events into log entries: message edits and deletes, member, channel and role
+// changes, voice moves, bans and invites.
+
+use super::audit::{find_entry, Attribution};
+use super::delivery::{send_log, Subject};
+use crate::commands::moderation::cases::truncate;
+use crate::data::cases::store::{CaseAction, CaseStore, NewCase};
+use crate::data::logging::store::{LogStore, LogType};
+use poise::serenity_prelude as serenity;
+use serenity::audit_log::{Action, ChannelAction, MemberAction};
+
+const COLOR_CREATE: u32 = 0x22C55E;
+const COLOR_UPDATE: u32 = 0x3B82F6;
+const COLOR_DELETE: u32 = 0xEF4444;
+
+fn message_link(guild_id: u64, channel_id: u64, message_id: u64) -> String {
+    format!("https://discord.com/channels/{}/{}/{}", guild_id, channel_id, message_id)
+}
+
+fn content_or_placeholder(content: &str) -> String {
+    if content.is_empty() { "*no text*".to_string() } else { truncate(content, 1024) }
+}
+
+/// Opens a case for a ban or kick that was done in the Discord client, if the guild wants that.
+/// Actions by the bot already have their case.
+fn manual_case(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: u64,
+    action: CaseAction,
+    user_id: u64,
+    attribution: &Attribution,
+) -> Result<Option<u64>, crate::Error> {
+    if attribution.moderator_id == ctx.cache.current_user().id.get() || !LogStore::open(db)?.config(guild_id)?.auto_cases {
+        return Ok(None);
+    }
+    let case = CaseStore::open(db)?.create(
+        guild_id,
+        NewCase {
+            action,
+            targets: vec![user_id],
+            moderator_id: attribution.moderator_id,
+            reason: attribution.reason.clone(),
+            expires_at: None,
+        },
+    )?;
+    Ok(Some(case.id))
+}
+
+fn role_list(roles: &[serenity::RoleId]) -> String {
+    if roles.is_empty() {
+        "none".to_string()
+    } else {
+        truncate(&roles.iter().map(|r| format!("<@&{}>", r)).collect::<Vec<_>>().join(" "), 1024)
+    }
+}
+
+/// Logs one gateway event if the guild routes its type somewhere. Messages are cached on the way.
+pub async fn log_event(ctx: &serenity::Context, event: &serenity::FullEvent, data: &crate::Data) -> Result<(), crate::Error> {
+    let db = &data.db;
+    match event {
+        serenity::FullEvent::Message { new_message } if new_message.guild_id.is_some() => {
+            data.message_cache.insert(new_message);
+        }
+        serenity::FullEvent::MessageUpdate { old_if_available, event, .. } => {
+            let Some(guild_id) = event.guild_id else { return Ok(()) };
+            let Some(content) = &event.content else { return Ok(()) };
+            let cached = data.message_cache.update(event.id.get(), content);
+            let before = cached
+                .as_ref()
+                .map(|c| c.content.clone())
+                .or_else(|| old_if_available.as_ref().map(|m| m.content.clone()));
+            // Embed unfurls also arrive as edits, only text changes are logged
+            if before.as_deref() == Some(content.as_str()) {
+                return Ok(());
+            }
+            let author = event.author.as_ref().map(|a| (a.id.get(), a.bot)).or(cached.as_ref().map(|c| (c.author_id, c.author_bot)));
+            if author.is_some_and(|(_, bot)| bot) {
+                return Ok(());
+            }
+            let author_id = author.map(|(id, _)| id);
+            let embed = serenity::CreateEmbed::default()
+                .title("Message edited")
+                .description(format!(
+                    "[Jump to message]({}) in <#{}>{}",
+                    message_link(guild_id.get(), event.channel_id.get(), event.id.get()),
+                    event.channel_id,
+                    author_id.map(|a| format!(" by <@{}>", a)).unwrap_or_default()
+                ))
+                .field("Before", before.map(|b| content_or_placeholder(&b)).unwrap_or_else(|| "*not cached*".to_string()), false)
+                .field("After", content_or_placeholder(content), false)
+                .color(COLOR_UPDATE);
+            let subject = Subject { user: author_id, channel: Some(event.channel_id.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::MessageEdit, subject, embed).await?;
+        }
+        serenity::FullEvent::MessageDelete { channel_id, deleted_message_id, guild_id } => {
+            let Some(guild_id) = guild_id else { return Ok(()) };
+            let cached = data.message_cache.remove(deleted_message_id.get());
+            if cached.as_ref().is_some_and(|c| c.author_bot) {
+                return Ok(());
+            }
+            let mut embed = serenity::CreateEmbed::default().title("Message deleted").color(COLOR_DELETE);
+            embed = match &cached {
+                Some(c) => {
+                    let mut embed = embed
+                        .description(format!("Message by <@{}> ({}) in <#{}>, sent <t:{}:R>", c.author_id, c.author_name, channel_id, c.created_at))
+                        .field("Content", content_or_placeholder(&c.content), false);
+                    if !c.attachments.is_empty() {
+                        embed = embed.field("Attachments", truncate(&c.attachments.join("\n"), 1024), false);
+                    }
+                    embed
+                }
+                None => embed.description(format!("Message `{}` in <#{}> (content not cached)", deleted_message_id, channel_id)),
+            };
+            let subject = Subject { user: cached.map(|c| c.author_id), channel: Some(channel_id.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::MessageDelete, subject, embed).await?;
+        }
+        serenity::FullEvent::MessageDeleteBulk { channel_id, multiple_deleted_messages_ids, guild_id } => {
+            let Some(guild_id) = guild_id else { return Ok(()) };
+            let mut cached: Vec<_> = multiple_deleted_messages_ids
+                .iter()
+                .filter_map(|id| data.message_cache.remove(id.get()))
+                .collect();
+            cached.sort_by_key(|c| c.created_at);
+            let mut desc = format!("**{}** message(s) deleted in <#{}>", multiple_deleted_messages_ids.len(), channel_id);
+            if !cached.is_empty() {
+                desc.push_str(&format!(", {} cached:\n", cached.len()));
+                for c in &cached {
+                    desc.push_str(&format!("\n**{}**: {}", c.author_name, truncate(&c.content, 200)));
+                }
+            }
+            let embed = serenity::CreateEmbed::default()
+                .title("Messages bulk deleted")
+                .description(truncate(&desc, 4096))
+                .color(COLOR_DELETE);
+            let subject = Subject { user: None, channel: Some(channel_id.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::MessageBulkDelete, subject, embed).await?;
+        }
+        serenity::FullEvent::GuildMemberAddition { new_member } => {
+            let embed = serenity::CreateEmbed::default()
+                .title("Member joined")
+                .description(format!("<@{}> ({})", new_member.user.id, new_member.user.name))
+                .field("Account created", format!("<t:{}:R>", new_member.user.created_at().unix_timestamp()), true)
+                .thumbnail(new_member.user.face())
+                .color(COLOR_CREATE);
+            let subject = Subject { user: Some(new_member.user.id.get()), channel: None };
+            send_log(ctx, db, new_member.guild_id.get(), LogType::MemberJoin, subject, embed).await?;
+        }
+        serenity::FullEvent::GuildMemberRemoval { guild_id, user, member_data_if_available } => {
+            // Discord has no kick event, a kick is a leave with a matching audit log entry
+            let config = LogStore::open(db)?.config(guild_id.get())?;
+            let kick = if config.channels.contains_key(&LogType::Kick) || config.auto_cases {
+                find_entry(ctx, *guild_id, Action::Member(MemberAction::Kick), user.id.get()).await
+            } else {
+                None
+            };
+            let mut embed = serenity::CreateEmbed::default()
+                .title(if kick.is_some() { "Member kicked" } else { "Member left" })
+                .description(format!("<@{}> ({})", user.id, user.name))
+                .thumbnail(user.face())
+                .color(COLOR_DELETE);
+            if let Some(m) = member_data_if_available {
+                if let Some(joined) = m.joined_at {
+                    embed = embed.field("Joined", format!("<t:{}:R>", joined.unix_timestamp()), true);
+                }
+                embed = embed.field("Roles", role_list(&m.roles), false);
+            }
+            let subject = Subject { user: Some(user.id.get()), channel: None };
+            match kick {
+                Some(kick) => {
+                    embed = kick.fields(embed);
+                    if let Some(id) = manual_case(ctx, db, guild_id.get(), CaseAction::Kick, user.id.get(), &kick)? {
+                        embed = embed.field("Case", format!("#{}", id), true);
+                    }
+                    send_log(ctx, db, guild_id.get(), LogType::Kick, subject, embed).await?;
+                }
+                None => send_log(ctx, db, guild_id.get(), LogType::MemberLeave, subject, embed).await?,
+            }
+        }
+        serenity::FullEvent::GuildMemberUpdate { old_if_available, event, .. } => {
+            let Some(old) = old_if_available else { return Ok(()) };
+            let guild_id = event.guild_id.get();
+            let subject = || Subject { user: Some(event.user.id.get()), channel: None };
+            let added: Vec<_> = event.roles.iter().filter(|r| !old.roles.contains(r)).copied().collect();
+            let removed: Vec<_> = old.roles.iter().filter(|r| !event.roles.contains(r)).copied().collect();
+            if !added.is_empty() || !removed.is_empty() {
+                let mut embed = serenity::CreateEmbed::default()
+                    .title("Member roles changed")
+                    .description(format!("<@{}> ({})", event.user.id, event.user.name))
+                    .color(COLOR_UPDATE);
+                if !added.is_empty() {
+                    embed = embed.field("Added", role_list(&added), false);
+                }
+                if !removed.is_empty() {
+                    embed = embed.field("Removed", role_list(&removed), false);
+                }
+                if LogStore::open(db)?.channel_for(guild_id, LogType::MemberRoles)?.is_some()
+                    && let Some(by) = find_entry(ctx, event.guild_id, Action::Member(MemberAction::RoleUpdate), event.user.id.get()).await
+                {
+                    embed = by.fields(embed);
+                }
+                send_log(ctx, db, guild_id, LogType::MemberRoles, subject(), embed).await?;
+            }
+            if old.nick != event.nick {
+                let embed = serenity::CreateEmbed::default()
+                    .title("Nickname changed")
+                    .description(format!("<@{}> ({})", event.user.id, event.user.name))
+                    .field("Before", old.nick.as_deref().unwrap_or("*none*"), true)
+                    .field("After", event.nick.as_deref().unwrap_or("*none*"), true)
+                    .color(COLOR_UPDATE);
+                send_log(ctx, db, guild_id, LogType::MemberNickname, subject(), embed).await?;
+            }
+        }
+        serenity::FullEvent::ChannelCreate { channel } => {
+            let embed = serenity::CreateEmbed::default()
+                .title("Channel created")
+                .description(format!("<#{}> (`{}`, {:?})", channel.id, channel.name, channel.kind))
+                .color(COLOR_CREATE);
+            let subject = Subject { user: None, channel: Some(channel.id.get()) };
+            send_log(ctx, db, channel.guild_id.get(), LogType::ChannelCreate, subject, embed).await?;
+        }
+        serenity::FullEvent::ChannelUpdate { old: Some(old), new } => {
+            let mut changes = Vec::new();
+            if old.name != new.name {
+                changes.push(format!("Name: `{}` -> `{}`", old.name, new.name));
+            }
+            if old.topic != new.topic {
+                changes.push(format!(
+                    "Topic: {} -> {}",
+                    truncate(old.topic.as_deref().unwrap_or("none"), 300),
+                    truncate(new.topic.as_deref().unwrap_or("none"), 300)
+                ));
+            }
+            if old.nsfw != new.nsfw {
+                changes.push(format!("NSFW: {} -> {}", old.nsfw, new.nsfw));
+            }
+            if old.rate_limit_per_user != new.rate_limit_per_user {
+                changes.push(format!(
+                    "Slow mode: {}s -> {}s",
+                    old.rate_limit_per_user.unwrap_or(0),
+                    new.rate_limit_per_user.unwrap_or(0)
+                ));
+            }
+            if old.parent_id != new.parent_id {
+                let show = |p: Option<serenity::ChannelId>| p.map(|p| format!("<#{}>", p)).unwrap_or_else(|| "none".to_string());
+                changes.push(format!("Category: {} -> {}", show(old.parent_id), show(new.parent_id)));
+            }
+            if old.bitrate != new.bitrate || old.user_limit != new.user_limit {
+                changes.push(format!(
+                    "Voice: {}kbps/{} users -> {}kbps/{} users",
+                    old.bitrate.unwrap_or(0) / 1000,
+                    old.user_limit.unwrap_or(0),
+                    new.bitrate.unwrap_or(0) / 1000,
+                    new.user_limit.unwrap_or(0)
+                ));
+            }
+            if old.permission_overwrites != new.permission_overwrites {
+                changes.push("Permission overwrites changed".to_string());
+            }
+            // Position shuffles touch every channel below the moved one and aren't worth logging
+            if changes.is_empty() {
+                return Ok(());
+            }
+            let embed = serenity::CreateEmbed::default()
+                .title("Channel updated")
+                .description(truncate(&format!("<#{}>\n{}", new.id, changes.join("\n")), 4096))
+                .color(COLOR_UPDATE);
+            let subject = Subject { user: None, channel: Some(new.id.get()) };
+            send_log(ctx, db, new.guild_id.get(), LogType::ChannelUpdate, subject, embed).await?;
+        }
+        serenity::FullEvent::ChannelDelete { channel, .. } => {
+            let mut embed = serenity::CreateEmbed::default()
+                .title("Channel deleted")
+                .description(format!("`#{}` ({:?}, `{}`)", channel.name, channel.kind, channel.id))
+                .color(COLOR_DELETE);
+            if LogStore::open(db)?.channel_for(channel.guild_id.get(), LogType::ChannelDelete)?.is_some()
+                && let Some(by) = find_entry(ctx, channel.guild_id, Action::Channel(ChannelAction::Delete), channel.id.get()).await
+            {
+                embed = by.fields(embed);
+            }
+            let subject = Subject { user: None, channel: Some(channel.id.get()) };
+            send_log(ctx, db, channel.guild_id.get(), LogType::ChannelDelete, subject, embed).await?;
+        }
+        serenity::FullEvent::GuildRoleCreate { new } => {
+            let embed = serenity::CreateEmbed::default()
+                .title("Role created")
+                .description(format!("<@&{}> (`{}`)", new.id, new.name))
+                .color(COLOR_CREATE);
+            send_log(ctx, db, new.guild_id.get(), LogType::RoleCreate, Subject::default(), embed).await?;
+        }
+        serenity::FullEvent::GuildRoleUpdate { old_data_if_available: Some(old), new } => {
+            let mut changes = Vec::new();
+            if old.name != new.name {
+                changes.push(format!("Name: `{}` -> `{}`", old.name, new.name));
+            }
+            if old.colour != new.colour {
+                changes.push(format!("Color: #{:06X} -> #{:06X}", old.colour.0, new.colour.0));
+            }
+            if old.hoist != new.hoist {
+                changes.push(format!("Shown separately: {} -> {}", old.hoist, new.hoist));
+            }
+            if old.mentionable != new.mentionable {
+                changes.push(format!("Mentionable: {} -> {}", old.mentionable, new.mentionable));
+            }
+            let granted = new.permissions - old.permissions;
+            let revoked = old.permissions - new.permissions;
+            if !granted.is_empty() {
+                changes.push(format!("Granted: {}", granted.get_permission_names().join(", ")));
+            }
+            if !revoked.is_empty() {
+                changes.push(format!("Revoked: {}", revoked.get_permission_names().join(", ")));
+            }
+            if changes.is_empty() {
+                return Ok(());
+            }
+            let embed = serenity::CreateEmbed::default()
+                .title("Role updated")
+                .description(truncate(&format!("<@&{}>\n{}", new.id, changes.join("\n")), 4096))
+                .color(COLOR_UPDATE);
+            send_log(ctx, db, new.guild_id.get(), LogType::RoleUpdate, Subject::default(), embed).await?;
+        }
+        serenity::FullEvent::GuildRoleDelete { guild_id, removed_role_id, removed_role_data_if_available } => {
+            let name = removed_role_data_if_available.as_ref().map(|r| r.name.clone()).unwrap_or_else(|| "unknown".to_string());
+            let embed = serenity::CreateEmbed::default()
+                .title("Role deleted")
+                .description(format!("`{}` (`{}`)", name, removed_role_id))
+                .color(COLOR_DELETE);
+            send_log(ctx, db, guild_id.get(), LogType::RoleDelete, Subject::default(), embed).await?;
+        }
+        serenity::FullEvent::VoiceStateUpdate { old, new } => {
+            let Some(guild_id) = new.guild_id else { return Ok(()) };
+            let before = old.as_ref().and_then(|o| o.channel_id);
+            if before == new.channel_id {
+                return Ok(());
+            }
+            let (title, desc, color) = match (before, new.channel_id) {
+                (None, Some(to)) => ("Joined voice", format!("<@{}> joined <#{}>", new.user_id, to), COLOR_CREATE),
+                (Some(from), None) => ("Left voice", format!("<@{}> left <#{}>", new.user_id, from), COLOR_DELETE),
+                (Some(from), Some(to)) => ("Moved voice", format!("<@{}> moved from <#{}> to <#{}>", new.user_id, from, to), COLOR_UPDATE),
+                (None, None) => return Ok(()),
+            };
+            let embed = serenity::CreateEmbed::default().title(title).description(desc).color(color);
+            let subject = Subject { user: Some(new.user_id.get()), channel: new.channel_id.or(before).map(|c| c.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::Voice, subject, embed).await?;
+        }
+        serenity::FullEvent::GuildBanAddition { guild_id, banned_user } => {
+            let mut embed = serenity::CreateEmbed::default()
+                .title("Member banned")
+                .description(format!("<@{}> ({})", banned_user.id, banned_user.name))
+                .thumbnail(banned_user.face())
+                .color(COLOR_DELETE);
+            let config = LogStore::open(db)?.config(guild_id.get())?;
+            if (config.channels.contains_key(&LogType::Ban) || config.auto_cases)
+                && let Some(ban) = find_entry(ctx, *guild_id, Action::Member(MemberAction::BanAdd), banned_user.id.get()).await
+            {
+                embed = ban.fields(embed);
+                if let Some(id) = manual_case(ctx, db, guild_id.get(), CaseAction::Ban, banned_user.id.get(), &ban)? {
+                    embed = embed.field("Case", format!("#{}", id), true);
+                }
+            }
+            let subject = Subject { user: Some(banned_user.id.get()), channel: None };
+            send_log(ctx, db, guild_id.get(), LogType::Ban, subject, embed).await?;
+        }
+        serenity::FullEvent::GuildBanRemoval { guild_id, unbanned_user } => {
+            let embed = serenity::CreateEmbed::default()
+                .title("Member unbanned")
+                .description(format!("<@{}> ({})", unbanned_user.id, unbanned_user.name))
+                .color(COLOR_CREATE);
+            let subject = Subject { user: Some(unbanned_user.id.get()), channel: None };
+            send_log(ctx, db, guild_id.get(), LogType::Unban, subject, embed).await?;
+        }
+        serenity::FullEvent::InviteCreate { data: invite } => {
+            let Some(guild_id) = invite.guild_id else { return Ok(()) };
+            let mut embed = serenity::CreateEmbed::default()
+                .title("Invite created")
+                .description(format!("`{}` for <#{}>", invite.code, invite.channel_id))
+                .field(
+                    "Expires",
+                    if invite.max_age == 0 {
+                        "never".to_string()
+                    } else {
+                        format!("<t:{}:R>", invite.created_at.unix_timestamp() + invite.max_age as i64)
+                    },
+                    true,
+                )
+                .field("Max uses", if invite.max_uses == 0 { "unlimited".to_string() } else { invite.max_uses.to_string() }, true)
+                .color(COLOR_CREATE);
+            if let Some(inviter) = &invite.inviter {
+                embed = embed.field("Created by", format!("<@{}>", inviter.id), true);
+            }
+            let subject = Subject { user: invite.inviter.as_ref().map(|u| u.id.get()), channel: Some(invite.channel_id.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::InviteCreate, subject, embed).await?;
+        }
+        serenity::FullEvent::InviteDelete { data: invite } => {
+            let Some(guild_id) = invite.guild_id else { return Ok(()) };
+            let embed = serenity::CreateEmbed::default()
+                .title("Invite deleted")
+                .description(format!("`{}` for <#{}>", invite.code, invite.channel_id))
+                .color(COLOR_DELETE);
+            let subject = Subject { user: None, channel: Some(invite.channel_id.get()) };
+            send_log(ctx, db, guild_id.get(), LogType::InviteDelete, subject, embed).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
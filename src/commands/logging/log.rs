@@ -1,2 +1,282 @@
 // Manages Logging.
-// log [<<set <channel>|remove> <type1> [type2] ...>|<ignore <user1|role1|channel1|> [user2|role2|channel2|]> ...>]
\ No newline at end of file
+// log [<<set <channel>|remove> <type1> [type2] ...>|<ignore <user1|role1|channel1|> [user2|role2|channel2|]> ...>]
+
+use crate::commands::information::utils::join_limited;
+use crate::data::logging::store::{LogConfig, LogStore, LogType};
+use poise::serenity_prelude as serenity;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Target {
+    User(u64),
+    Role(u64),
+    Channel(u64),
+}
+
+impl Target {
+    fn mention(&self) -> String {
+        match self {
+            Target::User(id) => format!("<@{}>", id),
+            Target::Role(id) => format!("<@&{}>", id),
+            Target::Channel(id) => format!("<#{}>", id),
+        }
+    }
+
+    fn list<'a>(&self, config: &'a mut LogConfig) -> (&'a mut Vec<u64>, u64) {
+        match *self {
+            Target::User(id) => (&mut config.ignored_users, id),
+            Target::Role(id) => (&mut config.ignored_roles, id),
+            Target::Channel(id) => (&mut config.ignored_channels, id),
+        }
+    }
+}
+
+/// Parses space separated type names, "all" selects every type. Returns the unknown names as error.
+fn parse_types(input: &str) -> Result<Vec<LogType>, Vec<String>> {
+    let mut types = Vec::new();
+    let mut unknown = Vec::new();
+    for word in input.split([' ', ',']).filter(|w| !w.is_empty()) {
+        if word.eq_ignore_ascii_case("all") {
+            types.extend(LogType::ALL);
+        } else {
+            match LogType::parse(word) {
+                Some(t) => types.push(t),
+                None => unknown.push(word.to_string()),
+            }
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(unknown);
+    }
+    types.sort();
+    types.dedup();
+    Ok(types)
+}
+
+/// Parses user, role and channel mentions or raw IDs. Raw IDs are looked up among the guild's roles
+/// and channels first and count as users otherwise.
+fn parse_targets(ctx: poise::Context<'_, crate::Data, crate::Error>, guild_id: serenity::GuildId, input: &str) -> Result<Vec<Target>, Vec<String>> {
+    let guild = ctx.cache().guild(guild_id).map(|g| {
+        (
+            g.roles.keys().map(|r| r.get()).collect::<Vec<_>>(),
+            g.channels.keys().map(|c| c.get()).collect::<Vec<_>>(),
+        )
+    });
+    let (roles, channels) = guild.unwrap_or_default();
+    let mut targets = Vec::new();
+    let mut invalid = Vec::new();
+    for word in input.split_whitespace() {
+        let (kind, raw) = if let Some(r) = word.strip_prefix("<@&") {
+            (Some('r'), r)
+        } else if let Some(c) = word.strip_prefix("<#") {
+            (Some('c'), c)
+        } else if let Some(u) = word.strip_prefix("<@") {
+            (Some('u'), u.trim_start_matches('!'))
+        } else {
+            (None, word)
+        };
+        let Ok(id) = raw.trim_end_matches('>').parse::<u64>() else {
+            invalid.push(word.to_string());
+            continue;
+        };
+        let target = match kind {
+            Some('r') => Target::Role(id),
+            Some('c') => Target::Channel(id),
+            Some(_) => Target::User(id),
+            None if roles.contains(&id) => Target::Role(id),
+            None if channels.contains(&id) => Target::Channel(id),
+            None => Target::User(id),
+        };
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    if !invalid.is_empty() {
+        return Err(invalid);
+    }
+    Ok(targets)
+}
+
+fn type_names() -> String {
+    LogType::ALL.iter().map(|t| format!("`{}`", t.as_str())).collect::<Vec<_>>().join(", ")
+}
+
+/// Routes log types to channels and ignores users, roles or channels.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("set", "remove", "ignore", "unignore", "autocases", "show"),
+    subcommand_required
+)]
+pub async fn log(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Sends the given log types (or "all") to a channel.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn set(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel the logs go to"] channel: serenity::GuildChannel,
+    #[rest]
+    #[description = "Log types separated by spaces, or all"]
+    types: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id || !matches!(channel.kind, serenity::ChannelType::Text | serenity::ChannelType::News) {
+        ctx.say("Pick a text channel of this server.").await?;
+        return Ok(());
+    }
+    let types = match parse_types(&types) {
+        Ok(t) if !t.is_empty() => t,
+        Ok(_) => {
+            ctx.say(format!("Name at least one log type: {}, or all.", type_names())).await?;
+            return Ok(());
+        }
+        Err(unknown) => {
+            ctx.say(format!("Unknown log type(s) `{}`. Valid types: {}, or all.", unknown.join("`, `"), type_names())).await?;
+            return Ok(());
+        }
+    };
+    let store = LogStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    for t in &types {
+        config.channels.insert(*t, channel.id.get());
+    }
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("{} log type(s) now go to <#{}>.", types.len(), channel.id)).await?;
+    Ok(())
+}
+
+/// Stops logging the given log types (or "all").
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Log types separated by spaces, or all"]
+    types: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let types = match parse_types(&types) {
+        Ok(t) => t,
+        Err(unknown) => {
+            ctx.say(format!("Unknown log type(s) `{}`. Valid types: {}, or all.", unknown.join("`, `"), type_names())).await?;
+            return Ok(());
+        }
+    };
+    let store = LogStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let removed = types.iter().filter(|t| config.channels.remove(t).is_some()).count();
+    if removed == 0 {
+        ctx.say("None of these log types were being logged.").await?;
+        return Ok(());
+    }
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("Stopped logging {} log type(s).", removed)).await?;
+    Ok(())
+}
+
+/// Ignores users, roles or channels in all logs.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn ignore(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Users, roles or channels (mentions or IDs)"]
+    targets: String,
+) -> Result<(), crate::Error> {
+    update_ignored(ctx, &targets, true).await
+}
+
+/// Removes users, roles or channels from the ignore lists.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn unignore(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Users, roles or channels (mentions or IDs)"]
+    targets: String,
+) -> Result<(), crate::Error> {
+    update_ignored(ctx, &targets, false).await
+}
+
+async fn update_ignored(ctx: poise::Context<'_, crate::Data, crate::Error>, input: &str, add: bool) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let targets = match parse_targets(ctx, guild_id, input) {
+        Ok(t) if !t.is_empty() => t,
+        Ok(_) => {
+            ctx.say("Name at least one user, role or channel.").await?;
+            return Ok(());
+        }
+        Err(invalid) => {
+            ctx.say(format!("Couldn't read `{}` as a user, role or channel.", invalid.join("`, `"))).await?;
+            return Ok(());
+        }
+    };
+    let store = LogStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let mut changed = Vec::new();
+    for target in &targets {
+        let (list, id) = target.list(&mut config);
+        if add && !list.contains(&id) {
+            list.push(id);
+            changed.push(target.mention());
+        } else if !add && list.contains(&id) {
+            list.retain(|i| *i != id);
+            changed.push(target.mention());
+        }
+    }
+    if changed.is_empty() {
+        ctx.say(if add { "Everything named is already ignored." } else { "Nothing named was ignored." }).await?;
+        return Ok(());
+    }
+    store.set_config(guild_id.get(), &config)?;
+    let text = format!("{} {}.", if add { "Now ignoring" } else { "No longer ignoring" }, changed.join(", "));
+    ctx.send(poise::CreateReply::default().content(text).allowed_mentions(serenity::CreateAllowedMentions::new()))
+        .await?;
+    Ok(())
+}
+
+/// Opens cases for bans and kicks done in the Discord client instead of through the bot.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn autocases(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Whether manual bans and kicks get a case"] enabled: bool,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = LogStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.auto_cases = enabled;
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(if enabled {
+        "Bans and kicks done in the Discord client now get a case. The bot needs the View Audit Log permission."
+    } else {
+        "Bans and kicks done in the Discord client no longer get a case."
+    })
+    .await?;
+    Ok(())
+}
+
+/// Shows where each log type goes and what is ignored.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn show(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let config = LogStore::open(&ctx.data().db)?.config(guild_id.get())?;
+    let routes: Vec<String> = LogType::ALL
+        .iter()
+        .map(|t| match config.channels.get(t) {
+            Some(c) => format!("`{}` -> <#{}>", t.as_str(), c),
+            None => format!("`{}` -> off", t.as_str()),
+        })
+        .collect();
+    // Embed fields hold at most 1024 characters
+    let show = |ids: &[u64], fmt: fn(&u64) -> String| join_limited(&ids.iter().map(fmt).collect::<Vec<_>>(), ", ", 1024);
+    let embed = serenity::CreateEmbed::default()
+        .title("Logging")
+        .description(routes.join("\n"))
+        .field("Ignored users", show(&config.ignored_users, |id| format!("<@{}>", id)), false)
+        .field("Ignored roles", show(&config.ignored_roles, |id| format!("<@&{}>", id)), false)
+        .field("Ignored channels", show(&config.ignored_channels, |id| format!("<#{}>", id)), false)
+        .field("Cases for manual bans and kicks", if config.auto_cases { "on" } else { "off" }, false)
+        .color(0x3B82F6);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1 +1,5 @@
-pub mod log;
\ No newline at end of file
+pub mod audit;
+pub mod cache;
+pub mod delivery;
+pub mod events;
+pub mod log;
@@ -0,0 +1,193 @@
+// Five field cron expressions (`minute hour day-of-month month day-of-week`) for scheduled messages.
+// Fields take `*`, numbers, ranges `a-b`, steps `*/n` or `a-b/n` and comma separated lists.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+// Upper bound on skipped months, days, hours and minutes while searching the next match
+const MAX_STEPS: usize = 200_000;
+
+struct Field {
+    allowed: Vec<bool>,
+    /// Whether the field was `*`, which matters for the day-of-month/day-of-week rule.
+    any: bool,
+}
+
+impl Field {
+    fn parse(input: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut allowed = vec![false; max as usize + 1];
+        for part in input.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((r, s)) => (r, s.parse::<u32>().ok().filter(|s| *s > 0).ok_or(format!("`{}` has an invalid step.", part))?),
+                None => (part, 1),
+            };
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                (number(a, min, max)?, number(b, min, max)?)
+            } else {
+                let n = number(range, min, max)?;
+                // `5/15` means from 5 to the end in steps of 15
+                (n, if part.contains('/') { max } else { n })
+            };
+            if start > end {
+                return Err(format!("`{}` is an empty range.", part));
+            }
+            for v in (start..=end).step_by(step as usize) {
+                allowed[v as usize] = true;
+            }
+        }
+        Ok(Self { allowed, any: input == "*" })
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.allowed.get(value as usize).copied().unwrap_or(false)
+    }
+}
+
+fn number(input: &str, min: u32, max: u32) -> Result<u32, String> {
+    match input.parse::<u32>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("`{}` is not a number from {} to {}.", input, min, max)),
+    }
+}
+
+pub(crate) struct CronExpr {
+    minute: Field,
+    hour: Field,
+    day: Field,
+    month: Field,
+    weekday: Field,
+}
+
+impl CronExpr {
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = input.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err("Cron expressions have five fields: minute hour day-of-month month day-of-week.".to_string());
+        };
+        let mut weekday = Field::parse(weekday, 0, 7)?;
+        // Both 0 and 7 stand for Sunday
+        if weekday.allowed[7] {
+            weekday.allowed[0] = true;
+        }
+        let expr = Self {
+            minute: Field::parse(minute, 0, 59)?,
+            hour: Field::parse(hour, 0, 23)?,
+            day: Field::parse(day, 1, 31)?,
+            month: Field::parse(month, 1, 12)?,
+            weekday,
+        };
+        let probe = NaiveDate::from_ymd_opt(2000, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0));
+        if probe.and_then(|p| expr.next_after(p)).is_none() {
+            return Err("That expression never matches a real date.".to_string());
+        }
+        Ok(expr)
+    }
+
+    fn day_matches(&self, t: NaiveDateTime) -> bool {
+        let day = self.day.matches(t.day());
+        let weekday = self.weekday.matches(t.weekday().num_days_from_sunday());
+        // Like cron: when both day fields are restricted, either of them may match
+        match (self.day.any, self.weekday.any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute after `after`, in the same local time as `after`.
+    pub(crate) fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for _ in 0..MAX_STEPS {
+            if !self.month.matches(t.month()) {
+                let (y, m) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(y, m, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(t) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if !self.hour.matches(t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !self.minute.matches(t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    /// The next `n` runs after `from`.
+    fn runs(expr: &str, from: NaiveDateTime, n: usize) -> Vec<NaiveDateTime> {
+        let cron = CronExpr::parse(expr).unwrap();
+        let mut out = Vec::new();
+        let mut t = from;
+        for _ in 0..n {
+            t = cron.next_after(t).unwrap();
+            out.push(t);
+        }
+        out
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // 2026-01-01 is a Thursday: Fridays and the 13th both match
+        assert_eq!(
+            runs("0 0 13 * 5", at(2026, 1, 1, 0, 0), 4),
+            vec![at(2026, 1, 2, 0, 0), at(2026, 1, 9, 0, 0), at(2026, 1, 13, 0, 0), at(2026, 1, 16, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn unrestricted_weekday_leaves_day_of_month_alone() {
+        assert_eq!(runs("0 0 13 * *", at(2026, 1, 1, 0, 0), 2), vec![at(2026, 1, 13, 0, 0), at(2026, 2, 13, 0, 0)]);
+        assert_eq!(runs("0 0 * * 5", at(2026, 1, 1, 0, 0), 2), vec![at(2026, 1, 2, 0, 0), at(2026, 1, 9, 0, 0)]);
+    }
+
+    #[test]
+    fn single_value_with_step_runs_to_the_end() {
+        assert_eq!(
+            runs("5/15 * * * *", at(2026, 1, 1, 10, 0), 5),
+            vec![at(2026, 1, 1, 10, 5), at(2026, 1, 1, 10, 20), at(2026, 1, 1, 10, 35), at(2026, 1, 1, 10, 50), at(2026, 1, 1, 11, 5)]
+        );
+    }
+
+    #[test]
+    fn seven_is_sunday() {
+        let sunday = vec![at(2026, 1, 4, 12, 0)];
+        assert_eq!(runs("0 12 * * 7", at(2026, 1, 1, 0, 0), 1), sunday);
+        assert_eq!(runs("0 12 * * 0", at(2026, 1, 1, 0, 0), 1), sunday);
+        assert_eq!(runs("0 12 * * 6-7", at(2026, 1, 1, 0, 0), 2), vec![at(2026, 1, 3, 12, 0), at(2026, 1, 4, 12, 0)]);
+    }
+
+    #[test]
+    fn next_run_is_strictly_later() {
+        assert_eq!(runs("30 9 * * *", at(2026, 1, 1, 9, 30), 1), vec![at(2026, 1, 2, 9, 30)]);
+    }
+
+    #[test]
+    fn leap_days_are_found() {
+        assert_eq!(runs("0 0 29 2 *", at(2026, 1, 1, 0, 0), 1), vec![at(2028, 2, 29, 0, 0)]);
+    }
+
+    #[test]
+    fn never_matching_expressions_are_rejected() {
+        assert!(CronExpr::parse("0 0 30 2 *").is_err());
+        assert!(CronExpr::parse("0 0 31 4,6,9,11 *").is_err());
+        // With a weekday the February Mondays still match
+        assert!(CronExpr::parse("0 0 30 2 1").is_ok());
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        for expr in ["* * * *", "* * * * * *", "*/0 * * * *", "60 * * * *", "0 5-1 * * *", "0 0 0 * *", "a * * * *"] {
+            assert!(CronExpr::parse(expr).is_err(), "{} was accepted", expr);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub(crate) mod cron;
+pub(crate) mod render;
+pub mod schedule;
+pub mod send_message;
+pub mod send_template;
+pub mod template;
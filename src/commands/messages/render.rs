@@ -0,0 +1,329 @@
+// Turns message templates into messages: reads Discord message JSON, fills in variables and
+// delivers the result to a channel or a webhook.
+
+use crate::data::templates::store::Deployment;
+use poise::serenity_prelude as serenity;
+use serde_json::{Map, Value};
+
+// Discord limits of a single message
+const MAX_CONTENT: usize = 2000;
+const MAX_EMBEDS: usize = 10;
+const MAX_ROWS: usize = 5;
+
+/// The values filled in for the variables of a template.
+pub(crate) struct Variables {
+    pub user: Option<serenity::User>,
+    pub guild: String,
+    pub member_count: u64,
+}
+
+impl Variables {
+    /// Variables for a message sent in a guild on behalf of a user, from the cache with an HTTP fallback.
+    pub(crate) async fn load(
+        ctx: &serenity::Context,
+        guild_id: serenity::GuildId,
+        user: Option<&serenity::User>,
+    ) -> Result<Self, serenity::Error> {
+        let cached = ctx.cache.guild(guild_id).map(|g| (g.name.clone(), g.member_count));
+        let (guild, member_count) = match cached {
+            Some(c) => c,
+            None => {
+                let g = ctx.http.get_guild_with_counts(guild_id).await?;
+                (g.name, g.approximate_member_count.unwrap_or(0))
+            }
+        };
+        Ok(Self { user: user.cloned(), guild, member_count })
+    }
+
+    fn fill(&self, text: &str) -> String {
+        let mut out = text
+            .replace("{guild}", &self.guild)
+            .replace("{member_count}", &self.member_count.to_string())
+            .replace("{date}", &chrono::Utc::now().format("%Y-%m-%d").to_string());
+        if let Some(user) = &self.user {
+            out = out.replace("{user}", &format!("<@{}>", user.id)).replace("{user_name}", &user.name);
+        }
+        out
+    }
+}
+
+/// Help text listing the variables templates may use.
+pub(crate) const VARIABLES_HELP: &str =
+    "`{user}` mention of the sender, `{user_name}` their name, `{guild}` server name, `{member_count}`, `{date}` (YYYY-MM-DD)";
+
+/// Fills in the variables of every string in a message body.
+pub(crate) fn render(body: &Value, vars: &Variables) -> Value {
+    match body {
+        Value::String(s) => Value::String(vars.fill(s)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render(v, vars)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (k.clone(), render(v, vars))).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Reads a message body from JSON. Accepts a plain message object as well as exports that wrap it
+/// in `data` or `messages[0].data`. Only content, embeds and components are kept.
+pub(crate) fn parse_body(input: &str) -> Result<Value, String> {
+    let input = input.trim().trim_start_matches("```json").trim_start_matches("```").trim_end_matches("```");
+    let mut value: Value = serde_json::from_str(input).map_err(|e| format!("That isn't valid JSON: {}", e))?;
+    if let Some(first) = value.get("messages").and_then(|m| m.get(0)) {
+        value = first.clone();
+    }
+    if let Some(data) = value.get("data").filter(|d| d.is_object()) {
+        value = data.clone();
+    }
+    let Value::Object(source) = value else { return Err("The JSON has to be a message object.".to_string()) };
+
+    let mut body = Map::new();
+    for key in ["content", "embeds", "components"] {
+        if let Some(v) = source.get(key).filter(|v| !v.is_null()) {
+            body.insert(key.to_string(), v.clone());
+        }
+    }
+    let content = body.get("content").map(|c| c.as_str().ok_or("`content` has to be a string.")).transpose()?;
+    if content.is_some_and(|c| c.chars().count() > MAX_CONTENT) {
+        return Err(format!("The content is longer than {} characters.", MAX_CONTENT));
+    }
+    let embeds: Vec<serenity::Embed> = match body.get("embeds") {
+        Some(e) => serde_json::from_value(e.clone()).map_err(|e| format!("The embeds are invalid: {}", e))?,
+        None => Vec::new(),
+    };
+    if embeds.len() > MAX_EMBEDS {
+        return Err(format!("A message can have at most {} embeds.", MAX_EMBEDS));
+    }
+    let rows: Vec<serenity::ActionRow> = match body.get("components") {
+        Some(c) => serde_json::from_value(c.clone()).map_err(|e| format!("The components are invalid: {}", e))?,
+        None => Vec::new(),
+    };
+    if rows.len() > MAX_ROWS {
+        return Err(format!("A message can have at most {} component rows.", MAX_ROWS));
+    }
+    if content.is_none_or(|c| c.trim().is_empty()) && embeds.is_empty() {
+        return Err("The message needs content or at least one embed.".to_string());
+    }
+    Ok(Value::Object(body))
+}
+
+/// Builds an ephemeral-friendly reply showing a rendered body. Buttons are shown as they are,
+/// other components can only be shown once the message is really sent.
+pub(crate) fn preview(body: &Value) -> poise::CreateReply {
+    let mut reply = poise::CreateReply::default().allowed_mentions(serenity::CreateAllowedMentions::new());
+    if let Some(content) = body.get("content").and_then(Value::as_str) {
+        reply = reply.content(content);
+    }
+    let embeds: Vec<serenity::Embed> = body.get("embeds").and_then(|e| serde_json::from_value(e.clone()).ok()).unwrap_or_default();
+    reply.embeds = embeds.into_iter().map(serenity::CreateEmbed::from).collect();
+    let rows: Vec<serenity::ActionRow> = body.get("components").and_then(|c| serde_json::from_value(c.clone()).ok()).unwrap_or_default();
+    let buttons: Vec<serenity::CreateActionRow> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let buttons: Vec<serenity::CreateButton> = row
+                .components
+                .into_iter()
+                .filter_map(|c| match c {
+                    serenity::ActionRowComponent::Button(b) => Some(serenity::CreateButton::from(b)),
+                    _ => None,
+                })
+                .collect();
+            (!buttons.is_empty()).then_some(serenity::CreateActionRow::Buttons(buttons))
+        })
+        .collect();
+    reply.components(buttons)
+}
+
+/// Where a template is sent to.
+pub(crate) enum Destination {
+    Channel(serenity::ChannelId),
+    Webhook { id: serenity::WebhookId, token: String },
+}
+
+impl Destination {
+    /// Reads a channel mention, a channel ID or a webhook URL.
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(rest) = input.split("/api/webhooks/").nth(1) {
+            let mut parts = rest.split(['/', '?']);
+            let id = parts.next()?.parse::<u64>().ok()?;
+            let token = parts.next().filter(|t| !t.is_empty())?;
+            return Some(Self::Webhook { id: serenity::WebhookId::new(id), token: token.to_string() });
+        }
+        let id = input.strip_prefix("<#").and_then(|r| r.strip_suffix('>')).unwrap_or(input);
+        id.parse::<u64>().ok().filter(|id| *id != 0).map(|id| Self::Channel(serenity::ChannelId::new(id)))
+    }
+}
+
+/// Permissions of a member in a channel. Threads use their parent's permissions, with Send
+/// Messages in Threads standing in for Send Messages.
+pub(crate) async fn channel_permissions(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    channel_id: serenity::ChannelId,
+    user_id: serenity::UserId,
+) -> Option<serenity::Permissions> {
+    let member = guild_id.member(ctx, user_id).await.ok()?;
+    let guild = ctx.cache.guild(guild_id)?;
+    if let Some(channel) = guild.channels.get(&channel_id) {
+        return Some(guild.user_permissions_in(channel, &member));
+    }
+    let parent = guild.threads.iter().find(|t| t.id == channel_id)?.parent_id?;
+    let mut permissions = guild.user_permissions_in(guild.channels.get(&parent)?, &member);
+    permissions.set(serenity::Permissions::SEND_MESSAGES, permissions.send_messages_in_threads());
+    Some(permissions)
+}
+
+/// Limits who a body may ping. Without `mass_mentions` only users can be pinged, so @everyone,
+/// @here and role mentions stay plain text.
+fn limit_mentions(body: &mut Value, mass_mentions: bool) {
+    let Value::Object(map) = body else { return };
+    if mass_mentions && map.contains_key("allowed_mentions") {
+        return;
+    }
+    let parse = if mass_mentions { vec!["users", "roles", "everyone"] } else { vec!["users"] };
+    map.insert("allowed_mentions".to_string(), serde_json::json!({ "parse": parse }));
+}
+
+/// Sends a rendered body. Webhooks may use a custom name and avatar; channel messages ignore them.
+/// `mass_mentions` allows @everyone and role pings, only for senders with Mention Everyone.
+pub(crate) async fn deliver(
+    http: &serenity::Http,
+    destination: &Destination,
+    body: &Value,
+    username: Option<&str>,
+    avatar_url: Option<&str>,
+    mass_mentions: bool,
+) -> Result<Option<serenity::Message>, serenity::Error> {
+    let mut body = body.clone();
+    limit_mentions(&mut body, mass_mentions);
+    match destination {
+        Destination::Channel(channel_id) => Ok(Some(http.send_message(*channel_id, vec![], &body).await?)),
+        Destination::Webhook { id, token } => {
+            if let Value::Object(map) = &mut body {
+                if let Some(name) = username {
+                    map.insert("username".to_string(), Value::from(name));
+                }
+                if let Some(url) = avatar_url {
+                    map.insert("avatar_url".to_string(), Value::from(url));
+                }
+            }
+            http.execute_webhook(*id, None, token, true, vec![], &body).await
+        }
+    }
+}
+
+/// Edits a message sent from a template. Keys missing from the body are cleared, so content or
+/// components removed from the template disappear from the message too.
+pub(crate) async fn edit_deployed(
+    http: &serenity::Http,
+    deployment: &Deployment,
+    body: &Value,
+    mass_mentions: bool,
+) -> Result<(), serenity::Error> {
+    let mut body = body.clone();
+    limit_mentions(&mut body, mass_mentions);
+    if let Value::Object(map) = &mut body {
+        map.entry("content").or_insert_with(|| Value::from(""));
+        map.entry("embeds").or_insert_with(|| Value::Array(Vec::new()));
+        map.entry("components").or_insert_with(|| Value::Array(Vec::new()));
+    }
+    let channel_id = serenity::ChannelId::new(deployment.channel_id);
+    let message_id = serenity::MessageId::new(deployment.message_id);
+    match &deployment.webhook {
+        Some((id, token)) => {
+            http.edit_webhook_message(serenity::WebhookId::new(*id), None, token, message_id, &body, vec![]).await?;
+        }
+        None => {
+            http.edit_message(channel_id, message_id, &body, vec![]).await?;
+        }
+    }
+    Ok(())
+}
+
+/// A line diff of two message bodies as pretty JSON, written like `diff -u` without headers.
+/// Unchanged lines are kept only around changes.
+pub(crate) fn diff(old: &Value, new: &Value) -> String {
+    let old_text = serde_json::to_string_pretty(old).unwrap_or_default();
+    let new_text = serde_json::to_string_pretty(new).unwrap_or_default();
+    let a: Vec<&str> = old_text.lines().collect();
+    let b: Vec<&str> = new_text.lines().collect();
+
+    // Longest common subsequence table, filled from the end
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let mut lines: Vec<(char, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            lines.push(('+', b[j]));
+            j += 1;
+        } else {
+            lines.push(('-', a[i]));
+            i += 1;
+        }
+    }
+
+    const CONTEXT: usize = 2;
+    let changed: Vec<usize> = lines.iter().enumerate().filter(|(_, (k, _))| *k != ' ').map(|(n, _)| n).collect();
+    let mut out = Vec::new();
+    let mut last_shown: Option<usize> = None;
+    for (n, (kind, line)) in lines.iter().enumerate() {
+        if !changed.iter().any(|c| c.abs_diff(n) <= CONTEXT) {
+            continue;
+        }
+        if last_shown.is_some_and(|l| n > l + 1) {
+            out.push("@@".to_string());
+        }
+        out.push(format!("{}{}", kind, line));
+        last_shown = Some(n);
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_of_equal_values_is_empty() {
+        let body = json!({ "content": "hi", "embeds": [{ "title": "a" }] });
+        assert_eq!(diff(&body, &body), "");
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let out = diff(&json!({ "content": "old" }), &json!({ "content": "new" }));
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"-  \"content\": \"old\""), "{}", out);
+        assert!(lines.contains(&"+  \"content\": \"new\""), "{}", out);
+        assert!(lines.contains(&" {"), "{}", out);
+    }
+
+    #[test]
+    fn diff_shows_added_and_removed_keys() {
+        let out = diff(&json!({ "a": 1, "b": 2 }), &json!({ "a": 1, "c": 3 }));
+        assert!(out.lines().any(|l| l.starts_with('-') && l.contains("\"b\": 2")), "{}", out);
+        assert!(out.lines().any(|l| l.starts_with('+') && l.contains("\"c\": 3")), "{}", out);
+        assert!(!out.lines().any(|l| l.starts_with(['-', '+']) && l.contains("\"a\"")), "{}", out);
+    }
+
+    #[test]
+    fn diff_skips_unchanged_lines_far_from_changes() {
+        let old = json!({ "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 6, "g": 7, "h": 8, "i": 9, "j": 10 });
+        let mut new = old.clone();
+        new["a"] = json!(0);
+        new["j"] = json!(0);
+        let out = diff(&old, &new);
+        assert!(out.lines().any(|l| l == "@@"), "{}", out);
+        assert!(!out.contains("\"e\": 5"), "{}", out);
+        // Two lines of context around each change
+        assert!(out.contains("\"c\": 3") && out.contains("\"h\": 8"), "{}", out);
+    }
+}
@@ -0,0 +1,489 @@
+// Schedules one-off and recurring messages.
+// schedule once <channel> <when> <text|template:name>
+// schedule every <channel> <interval> <text|template:name>
+// schedule cron <channel> "<min hour day month weekday>" <text|template:name>
+// schedule <list|pause|resume|delete|catchup|timezone> ...
+
+use super::cron::CronExpr;
+use super::render::{channel_permissions, deliver, render, Destination, Variables};
+use super::send_template::check_can_post;
+use crate::commands::moderation::cases::truncate;
+use crate::commands::moderation::utils::{format_duration, parse_duration};
+use crate::commands::pagination::paginate_embeds;
+use crate::data::schedules::store::{CatchUp, Schedule, ScheduleContent, ScheduleStore, ScheduleTiming};
+use crate::data::templates::store::TemplateStore;
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use poise::serenity_prelude as serenity;
+
+const MIN_INTERVAL_SECS: u64 = 5 * 60;
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum CatchUpChoice {
+    Skip,
+    Once,
+    All,
+}
+
+impl From<CatchUpChoice> for CatchUp {
+    fn from(c: CatchUpChoice) -> Self {
+        match c {
+            CatchUpChoice::Skip => CatchUp::Skip,
+            CatchUpChoice::Once => CatchUp::Once,
+            CatchUpChoice::All => CatchUp::All,
+        }
+    }
+}
+
+/// Reads IANA timezone names like `Europe/Berlin`, ignoring case.
+fn parse_timezone(input: &str) -> Option<Tz> {
+    let input = input.trim();
+    chrono_tz::TZ_VARIANTS.iter().find(|tz| tz.name().eq_ignore_ascii_case(input)).copied()
+}
+
+/// Suggests timezone names containing what was typed so far.
+async fn autocomplete_timezone<'a>(_ctx: poise::Context<'a, crate::Data, crate::Error>, partial: &'a str) -> Vec<String> {
+    let partial = partial.to_lowercase();
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| tz.name())
+        .filter(|name| name.to_lowercase().contains(&partial))
+        .take(25)
+        .map(str::to_string)
+        .collect()
+}
+
+/// The first moment a local time exists at or after `after`, None when it doesn't exist (skipped by a
+/// DST change). Local times that happen twice count at their first occurrence that isn't over yet.
+fn local_to_utc(tz: Tz, local: chrono::NaiveDateTime, after: i64) -> Option<i64> {
+    let mapped = tz.from_local_datetime(&local);
+    [mapped.earliest(), mapped.latest()].into_iter().flatten().map(|t| t.timestamp()).find(|t| *t > after)
+}
+
+/// The run after `after` (unix time), None for one-off schedules. Cron expressions are read in the
+/// guild's timezone, local times skipped by a DST change don't run.
+pub(crate) fn next_run(timing: &ScheduleTiming, after: i64, tz: Tz) -> Option<i64> {
+    match timing {
+        ScheduleTiming::Once => None,
+        ScheduleTiming::Interval { secs } => Some(after + *secs as i64),
+        ScheduleTiming::Cron { expr } => {
+            let cron = CronExpr::parse(expr).ok()?;
+            let mut local = tz.timestamp_opt(after, 0).single()?.naive_local();
+            loop {
+                local = cron.next_after(local)?;
+                if let Some(at) = local_to_utc(tz, local, after) {
+                    return Some(at);
+                }
+            }
+        }
+    }
+}
+
+/// Reads a point in time: `YYYY-MM-DD HH:MM` or `HH:MM` in the guild's timezone, or a duration from now.
+fn parse_when(input: &str, tz: Tz, now: i64) -> Option<i64> {
+    let input = input.trim();
+    if let Ok(t) = chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M") {
+        return tz.from_local_datetime(&t).earliest().map(|t| t.timestamp());
+    }
+    if let Ok(t) = chrono::NaiveTime::parse_from_str(input, "%H:%M") {
+        let today = tz.timestamp_opt(now, 0).single()?.date_naive();
+        // A time that already passed today means tomorrow
+        return [today, today.succ_opt()?].into_iter().find_map(|day| local_to_utc(tz, day.and_time(t), now));
+    }
+    parse_duration(input.trim_start_matches("in ")).map(|d| now + d.as_secs() as i64)
+}
+
+/// Reads the text to send, or `template:<name>` for a stored template.
+fn parse_content(input: &str) -> ScheduleContent {
+    match input.trim().strip_prefix("template:") {
+        Some(name) => ScheduleContent::Template(name.trim().to_lowercase()),
+        None => ScheduleContent::Text(input.trim().to_string()),
+    }
+}
+
+fn describe(schedule: &Schedule, tz: Tz) -> String {
+    let timing = match &schedule.timing {
+        ScheduleTiming::Once => "once".to_string(),
+        ScheduleTiming::Interval { secs } => format!("every {}", format_duration(*secs)),
+        ScheduleTiming::Cron { expr } => format!("`{}` ({})", expr, tz.name()),
+    };
+    let content = match &schedule.content {
+        ScheduleContent::Text(t) => truncate(t, 60),
+        ScheduleContent::Template(name) => format!("template `{}`", name),
+    };
+    let state = if schedule.paused { "paused".to_string() } else { format!("next <t:{}:R>", schedule.next_run) };
+    format!("**#{}** {} in <#{}>, {}, catch-up {:?}\n{}", schedule.id, timing, schedule.channel_id, state, schedule.catch_up, content)
+}
+
+/// Sends the message of a schedule in its channel.
+pub(crate) async fn send(ctx: &serenity::Context, db: &sled::Db, schedule: &Schedule) -> Result<(), crate::Error> {
+    let guild_id = serenity::GuildId::new(schedule.guild_id);
+    let creator = ctx.http.get_user(serenity::UserId::new(schedule.created_by)).await.ok();
+    let vars = Variables::load(ctx, guild_id, creator.as_ref()).await?;
+    let channel_id = serenity::ChannelId::new(schedule.channel_id);
+    let destination = Destination::Channel(channel_id);
+    // Checked on every run, so creators who lost Mention Everyone can't ping with it anymore
+    let mass = channel_permissions(ctx, guild_id, channel_id, serenity::UserId::new(schedule.created_by))
+        .await
+        .is_some_and(|p| p.mention_everyone());
+    match &schedule.content {
+        ScheduleContent::Text(text) => {
+            deliver(&ctx.http, &destination, &render(&serde_json::json!({ "content": text }), &vars), None, None, mass).await?;
+        }
+        ScheduleContent::Template(name) => {
+            let Some(template) = TemplateStore::open(db)?.get(schedule.guild_id, name)? else {
+                return Err(format!("template `{}` no longer exists", name).into());
+            };
+            // Not recorded as a deployment: recurring schedules would pile up entries for template sync
+            deliver(&ctx.http, &destination, &render(&template.body, &vars), None, None, mass).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks and saves a new schedule, then tells when it first runs.
+async fn create(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    channel: serenity::GuildChannel,
+    timing: ScheduleTiming,
+    first_run: i64,
+    content: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel is not part of this server.").await?;
+        return Ok(());
+    }
+    if !check_can_post(ctx, channel.id).await? {
+        return Ok(());
+    }
+    let content = parse_content(&content);
+    match &content {
+        ScheduleContent::Text(t) if t.is_empty() || t.chars().count() > 2000 => {
+            ctx.say("Messages need 1 to 2000 characters.").await?;
+            return Ok(());
+        }
+        ScheduleContent::Template(name) if TemplateStore::open(&ctx.data().db)?.get(guild_id.get(), name)?.is_none() => {
+            ctx.say(format!("There is no template called `{}`.", name)).await?;
+            return Ok(());
+        }
+        _ => {}
+    }
+    let store = ScheduleStore::open(&ctx.data().db)?;
+    let schedule = Schedule {
+        id: store.next_id(guild_id.get())?,
+        guild_id: guild_id.get(),
+        channel_id: channel.id.get(),
+        content,
+        timing,
+        next_run: first_run,
+        last_run: None,
+        paused: false,
+        catch_up: CatchUp::default(),
+        created_by: ctx.author().id.get(),
+    };
+    store.save(&schedule)?;
+    ctx.say(format!("Schedule #{} created, it first runs <t:{}:F>.", schedule.id, first_run)).await?;
+    Ok(())
+}
+
+/// Loads a schedule of this guild. Replies and returns None when it doesn't exist.
+async fn load(ctx: poise::Context<'_, crate::Data, crate::Error>, id: u64) -> Result<Option<(ScheduleStore, Schedule)>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let store = ScheduleStore::open(&ctx.data().db)?;
+    match store.get(guild_id.get(), id)? {
+        Some(s) => Ok(Some((store, s))),
+        None => {
+            ctx.say(format!("Schedule #{} does not exist.", id)).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Sends messages at a set time or on a recurring schedule.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "schedule",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    subcommands("once", "every", "cron", "list", "pause", "resume", "delete", "catchup", "timezone"),
+    subcommand_required
+)]
+pub async fn schedule(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Sends a message once, at a date and time or after a delay.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn once(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to send to"] channel: serenity::GuildChannel,
+    #[description = "YYYY-MM-DD HH:MM, HH:MM or a delay like 2h"] when: String,
+    #[rest]
+    #[description = "Text to send, or template:<name>"]
+    content: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let tz = ScheduleStore::open(&ctx.data().db)?.config(guild_id.get())?.tz();
+    let now = chrono::Utc::now().timestamp();
+    match parse_when(&when, tz, now) {
+        Some(at) if at > now => create(ctx, channel, ScheduleTiming::Once, at, content).await,
+        Some(_) => {
+            ctx.say("That time has already passed.").await?;
+            Ok(())
+        }
+        None => {
+            ctx.say(format!("Couldn't read `{}` as a time. Use `YYYY-MM-DD HH:MM`, `HH:MM` or a delay like `2h`.", when)).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Sends a message repeatedly at a fixed interval, starting one interval from now.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn every(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to send to"] channel: serenity::GuildChannel,
+    #[description = "Interval like 6h or 1d"] interval: String,
+    #[rest]
+    #[description = "Text to send, or template:<name>"]
+    content: String,
+) -> Result<(), crate::Error> {
+    let secs = match parse_duration(&interval) {
+        Some(d) if d.as_secs() >= MIN_INTERVAL_SECS => d.as_secs(),
+        Some(_) => {
+            ctx.say(format!("Intervals have to be at least {}.", format_duration(MIN_INTERVAL_SECS))).await?;
+            return Ok(());
+        }
+        None => {
+            ctx.say(format!("`{}` is not an interval. Use something like `6h` or `1d`.", interval)).await?;
+            return Ok(());
+        }
+    };
+    let first = chrono::Utc::now().timestamp() + secs as i64;
+    create(ctx, channel, ScheduleTiming::Interval { secs }, first, content).await
+}
+
+/// Sends a message whenever a cron expression matches, in the server's timezone.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn cron(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to send to"] channel: serenity::GuildChannel,
+    #[description = "minute hour day month weekday, e.g. 0 9 * * 1-5"] expression: String,
+    #[rest]
+    #[description = "Text to send, or template:<name>"]
+    content: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if let Err(problem) = CronExpr::parse(&expression) {
+        ctx.say(problem).await?;
+        return Ok(());
+    }
+    let expr = expression.split_whitespace().collect::<Vec<_>>().join(" ");
+    let tz = ScheduleStore::open(&ctx.data().db)?.config(guild_id.get())?.tz();
+    let timing = ScheduleTiming::Cron { expr };
+    let Some(first) = next_run(&timing, chrono::Utc::now().timestamp(), tz) else {
+        ctx.say("That expression has no upcoming run.").await?;
+        return Ok(());
+    };
+    create(ctx, channel, timing, first, content).await
+}
+
+/// Lists the scheduled messages of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = ScheduleStore::open(&ctx.data().db)?;
+    let schedules = store.list(guild_id.get())?;
+    if schedules.is_empty() {
+        ctx.say("This server has no scheduled messages.").await?;
+        return Ok(());
+    }
+    let tz = store.config(guild_id.get())?.tz();
+    let pages = schedules
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            serenity::CreateEmbed::default()
+                .title(format!("Scheduled messages ({})", schedules.len()))
+                .description(chunk.iter().map(|s| describe(s, tz)).collect::<Vec<_>>().join("\n\n"))
+                .footer(serenity::CreateEmbedFooter::new(format!("Timezone: {}", tz.name())))
+                .color(0x3B82F6)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
+
+/// Pauses a scheduled message.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn pause(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Schedule ID"] id: u64,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, id).await? else { return Ok(()) };
+    existing.paused = true;
+    store.save(&existing)?;
+    ctx.say(format!("Schedule #{} is paused.", id)).await?;
+    Ok(())
+}
+
+/// Resumes a paused scheduled message. Runs missed while paused are skipped.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn resume(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Schedule ID"] id: u64,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, id).await? else { return Ok(()) };
+    let now = chrono::Utc::now().timestamp();
+    if existing.next_run <= now {
+        let tz = store.config(existing.guild_id)?.tz();
+        let next = match &existing.timing {
+            ScheduleTiming::Once => None,
+            ScheduleTiming::Interval { secs } => Some(now + *secs as i64),
+            ScheduleTiming::Cron { .. } => next_run(&existing.timing, now, tz),
+        };
+        let Some(next) = next else {
+            store.remove(existing.guild_id, id)?;
+            ctx.say(format!("Schedule #{} was due while paused and has been deleted.", id)).await?;
+            return Ok(());
+        };
+        existing.next_run = next;
+    }
+    existing.paused = false;
+    store.save(&existing)?;
+    ctx.say(format!("Schedule #{} resumed, it runs next <t:{}:F>.", id, existing.next_run)).await?;
+    Ok(())
+}
+
+/// Deletes a scheduled message.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Schedule ID"] id: u64,
+) -> Result<(), crate::Error> {
+    let Some((store, existing)) = load(ctx, id).await? else { return Ok(()) };
+    store.remove(existing.guild_id, id)?;
+    ctx.say(format!("Schedule #{} deleted.", id)).await?;
+    Ok(())
+}
+
+/// Sets what happens to runs missed while the bot was offline.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn catchup(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Schedule ID"] id: u64,
+    #[description = "Skip missed runs, send them once, or send all of them"] policy: CatchUpChoice,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, id).await? else { return Ok(()) };
+    existing.catch_up = policy.into();
+    store.save(&existing)?;
+    let text = match existing.catch_up {
+        CatchUp::Skip => "skips missed runs",
+        CatchUp::Once => "sends missed runs as one message",
+        CatchUp::All => "sends every missed run",
+    };
+    ctx.say(format!("Schedule #{} now {}.", id, text)).await?;
+    Ok(())
+}
+
+/// Sets the timezone used for schedule times and cron expressions.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn timezone(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Timezone like Europe/Berlin or America/New_York"]
+    #[autocomplete = "autocomplete_timezone"]
+    name: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(tz) = parse_timezone(&name) else {
+        ctx.say(format!("`{}` is not a timezone. Use a name like `Europe/Berlin`, `America/New_York` or `UTC`.", name)).await?;
+        return Ok(());
+    };
+    let store = ScheduleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let old = config.tz();
+    config.timezone = Some(tz.name().to_string());
+    store.set_config(guild_id.get(), &config)?;
+
+    // Cron schedules run at local times, so their next run moves with the timezone
+    let now = chrono::Utc::now().timestamp();
+    for mut s in store.list(guild_id.get())? {
+        if matches!(s.timing, ScheduleTiming::Cron { .. })
+            && let Some(next) = next_run(&s.timing, now, tz)
+        {
+            s.next_run = next;
+            store.save(&s)?;
+        }
+    }
+    ctx.say(format!("Schedules now use {} (was {}).", tz.name(), old.name())).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap().and_utc().timestamp()
+    }
+
+    fn cron_runs(expr: &str, tz: Tz, from: i64, n: usize) -> Vec<i64> {
+        let timing = ScheduleTiming::Cron { expr: expr.to_string() };
+        let mut out = Vec::new();
+        let mut t = from;
+        for _ in 0..n {
+            t = next_run(&timing, t, tz).unwrap();
+            out.push(t);
+        }
+        out
+    }
+
+    #[test]
+    fn cron_follows_daylight_saving_time() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // 09:00 local is 08:00 UTC in winter and 07:00 UTC in summer
+        assert_eq!(
+            cron_runs("0 9 * * *", berlin, utc(2026, 3, 28, 12, 0), 2),
+            vec![utc(2026, 3, 29, 7, 0), utc(2026, 3, 30, 7, 0)]
+        );
+    }
+
+    #[test]
+    fn skipped_local_times_do_not_run() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // 02:30 doesn't exist on 2026-03-29
+        assert_eq!(
+            cron_runs("30 2 * * *", berlin, utc(2026, 3, 28, 0, 0), 2),
+            vec![utc(2026, 3, 28, 1, 30), utc(2026, 3, 30, 0, 30)]
+        );
+    }
+
+    #[test]
+    fn repeated_local_times_run_once() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        // 02:30 happens twice on 2026-10-25
+        assert_eq!(
+            cron_runs("30 2 * * *", berlin, utc(2026, 10, 24, 12, 0), 2),
+            vec![utc(2026, 10, 25, 0, 30), utc(2026, 10, 26, 1, 30)]
+        );
+    }
+
+    #[test]
+    fn times_are_read_in_the_timezone() {
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+        let now = utc(2026, 1, 1, 0, 0);
+        assert_eq!(parse_when("2026-01-02 09:00", tokyo, now), Some(utc(2026, 1, 2, 0, 0)));
+        // 08:00 in Tokyo already passed (it's 09:00 there), so it means tomorrow
+        assert_eq!(parse_when("08:00", tokyo, now), Some(utc(2026, 1, 1, 23, 0)));
+        assert_eq!(parse_when("10:00", tokyo, now), Some(utc(2026, 1, 1, 1, 0)));
+    }
+
+    #[test]
+    fn timezone_names_ignore_case() {
+        assert_eq!(parse_timezone("europe/berlin").map(|tz| tz.name()), Some("Europe/Berlin"));
+        assert_eq!(parse_timezone("UTC").map(|tz| tz.name()), Some("UTC"));
+        assert!(parse_timezone("UTC+2").is_none());
+    }
+}
@@ -1,2 +1,46 @@
 // Sends a message into a specific channel.
-// sendmessage [channel] <message>
\ No newline at end of file
+// sendmessage [channel] <message>
+
+use super::render::{deliver, render, Destination, Variables};
+use super::send_template::{check_can_post, mass_mentions};
+use poise::serenity_prelude as serenity;
+
+/// Sends a message as the bot, with template variables filled in.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "sendmessage",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn send_message(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel (default: this channel)"] channel: Option<serenity::GuildChannel>,
+    #[rest]
+    #[description = "Message to send"]
+    message: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.as_ref().is_some_and(|c| c.guild_id != guild_id) {
+        ctx.say("That channel is not part of this server.").await?;
+        return Ok(());
+    }
+    if message.trim().is_empty() || message.chars().count() > 2000 {
+        ctx.say("Messages need 1 to 2000 characters.").await?;
+        return Ok(());
+    }
+    let channel_id = channel.map(|c| c.id).unwrap_or(ctx.channel_id());
+    if !check_can_post(ctx, channel_id).await? {
+        return Ok(());
+    }
+    let destination = Destination::Channel(channel_id);
+    let mass = mass_mentions(ctx, &destination).await;
+    let vars = Variables::load(ctx.serenity_context(), guild_id, Some(ctx.author())).await?;
+    let body = render(&serde_json::json!({ "content": message }), &vars);
+    let text = match deliver(ctx.http(), &destination, &body, None, None, mass).await {
+        Ok(_) => format!("Sent to <#{}>.", channel_id),
+        Err(e) => format!("Couldn't send the message: {}", e),
+    };
+    ctx.send(poise::CreateReply::default().content(text).ephemeral(true)).await?;
+    Ok(())
+}
@@ -1,2 +1,166 @@
 // Sends a message template into a specific channel.
-// sendtemplate [[channel|webhook url] <template name>]
\ No newline at end of file
+// sendtemplate <template name> [channel|webhook url] [username] [avatar url] [preview]
+
+use super::render::{channel_permissions, deliver, preview as preview_reply, render, Destination, Variables};
+use super::template::load;
+use crate::data::templates::store::{Deployment, MessageTemplate, TemplateStore};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+/// Whether the author can see and post in a channel, so the bot can't be used to reach channels
+/// they can't. Replies with the problem when not.
+pub(crate) async fn check_can_post(ctx: poise::Context<'_, crate::Data, crate::Error>, channel_id: serenity::ChannelId) -> Result<bool, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(false) };
+    let needed = serenity::Permissions::VIEW_CHANNEL | serenity::Permissions::SEND_MESSAGES;
+    let permissions = channel_permissions(ctx.serenity_context(), guild_id, channel_id, ctx.author().id).await;
+    if permissions.is_some_and(|p| p.contains(needed)) {
+        return Ok(true);
+    }
+    ctx.say(format!("You need to be able to view and send messages in <#{}> yourself.", channel_id)).await?;
+    Ok(false)
+}
+
+/// Whether messages the author sends to a destination may ping @everyone and roles: only with
+/// Mention Everyone there, or in this channel for webhooks.
+pub(crate) async fn mass_mentions(ctx: poise::Context<'_, crate::Data, crate::Error>, destination: &Destination) -> bool {
+    let Some(guild_id) = ctx.guild_id() else { return false };
+    let channel_id = match destination {
+        Destination::Channel(c) => *c,
+        Destination::Webhook { .. } => ctx.channel_id(),
+    };
+    channel_permissions(ctx.serenity_context(), guild_id, channel_id, ctx.author().id)
+        .await
+        .is_some_and(|p| p.mention_everyone())
+}
+
+/// Reads where to send a message. Channels have to belong to this server and be open to the author.
+/// Replies with the problem and returns None when the destination can't be used.
+pub(crate) async fn destination_arg(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    input: Option<&str>,
+) -> Result<Option<Destination>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let Some(input) = input else { return Ok(Some(Destination::Channel(ctx.channel_id()))) };
+    let Some(destination) = Destination::parse(input) else {
+        ctx.say(format!("`{}` is neither a channel nor a webhook URL.", input)).await?;
+        return Ok(None);
+    };
+    if let Destination::Channel(channel_id) = &destination {
+        let in_guild = match channel_id.to_channel(ctx).await {
+            Ok(c) => c.guild().is_some_and(|c| c.guild_id == guild_id),
+            Err(_) => false,
+        };
+        if !in_guild {
+            ctx.say(format!("<#{}> is not a channel of this server.", channel_id)).await?;
+            return Ok(None);
+        }
+        if !check_can_post(ctx, *channel_id).await? {
+            return Ok(None);
+        }
+    }
+    Ok(Some(destination))
+}
+
+/// Sends a prompt with confirm and cancel buttons, only to the author. True when confirmed; the
+/// buttons are removed either way.
+pub(crate) async fn confirm(ctx: poise::Context<'_, crate::Data, crate::Error>, prompt: poise::CreateReply, label: &str) -> Result<bool, crate::Error> {
+    let confirm_id = format!("{}_confirm", ctx.id());
+    let cancel_id = format!("{}_cancel", ctx.id());
+    let buttons = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(&confirm_id).label(label).style(serenity::ButtonStyle::Success),
+        serenity::CreateButton::new(&cancel_id).label("Cancel").style(serenity::ButtonStyle::Danger),
+    ]);
+    let handle = ctx.send(prompt.components(vec![buttons]).ephemeral(true)).await?;
+    let prefix = ctx.id().to_string();
+    let Some(mci) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(120))
+        .filter(move |mci| mci.data.custom_id.starts_with(&prefix))
+        .await
+    else {
+        handle.edit(ctx, poise::CreateReply::default().content("Timed out, nothing was done.").components(vec![])).await?;
+        return Ok(false);
+    };
+    let confirmed = mci.data.custom_id == confirm_id;
+    let mut update = serenity::CreateInteractionResponseMessage::new().components(vec![]);
+    if !confirmed {
+        update = update.content("Cancelled, nothing was done.").embeds(vec![]);
+    }
+    mci.create_response(ctx, serenity::CreateInteractionResponse::UpdateMessage(update)).await?;
+    Ok(confirmed)
+}
+
+/// Shows a rendered message only to the author and asks whether to send it. True when confirmed.
+pub(crate) async fn confirm_preview(ctx: poise::Context<'_, crate::Data, crate::Error>, body: &serde_json::Value) -> Result<bool, crate::Error> {
+    ctx.send(preview_reply(body).ephemeral(true)).await?;
+    confirm(ctx, poise::CreateReply::default().content("Send this message?"), "Send").await
+}
+
+/// Sends a message template to a channel or a webhook.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "sendtemplate",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES"
+)]
+pub async fn send_template(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+    #[description = "Channel or webhook URL (default: this channel)"] destination: Option<String>,
+    #[description = "Name shown on webhook messages"] username: Option<String>,
+    #[description = "Avatar URL shown on webhook messages"] avatar_url: Option<String>,
+    #[description = "Preview the message before sending it"] preview: Option<bool>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some((_, template)) = load(ctx, &name).await? else { return Ok(()) };
+    let Some(destination) = destination_arg(ctx, destination.as_deref()).await? else { return Ok(()) };
+    let vars = Variables::load(ctx.serenity_context(), guild_id, Some(ctx.author())).await?;
+    let body = render(&template.body, &vars);
+    if preview.unwrap_or(false) && !confirm_preview(ctx, &body).await? {
+        return Ok(());
+    }
+
+    let mass = mass_mentions(ctx, &destination).await;
+    let sent = deliver(ctx.http(), &destination, &body, username.as_deref(), avatar_url.as_deref(), mass).await;
+    if let Ok(Some(message)) = &sent {
+        record_deployment(&ctx.data().db, &template, &destination, message, ctx.author().id.get(), body)?;
+    }
+    let text = match sent {
+        Ok(_) => match &destination {
+            Destination::Channel(c) => format!("Sent template `{}` to <#{}>.", template.name, c),
+            Destination::Webhook { .. } => format!("Sent template `{}` through the webhook.", template.name),
+        },
+        Err(e) => format!("Couldn't send template `{}`: {}", template.name, e),
+    };
+    ctx.send(poise::CreateReply::default().content(text).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Remembers a message sent from a template so `template sync` can edit it later.
+fn record_deployment(
+    db: &sled::Db,
+    template: &MessageTemplate,
+    destination: &Destination,
+    message: &serenity::Message,
+    sent_by: u64,
+    rendered: serde_json::Value,
+) -> Result<(), crate::Error> {
+    let store = TemplateStore::open(db)?;
+    // Reloaded so deployments recorded meanwhile aren't lost
+    let Some(mut current) = store.get(template.guild_id, &template.name)? else { return Ok(()) };
+    current.deployments.push(Deployment {
+        channel_id: message.channel_id.get(),
+        message_id: message.id.get(),
+        webhook: match destination {
+            Destination::Webhook { id, token } => Some((id.get(), token.clone())),
+            Destination::Channel(_) => None,
+        },
+        sent_by,
+        sent_at: chrono::Utc::now().timestamp(),
+        rendered,
+    });
+    store.save(&current)?;
+    Ok(())
+}
@@ -0,0 +1,329 @@
+// Manages message templates.
+// template <create|edit> <name> <json|json file>
+// template <delete|show|preview> <name>
+// template list
+// template sync <name>
+
+use super::render::{diff, edit_deployed, parse_body, preview as preview_reply, render, Destination, Variables, VARIABLES_HELP};
+use super::send_template::{confirm, mass_mentions};
+use crate::commands::moderation::cases::truncate;
+use crate::commands::pagination::paginate_embeds;
+use crate::data::templates::store::{MessageTemplate, TemplateStore};
+use poise::serenity_prelude as serenity;
+
+const MAX_NAME: usize = 32;
+const PAGE_SIZE: usize = 15;
+// Room for the diff in the preview embed, the full diff is attached when it's longer
+const MAX_DIFF: usize = 3800;
+// Discord error codes of a deleted message and a deleted webhook
+const UNKNOWN_MESSAGE: isize = 10008;
+const UNKNOWN_WEBHOOK: isize = 10015;
+
+/// Template names are short lowercase words so they are easy to type in prefix commands.
+pub(crate) fn normalize_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || name.chars().count() > MAX_NAME {
+        return Err(format!("Template names have 1 to {} characters.", MAX_NAME));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("Template names may only use letters, digits, `-` and `_`.".to_string());
+    }
+    Ok(name)
+}
+
+/// Loads a template of this guild. Replies and returns None when it doesn't exist.
+pub(crate) async fn load(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    name: &str,
+) -> Result<Option<(TemplateStore, MessageTemplate)>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let store = TemplateStore::open(&ctx.data().db)?;
+    let name = name.trim().to_lowercase();
+    match store.get(guild_id.get(), &name)? {
+        Some(t) => Ok(Some((store, t))),
+        None => {
+            ctx.say(format!("There is no template called `{}`.", name)).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Reads the message JSON from an attached file or the text. Replies with the problem when it's invalid.
+async fn body_arg(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    file: Option<serenity::Attachment>,
+    json: Option<String>,
+) -> Result<Option<serde_json::Value>, crate::Error> {
+    let input = match (file, json) {
+        (Some(att), _) => String::from_utf8_lossy(&att.download().await?).into_owned(),
+        (None, Some(text)) => text,
+        (None, None) => {
+            ctx.say("Attach a JSON file or write the message JSON after the name.").await?;
+            return Ok(None);
+        }
+    };
+    match parse_body(&input) {
+        Ok(body) => Ok(Some(body)),
+        Err(problem) => {
+            ctx.say(problem).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Manages reusable messages with embeds, components and variables.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "template",
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    subcommands("create", "edit", "delete", "list", "show", "preview", "sync"),
+    subcommand_required
+)]
+pub async fn template(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Creates a template from Discord message JSON.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn create(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+    #[description = "JSON file with the message"] file: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Message JSON with content, embeds and components"]
+    json: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let name = match normalize_name(&name) {
+        Ok(n) => n,
+        Err(problem) => {
+            ctx.say(problem).await?;
+            return Ok(());
+        }
+    };
+    let store = TemplateStore::open(&ctx.data().db)?;
+    if store.get(guild_id.get(), &name)?.is_some() {
+        ctx.say(format!("A template called `{}` already exists, use `template edit` to change it.", name)).await?;
+        return Ok(());
+    }
+    let Some(body) = body_arg(ctx, file, json).await? else { return Ok(()) };
+    store.save(&MessageTemplate {
+        guild_id: guild_id.get(),
+        name: name.clone(),
+        body,
+        created_by: ctx.author().id.get(),
+        updated_at: chrono::Utc::now().timestamp(),
+        deployments: Vec::new(),
+    })?;
+    ctx.say(format!("Template `{}` created. Variables: {}", name, VARIABLES_HELP)).await?;
+    Ok(())
+}
+
+/// Replaces the message of a template.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn edit(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+    #[description = "JSON file with the message"] file: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Message JSON with content, embeds and components"]
+    json: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &name).await? else { return Ok(()) };
+    let Some(body) = body_arg(ctx, file, json).await? else { return Ok(()) };
+    existing.body = body;
+    existing.updated_at = chrono::Utc::now().timestamp();
+    store.save(&existing)?;
+    let note = match existing.deployments.len() {
+        0 => String::new(),
+        n => format!(" Use `template sync {}` to update its {} sent message(s).", existing.name, n),
+    };
+    ctx.say(format!("Template `{}` updated.{}", existing.name, note)).await?;
+    Ok(())
+}
+
+/// Deletes a template.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+) -> Result<(), crate::Error> {
+    let Some((store, existing)) = load(ctx, &name).await? else { return Ok(()) };
+    store.remove(existing.guild_id, &existing.name)?;
+    ctx.say(format!("Template `{}` deleted.", existing.name)).await?;
+    Ok(())
+}
+
+/// Lists the templates of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let templates = TemplateStore::open(&ctx.data().db)?.list(guild_id.get())?;
+    if templates.is_empty() {
+        ctx.say("This server has no templates yet.").await?;
+        return Ok(());
+    }
+    let pages = templates
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let lines: Vec<String> = chunk
+                .iter()
+                .map(|t| {
+                    let embeds = t.body.get("embeds").and_then(|e| e.as_array()).map_or(0, |e| e.len());
+                    format!("`{}` - {} embed(s), {} sent, updated <t:{}:R>", t.name, embeds, t.deployments.len(), t.updated_at)
+                })
+                .collect();
+            serenity::CreateEmbed::default()
+                .title(format!("Templates ({})", templates.len()))
+                .description(lines.join("\n"))
+                .footer(serenity::CreateEmbedFooter::new("Variables: {user} {user_name} {guild} {member_count} {date}"))
+                .color(0x3B82F6)
+        })
+        .collect();
+    paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
+
+/// Sends the JSON of a template as a file.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn show(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+) -> Result<(), crate::Error> {
+    let Some((_, existing)) = load(ctx, &name).await? else { return Ok(()) };
+    let json = serde_json::to_string_pretty(&existing.body)?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("Template `{}`, last updated <t:{}:R>.", existing.name, existing.updated_at))
+            .attachment(serenity::CreateAttachment::bytes(json.into_bytes(), format!("{}.json", existing.name))),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Shows a template with its variables filled in, only to you.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn preview(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some((_, existing)) = load(ctx, &name).await? else { return Ok(()) };
+    let vars = Variables::load(ctx.serenity_context(), guild_id, Some(ctx.author())).await?;
+    ctx.send(preview_reply(&render(&existing.body, &vars)).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Re-renders a template and edits every message sent from it, after showing what changes.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_MESSAGES")]
+async fn sync(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Template name"] name: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some((store, existing)) = load(ctx, &name).await? else { return Ok(()) };
+    if existing.deployments.is_empty() {
+        ctx.say(format!("Template `{}` hasn't been sent anywhere yet.", existing.name)).await?;
+        return Ok(());
+    }
+    ctx.defer_ephemeral().await?;
+
+    // Each copy is rendered with the values of the user who sent it
+    let mut changes: Vec<(usize, serde_json::Value)> = Vec::new();
+    for (i, deployment) in existing.deployments.iter().enumerate() {
+        let sender = ctx.http().get_user(serenity::UserId::new(deployment.sent_by)).await.ok();
+        let vars = Variables::load(ctx.serenity_context(), guild_id, sender.as_ref()).await?;
+        let body = render(&existing.body, &vars);
+        if body != deployment.rendered {
+            changes.push((i, body));
+        }
+    }
+    if changes.is_empty() {
+        ctx.say(format!("All {} message(s) of template `{}` are up to date.", existing.deployments.len(), existing.name)).await?;
+        return Ok(());
+    }
+
+    // Copies usually differ only in their variables, so identical diffs are shown once
+    let mut diffs: Vec<(String, Vec<String>)> = Vec::new();
+    for (i, body) in &changes {
+        let d = &existing.deployments[*i];
+        let link = format!("https://discord.com/channels/{}/{}/{}", guild_id, d.channel_id, d.message_id);
+        let text = diff(&d.rendered, body);
+        match diffs.iter_mut().find(|(t, _)| *t == text) {
+            Some((_, links)) => links.push(link),
+            None => diffs.push((text, vec![link])),
+        }
+    }
+    let full: String = diffs
+        .iter()
+        .map(|(text, links)| format!("{}\n{}\n", links.join("\n"), text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (first, links) = &diffs[0];
+    let mut description = format!("```diff\n{}\n```", truncate(first, MAX_DIFF));
+    if diffs.len() > 1 {
+        description.push_str(&format!("\n{} other variant(s) of the change are in the attached file.", diffs.len() - 1));
+    }
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Sync template `{}`", existing.name))
+        .description(description)
+        .field("Messages to edit", format!("{} of {}", changes.len(), existing.deployments.len()), true)
+        .field("Shown diff applies to", links.iter().take(5).cloned().collect::<Vec<_>>().join("\n"), false)
+        .color(0xF59E0B);
+    let mut prompt = poise::CreateReply::default().embed(embed);
+    if diffs.len() > 1 || first.chars().count() > MAX_DIFF {
+        prompt = prompt.attachment(serenity::CreateAttachment::bytes(full.into_bytes(), format!("{}-sync.diff", existing.name)));
+    }
+    if !confirm(ctx, prompt, "Apply").await? {
+        return Ok(());
+    }
+
+    let (mut edited, mut failed) = (0, 0);
+    let mut gone: Vec<u64> = Vec::new();
+    let mut rendered: Vec<(u64, serde_json::Value)> = Vec::new();
+    for (i, body) in changes {
+        let deployment = &existing.deployments[i];
+        let destination = match &deployment.webhook {
+            Some((id, token)) => Destination::Webhook { id: serenity::WebhookId::new(*id), token: token.clone() },
+            None => Destination::Channel(serenity::ChannelId::new(deployment.channel_id)),
+        };
+        let mass = mass_mentions(ctx, &destination).await;
+        match edit_deployed(ctx.http(), deployment, &body, mass).await {
+            Ok(()) => {
+                edited += 1;
+                rendered.push((deployment.message_id, body));
+            }
+            Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r)))
+                if r.error.code == UNKNOWN_MESSAGE || r.error.code == UNKNOWN_WEBHOOK =>
+            {
+                gone.push(deployment.message_id);
+            }
+            Err(e) => {
+                eprintln!("failed to edit message {} of template {}: {}", deployment.message_id, existing.name, e);
+                failed += 1;
+            }
+        }
+    }
+
+    // Reloaded so messages sent while the prompt was open aren't lost
+    if let Some(mut current) = store.get(existing.guild_id, &existing.name)? {
+        current.deployments.retain(|d| !gone.contains(&d.message_id));
+        for d in &mut current.deployments {
+            if let Some((_, body)) = rendered.iter().find(|(id, _)| *id == d.message_id) {
+                d.rendered = body.clone();
+            }
+        }
+        store.save(&current)?;
+    }
+    let mut text = format!("Edited {} message(s) of template `{}`.", edited, existing.name);
+    if !gone.is_empty() {
+        text.push_str(&format!(" {} deleted message(s) were forgotten.", gone.len()));
+    }
+    if failed > 0 {
+        text.push_str(&format!(" {} message(s) couldn't be edited.", failed));
+    }
+    ctx.send(poise::CreateReply::default().content(text).ephemeral(true)).await?;
+    Ok(())
+}
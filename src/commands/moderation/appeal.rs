@@ -0,0 +1,73 @@
+// Lets a banned user appeal their ban from DMs.
+// appeal [serverid]
+
+use super::appeals::{appeal_button, appeal_form, check_can_appeal, guild_name};
+use super::mass::is_banned;
+use crate::data::appeals::store::AppealStore;
+use poise::serenity_prelude as serenity;
+
+/// Appeal a ban. Use it in DMs with the bot.
+#[poise::command(slash_command, prefix_command, dm_only)]
+pub async fn appeal(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the server you were banned from"] server: Option<String>,
+) -> Result<(), crate::Error> {
+    let store = AppealStore::open(&ctx.data().db)?;
+    let user_id = ctx.author().id;
+
+    let guild_id = match server {
+        Some(s) => match s.trim().parse::<u64>().ok().filter(|id| *id > 0) {
+            Some(id) => id,
+            None => {
+                ctx.say(format!("`{}` is not a valid server ID.", s)).await?;
+                return Ok(());
+            }
+        },
+        None => {
+            // Without a server, look for the servers accepting appeals that banned this user
+            let mut banned_in = Vec::new();
+            for g in store.configured_guilds()? {
+                if is_banned(ctx.http(), serenity::GuildId::new(g), user_id).await.unwrap_or(false) {
+                    banned_in.push(g);
+                }
+            }
+            match banned_in.as_slice() {
+                [] => {
+                    ctx.say("I couldn't find a server that banned you and accepts appeals.").await?;
+                    return Ok(());
+                }
+                [only] => *only,
+                many => {
+                    let mut lines = Vec::new();
+                    for g in many {
+                        lines.push(format!("**{}** - `appeal {}`", guild_name(ctx.serenity_context(), *g).await, g));
+                    }
+                    ctx.say(format!("You are banned from several servers. Pick one:\n{}", lines.join("\n"))).await?;
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    if let Err(msg) = check_can_appeal(ctx.serenity_context(), &store, guild_id, user_id.get()).await? {
+        ctx.say(msg).await?;
+        return Ok(());
+    }
+
+    match ctx {
+        // The form is answered through the global appeal handler, like the button on the ban notice
+        poise::Context::Application(app) => {
+            app.interaction.create_response(ctx, appeal_form(guild_id)).await?;
+        }
+        poise::Context::Prefix(_) => {
+            let name = guild_name(ctx.serenity_context(), guild_id).await;
+            ctx.send(
+                poise::CreateReply::default()
+                    .content(format!("Press the button to appeal your ban from **{}**.", name))
+                    .components(vec![serenity::CreateActionRow::Buttons(vec![appeal_button(guild_id)])]),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// Sets up where ban appeals are posted.
+// appealconfig <channel|disable> [channel]
+
+use super::utils::{disable_posts, set_post_channel};
+use crate::data::appeals::store::{AppealConfig, AppealStore};
+use poise::serenity_prelude as serenity;
+
+/// Sets up where ban appeals are posted.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "appealconfig",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("channel", "disable"),
+    subcommand_required
+)]
+pub async fn appeal_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Accepts appeals and posts them in the given channel. Banned users get an appeal button from then on.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn channel(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel for appeal posts, ideally only visible to moderators"] channel: serenity::GuildChannel,
+) -> Result<(), crate::Error> {
+    let store = AppealStore::open(&ctx.data().db)?;
+    set_post_channel(ctx, &channel, "Appeals", |guild_id, channel_id| {
+        store.set_config(guild_id, Some(&AppealConfig { channel_id }))
+    })
+    .await
+}
+
+/// Stops accepting new appeals. Open appeals can still be decided.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn disable(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let store = AppealStore::open(&ctx.data().db)?;
+    disable_posts(ctx, "appeals", |guild_id| store.set_config(guild_id, None)).await
+}
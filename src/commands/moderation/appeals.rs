@@ -0,0 +1,591 @@
+// Shared parts of the ban appeal system: the ban notice, the submission form, the appeal post in the
+// mod channel with its buttons, and relaying messages between the user and the moderators.
+//
+// Buttons and modals use custom ids of the form `appeal:<action>:<guild id>[:<appeal id>]`, so they
+// keep working after a restart and are routed here from the event handler.
+
+use super::cases::{split_case, truncate};
+use super::mass::is_banned;
+use super::utils::audit_reason;
+use crate::data::appeals::store::{Appeal, AppealStatus, AppealStore};
+use crate::commands::general::features::{enabled, refusal, Feature};
+use crate::data::cases::store::{CaseAction, CaseStore};
+use crate::handlers::component_router::{component_id, ComponentId};
+use poise::serenity_prelude as serenity;
+use poise::Modal;
+
+pub(crate) const PREFIX: &str = "appeal";
+
+const QUESTION_REASON: &str = "Why were you banned?";
+const QUESTION_ARGUMENT: &str = "Why should you be unbanned?";
+const QUESTION_EXTRA: &str = "Anything else?";
+
+#[derive(Debug, poise::Modal)]
+#[name = "Ban appeal"]
+struct AppealModal {
+    #[name = "Why were you banned?"]
+    #[paragraph]
+    #[max_length = 1000]
+    reason: String,
+    #[name = "Why should you be unbanned?"]
+    #[paragraph]
+    #[max_length = 1000]
+    argument: String,
+    #[name = "Anything else?"]
+    #[paragraph]
+    #[max_length = 1000]
+    extra: Option<String>,
+}
+
+#[derive(Debug, poise::Modal)]
+#[name = "Deny appeal"]
+struct DenyModal {
+    #[name = "Reason (sent to the user)"]
+    #[paragraph]
+    #[max_length = 1000]
+    reason: Option<String>,
+}
+
+#[derive(Debug, poise::Modal)]
+#[name = "Reply to appeal"]
+struct ReplyModal {
+    #[name = "Message"]
+    #[paragraph]
+    #[max_length = 2000]
+    message: String,
+    #[name = "Send anonymously? (yes/no)"]
+    #[placeholder = "no"]
+    #[max_length = 3]
+    anonymous: Option<String>,
+}
+
+struct Route {
+    action: String,
+    guild_id: u64,
+    appeal_id: Option<u64>,
+}
+
+impl Route {
+    fn parse(custom_id: &str) -> Option<Self> {
+        let id = ComponentId::parse(custom_id).filter(|id| id.prefix == PREFIX)?;
+        let guild_id = id.arg(0)?;
+        let appeal_id = match id.args.get(1) {
+            Some(_) => Some(id.arg(1)?),
+            None => None,
+        };
+        Some(Self { action: id.action, guild_id, appeal_id })
+    }
+}
+
+fn custom_id(action: &str, guild_id: u64, appeal_id: Option<u64>) -> String {
+    component_id(PREFIX, action, std::iter::once(guild_id).chain(appeal_id))
+}
+
+pub(crate) async fn guild_name(ctx: &serenity::Context, guild_id: u64) -> String {
+    let id = serenity::GuildId::new(guild_id);
+    if let Some(name) = id.name(&ctx.cache) {
+        return name;
+    }
+    id.to_partial_guild(&ctx.http).await.map(|g| g.name).unwrap_or_else(|_| guild_id.to_string())
+}
+
+/// The button that opens the appeal form for a guild.
+pub(crate) fn appeal_button(guild_id: u64) -> serenity::CreateButton {
+    serenity::CreateButton::new(custom_id("open", guild_id, None))
+        .label("Appeal")
+        .style(serenity::ButtonStyle::Primary)
+}
+
+/// The modal response with the appeal form for a guild.
+pub(crate) fn appeal_form(guild_id: u64) -> serenity::CreateInteractionResponse {
+    AppealModal::create(None, custom_id("submit", guild_id, None))
+}
+
+/// Whether a user may submit an appeal to a guild right now. On refusal the error is a message for the user.
+pub(crate) async fn check_can_appeal(
+    ctx: &serenity::Context,
+    store: &AppealStore,
+    guild_id: u64,
+    user_id: u64,
+) -> Result<Result<(), String>, crate::Error> {
+    if store.config(guild_id)?.is_none() {
+        return Ok(Err("That server doesn't accept ban appeals.".to_string()));
+    }
+    if let Some(block) = store.active_block(guild_id, user_id)? {
+        let until = match block.until {
+            Some(u) => format!("until <t:{}:f>", u),
+            None => "until further notice".to_string(),
+        };
+        return Ok(Err(format!("You are blocked from appealing in that server {}.", until)));
+    }
+    if store.open_for(user_id, guild_id)?.is_some() {
+        return Ok(Err(
+            "You already have an open appeal in that server. You'll be messaged here once it's decided.".to_string(),
+        ));
+    }
+    if !is_banned(&ctx.http, serenity::GuildId::new(guild_id), serenity::UserId::new(user_id)).await? {
+        return Ok(Err("You are not banned from that server.".to_string()));
+    }
+    Ok(Ok(()))
+}
+
+/// DMs a user that is about to be banned, with a button to appeal when the guild accepts appeals.
+/// Best effort: users with closed DMs are skipped silently.
+pub(crate) async fn send_ban_notice(ctx: &serenity::Context, db: &sled::Db, guild_id: serenity::GuildId, user_id: serenity::UserId) {
+    let configured = AppealStore::open(db)
+        .and_then(|s| s.config(guild_id.get()))
+        .ok()
+        .flatten()
+        .is_some();
+    if !configured {
+        return;
+    }
+    let name = guild_name(ctx, guild_id.get()).await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("You were banned from {}", name))
+        .description("If you think this ban was a mistake, you can appeal it with the button below.")
+        .color(0xB91C1C);
+    let msg = serenity::CreateMessage::new()
+        .embed(embed)
+        .components(vec![serenity::CreateActionRow::Buttons(vec![appeal_button(guild_id.get())])]);
+    let _ = user_id.direct_message(ctx, msg).await;
+}
+
+fn status_color(status: AppealStatus) -> u32 {
+    match status {
+        AppealStatus::Open => 0x3B82F6,
+        AppealStatus::Accepted => 0x22C55E,
+        AppealStatus::Denied => 0xEF4444,
+    }
+}
+
+pub(crate) fn appeal_embed(appeal: &Appeal) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("Appeal #{}", appeal.id))
+        .color(status_color(appeal.status))
+        .field("User", format!("<@{}> ({})\n`{}`", appeal.user_id, appeal.user_name, appeal.user_id), true)
+        .field("Case", appeal.case_id.map(|c| format!("#{}", c)).unwrap_or_else(|| "none found".to_string()), true)
+        .field("Submitted", format!("<t:{}:f>", appeal.created_at), true);
+    for (question, answer) in &appeal.answers {
+        embed = embed.field(question, truncate(answer, 1024), false);
+    }
+    let verdict = match appeal.status {
+        AppealStatus::Open => None,
+        AppealStatus::Accepted => Some("Accepted"),
+        AppealStatus::Denied => Some("Denied"),
+    };
+    if let Some(verdict) = verdict {
+        let mut text = format!(
+            "{} by {} <t:{}:R>",
+            verdict,
+            appeal.resolved_by.map(|m| format!("<@{}>", m)).unwrap_or_else(|| "unknown".to_string()),
+            appeal.resolved_at.unwrap_or(appeal.created_at)
+        );
+        if let Some(r) = &appeal.resolution_reason {
+            text.push_str(&format!("\n{}", r));
+        }
+        embed = embed.field("Decision", truncate(&text, 1024), false);
+    }
+    embed
+}
+
+fn appeal_buttons(appeal: &Appeal) -> Vec<serenity::CreateActionRow> {
+    let closed = appeal.status != AppealStatus::Open;
+    let id = Some(appeal.id);
+    vec![serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(custom_id("accept", appeal.guild_id, id))
+            .label("Accept")
+            .style(serenity::ButtonStyle::Success)
+            .disabled(closed),
+        serenity::CreateButton::new(custom_id("deny", appeal.guild_id, id))
+            .label("Deny")
+            .style(serenity::ButtonStyle::Danger)
+            .disabled(closed),
+        serenity::CreateButton::new(custom_id("reply", appeal.guild_id, id))
+            .label("Reply")
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(closed),
+    ])]
+}
+
+/// Posts the submission message for an appeal in the guild's appeal channel and opens a thread for it
+/// if it doesn't have one yet. Updates the channel, message and thread ids on the appeal; the caller saves it.
+pub(crate) async fn post_appeal(ctx: &serenity::Context, appeal: &mut Appeal, channel_id: u64) -> Result<(), crate::Error> {
+    let channel = serenity::ChannelId::new(channel_id);
+    let msg = channel
+        .send_message(
+            ctx,
+            serenity::CreateMessage::new().embed(appeal_embed(appeal)).components(appeal_buttons(appeal)),
+        )
+        .await?;
+    appeal.channel_id = channel_id;
+    appeal.message_id = msg.id.get();
+
+    if appeal.thread_id.is_none() {
+        let name = truncate(&format!("Appeal #{} - {}", appeal.id, appeal.user_name), 100);
+        // Appeal channels are not necessarily text channels (e.g. forums), so the thread is optional
+        if let Ok(thread) = channel
+            .create_thread_from_message(ctx, msg.id, serenity::CreateThread::new(name))
+            .await
+        {
+            appeal.thread_id = Some(thread.id.get());
+            let _ = thread
+                .say(
+                    ctx,
+                    "Messages the user sends me are relayed into this thread. Answer them with `send_to_appeal` here or with the Reply button.",
+                )
+                .await;
+        }
+    }
+    Ok(())
+}
+
+/// Updates the submission message so it reflects the appeal's current state.
+async fn refresh_post(ctx: &serenity::Context, appeal: &Appeal) {
+    let _ = serenity::ChannelId::new(appeal.channel_id)
+        .edit_message(
+            ctx,
+            serenity::MessageId::new(appeal.message_id),
+            serenity::EditMessage::new().embed(appeal_embed(appeal)).components(appeal_buttons(appeal)),
+        )
+        .await;
+}
+
+/// Posts into the appeal's thread, or the appeal channel when it has none.
+async fn log(ctx: &serenity::Context, appeal: &Appeal, embed: serenity::CreateEmbed) -> bool {
+    let channel = serenity::ChannelId::new(appeal.thread_id.unwrap_or(appeal.channel_id));
+    channel.send_message(ctx, serenity::CreateMessage::new().embed(embed)).await.is_ok()
+}
+
+/// Sends a moderator message to the user of an appeal and logs it in the thread.
+/// Returns false if the user could not be messaged.
+pub(crate) async fn message_user(
+    ctx: &serenity::Context,
+    appeal: &Appeal,
+    sender: &serenity::User,
+    text: &str,
+    anonymous: bool,
+) -> bool {
+    let name = guild_name(ctx, appeal.guild_id).await;
+    let author = if anonymous {
+        serenity::CreateEmbedAuthor::new(format!("Moderators of {}", name))
+    } else {
+        serenity::CreateEmbedAuthor::new(format!("{} ({})", sender.name, name)).icon_url(sender.face())
+    };
+    let embed = serenity::CreateEmbed::default()
+        .author(author)
+        .title(format!("Message about your appeal #{}", appeal.id))
+        .description(text)
+        .footer(serenity::CreateEmbedFooter::new("Reply by messaging me here."))
+        .color(0x3B82F6);
+    let sent = serenity::UserId::new(appeal.user_id)
+        .direct_message(ctx, serenity::CreateMessage::new().embed(embed))
+        .await
+        .is_ok();
+
+    let log_embed = serenity::CreateEmbed::default()
+        .author(serenity::CreateEmbedAuthor::new(format!("{} to the user", sender.name)).icon_url(sender.face()))
+        .description(text)
+        .footer(serenity::CreateEmbedFooter::new(match (sent, anonymous) {
+            (false, _) => "Not delivered: the user can't be messaged",
+            (true, true) => "Sent anonymously",
+            (true, false) => "Sent with your name",
+        }))
+        .color(if sent { 0x6B7280 } else { 0xEF4444 });
+    log(ctx, appeal, log_embed).await;
+    sent
+}
+
+/// Unbans the user of an appeal, closes their open ban cases and marks the appeal accepted.
+/// Returns notes for the moderator.
+async fn accept(ctx: &serenity::Context, db: &sled::Db, appeal: &mut Appeal, moderator: &serenity::User) -> Result<Vec<String>, crate::Error> {
+    let guild_id = serenity::GuildId::new(appeal.guild_id);
+    let user_id = serenity::UserId::new(appeal.user_id);
+    let reason = format!("Appeal #{} accepted", appeal.id);
+    let mut notes = Vec::new();
+
+    if is_banned(&ctx.http, guild_id, user_id).await? {
+        ctx.http.remove_ban(guild_id, user_id, Some(&audit_reason(moderator, Some(&reason)))).await?;
+    } else {
+        notes.push("The user was already unbanned.".to_string());
+    }
+
+    let cases = CaseStore::open(db)?;
+    let open: Vec<_> = cases
+        .list(appeal.guild_id)?
+        .into_iter()
+        .filter(|c| c.action == CaseAction::Ban && !c.closed && c.targets.contains(&appeal.user_id))
+        .collect();
+    for mut case in open {
+        // Only this user is unbanned, so they get their own case out of a mass ban
+        let mut case = if case.is_mass() { split_case(&cases, &mut case, appeal.user_id, moderator.id.get())? } else { case };
+        case.close(Some(moderator.id.get()), Some(reason.clone()));
+        cases.save(&case)?;
+        notes.push(format!("Closed case #{}.", case.id));
+    }
+
+    appeal.status = AppealStatus::Accepted;
+    appeal.resolved_by = Some(moderator.id.get());
+    appeal.resolved_at = Some(chrono::Utc::now().timestamp());
+    AppealStore::open(db)?.save(appeal)?;
+
+    let name = guild_name(ctx, appeal.guild_id).await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Your appeal in {} was accepted", name))
+        .description("You have been unbanned and can rejoin the server.")
+        .color(status_color(AppealStatus::Accepted));
+    if user_id.direct_message(ctx, serenity::CreateMessage::new().embed(embed)).await.is_err() {
+        notes.push("The user couldn't be messaged about the decision.".to_string());
+    }
+    Ok(notes)
+}
+
+async fn deny(ctx: &serenity::Context, db: &sled::Db, appeal: &mut Appeal, moderator: &serenity::User, reason: Option<String>) -> Result<Vec<String>, crate::Error> {
+    appeal.status = AppealStatus::Denied;
+    appeal.resolved_by = Some(moderator.id.get());
+    appeal.resolved_at = Some(chrono::Utc::now().timestamp());
+    appeal.resolution_reason = reason.clone();
+    AppealStore::open(db)?.save(appeal)?;
+
+    let name = guild_name(ctx, appeal.guild_id).await;
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Your appeal in {} was denied", name))
+        .description(reason.unwrap_or_else(|| "No reason was given.".to_string()))
+        .color(status_color(AppealStatus::Denied));
+    let mut notes = Vec::new();
+    if serenity::UserId::new(appeal.user_id)
+        .direct_message(ctx, serenity::CreateMessage::new().embed(embed))
+        .await
+        .is_err()
+    {
+        notes.push("The user couldn't be messaged about the decision.".to_string());
+    }
+    Ok(notes)
+}
+
+fn is_moderator(member: Option<&serenity::Member>) -> bool {
+    member.and_then(|m| m.permissions).is_some_and(|p| p.ban_members())
+}
+
+fn ephemeral(text: impl Into<String>) -> serenity::CreateInteractionResponse {
+    serenity::CreateInteractionResponse::Message(
+        serenity::CreateInteractionResponseMessage::new().content(text).ephemeral(true),
+    )
+}
+
+/// Loads the open appeal a moderator button or modal refers to. On failure the error is a message for the moderator.
+fn load_for_moderator(
+    store: &AppealStore,
+    route: &Route,
+    guild_id: Option<serenity::GuildId>,
+    member: Option<&serenity::Member>,
+) -> Result<Result<Appeal, &'static str>, crate::Error> {
+    if guild_id.map(|g| g.get()) != Some(route.guild_id) || !is_moderator(member) {
+        return Ok(Err("You need the Ban Members permission to handle appeals."));
+    }
+    let Some(appeal) = route.appeal_id.map(|id| store.get(route.guild_id, id)).transpose()?.flatten() else {
+        return Ok(Err("This appeal no longer exists."));
+    };
+    if appeal.status != AppealStatus::Open {
+        return Ok(Err("This appeal was already decided."));
+    }
+    Ok(Ok(appeal))
+}
+
+/// Handles appeal buttons and modals. Other interactions are ignored.
+pub async fn handle_interaction(ctx: &serenity::Context, interaction: &serenity::Interaction, data: &crate::Data) -> Result<(), crate::Error> {
+    match interaction {
+        serenity::Interaction::Component(mci) => {
+            let Some(route) = Route::parse(&mci.data.custom_id) else { return Ok(()) };
+            if let Some(msg) = refusal(&data.db, route.guild_id, Feature::Moderation) {
+                mci.create_response(ctx, ephemeral(msg)).await?;
+                return Ok(());
+            }
+            handle_component(ctx, mci, route, data).await
+        }
+        serenity::Interaction::Modal(modal) => {
+            let Some(route) = Route::parse(&modal.data.custom_id) else { return Ok(()) };
+            if let Some(msg) = refusal(&data.db, route.guild_id, Feature::Moderation) {
+                modal.create_response(ctx, ephemeral(msg)).await?;
+                return Ok(());
+            }
+            handle_modal(ctx, modal, route, data).await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn handle_component(ctx: &serenity::Context, mci: &serenity::ComponentInteraction, route: Route, data: &crate::Data) -> Result<(), crate::Error> {
+    let store = AppealStore::open(&data.db)?;
+    if route.action == "open" {
+        let response = match check_can_appeal(ctx, &store, route.guild_id, mci.user.id.get()).await? {
+            Ok(()) => appeal_form(route.guild_id),
+            Err(msg) => ephemeral(msg),
+        };
+        mci.create_response(ctx, response).await?;
+        return Ok(());
+    }
+
+    let mut appeal = match load_for_moderator(&store, &route, mci.guild_id, mci.member.as_ref())? {
+        Ok(a) => a,
+        Err(msg) => {
+            mci.create_response(ctx, ephemeral(msg)).await?;
+            return Ok(());
+        }
+    };
+    match route.action.as_str() {
+        "accept" => {
+            mci.defer_ephemeral(ctx).await?;
+            let text = match accept(ctx, &data.db, &mut appeal, &mci.user).await {
+                Ok(notes) => {
+                    refresh_post(ctx, &appeal).await;
+                    let embed = serenity::CreateEmbed::default()
+                        .description(format!("Appeal accepted by <@{}>. {}", mci.user.id, notes.join(" ")))
+                        .color(status_color(AppealStatus::Accepted));
+                    log(ctx, &appeal, embed).await;
+                    format!("Accepted appeal #{}. {}", appeal.id, notes.join(" "))
+                }
+                Err(e) => format!("Couldn't accept appeal #{}: {}", appeal.id, e),
+            };
+            mci.create_followup(ctx, serenity::CreateInteractionResponseFollowup::new().content(text).ephemeral(true))
+                .await?;
+        }
+        "deny" => {
+            mci.create_response(ctx, DenyModal::create(None, mci.data.custom_id.clone())).await?;
+        }
+        "reply" => {
+            mci.create_response(ctx, ReplyModal::create(None, mci.data.custom_id.clone())).await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn handle_modal(ctx: &serenity::Context, modal: &serenity::ModalInteraction, route: Route, data: &crate::Data) -> Result<(), crate::Error> {
+    let store = AppealStore::open(&data.db)?;
+    match route.action.as_str() {
+        "submit" => {
+            let Ok(form) = AppealModal::parse(modal.data.clone()) else { return Ok(()) };
+            if let Err(msg) = check_can_appeal(ctx, &store, route.guild_id, modal.user.id.get()).await? {
+                modal.create_response(ctx, ephemeral(msg)).await?;
+                return Ok(());
+            }
+            let Some(config) = store.config(route.guild_id)? else { return Ok(()) };
+            modal.defer_ephemeral(ctx).await?;
+
+            // The latest open ban case is the one being appealed
+            let case_id = CaseStore::open(&data.db)?
+                .list(route.guild_id)?
+                .into_iter()
+                .find(|c| c.action == CaseAction::Ban && !c.closed && c.targets.contains(&modal.user.id.get()))
+                .map(|c| c.id);
+            let mut answers = vec![
+                (QUESTION_REASON.to_string(), form.reason),
+                (QUESTION_ARGUMENT.to_string(), form.argument),
+            ];
+            if let Some(extra) = form.extra.filter(|e| !e.trim().is_empty()) {
+                answers.push((QUESTION_EXTRA.to_string(), extra));
+            }
+            let mut appeal = Appeal {
+                id: store.next_id(route.guild_id)?,
+                guild_id: route.guild_id,
+                user_id: modal.user.id.get(),
+                user_name: modal.user.name.clone(),
+                case_id,
+                answers,
+                status: AppealStatus::Open,
+                created_at: chrono::Utc::now().timestamp(),
+                channel_id: config.channel_id,
+                message_id: 0,
+                thread_id: None,
+                resolved_by: None,
+                resolved_at: None,
+                resolution_reason: None,
+            };
+            let text = match post_appeal(ctx, &mut appeal, config.channel_id).await {
+                Ok(()) => {
+                    store.save(&appeal)?;
+                    format!(
+                        "Your appeal to **{}** was submitted. You'll be messaged here when it's decided, and anything you send me in the meantime is passed on to the moderators.",
+                        guild_name(ctx, route.guild_id).await
+                    )
+                }
+                Err(e) => {
+                    eprintln!("failed to post appeal in guild {}: {}", route.guild_id, e);
+                    "Your appeal couldn't be delivered to the moderators. Please try again later.".to_string()
+                }
+            };
+            modal
+                .create_followup(ctx, serenity::CreateInteractionResponseFollowup::new().content(text).ephemeral(true))
+                .await?;
+        }
+        "deny" | "reply" => {
+            let mut appeal = match load_for_moderator(&store, &route, modal.guild_id, modal.member.as_ref())? {
+                Ok(a) => a,
+                Err(msg) => {
+                    modal.create_response(ctx, ephemeral(msg)).await?;
+                    return Ok(());
+                }
+            };
+            modal.defer_ephemeral(ctx).await?;
+            let text = if route.action == "deny" {
+                let Ok(form) = DenyModal::parse(modal.data.clone()) else { return Ok(()) };
+                let reason = form.reason.filter(|r| !r.trim().is_empty());
+                let notes = deny(ctx, &data.db, &mut appeal, &modal.user, reason).await?;
+                refresh_post(ctx, &appeal).await;
+                let embed = serenity::CreateEmbed::default()
+                    .description(format!("Appeal denied by <@{}>. {}", modal.user.id, notes.join(" ")))
+                    .color(status_color(AppealStatus::Denied));
+                log(ctx, &appeal, embed).await;
+                format!("Denied appeal #{}. {}", appeal.id, notes.join(" "))
+            } else {
+                let Ok(form) = ReplyModal::parse(modal.data.clone()) else { return Ok(()) };
+                let anonymous = form
+                    .anonymous
+                    .is_some_and(|a| matches!(a.trim().to_lowercase().as_str(), "y" | "yes" | "true"));
+                if message_user(ctx, &appeal, &modal.user, &form.message, anonymous).await {
+                    "Message sent.".to_string()
+                } else {
+                    "The user can't be messaged (DMs closed or no shared server).".to_string()
+                }
+            };
+            modal
+                .create_followup(ctx, serenity::CreateInteractionResponseFollowup::new().content(text).ephemeral(true))
+                .await?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Relays a DM from a user with an open appeal into that appeal's thread.
+pub async fn relay_dm(ctx: &serenity::Context, msg: &serenity::Message, data: &crate::Data) -> Result<(), crate::Error> {
+    if msg.author.bot || msg.guild_id.is_some() || (msg.content.is_empty() && msg.attachments.is_empty()) {
+        return Ok(());
+    }
+    let store = AppealStore::open(&data.db)?;
+    // With appeals in several servers, messages go to the most recent one of a server with moderation on
+    let Some(appeal) = store
+        .open_for_user(msg.author.id.get())?
+        .into_iter()
+        .filter(|a| enabled(&data.db, a.guild_id, Feature::Moderation))
+        .max_by_key(|a| a.created_at)
+    else {
+        return Ok(());
+    };
+
+    let mut text = msg.content.clone();
+    for a in &msg.attachments {
+        text.push_str(&format!("\n{}", a.url));
+    }
+    let mut embed = serenity::CreateEmbed::default()
+        .author(serenity::CreateEmbedAuthor::new(format!("{} (user)", msg.author.name)).icon_url(msg.author.face()))
+        .description(truncate(text.trim(), 4096))
+        .color(0x3B82F6);
+    if let Some(image) = msg.attachments.iter().find(|a| a.content_type.as_deref().is_some_and(|t| t.starts_with("image/"))) {
+        embed = embed.image(image.url.clone());
+    }
+    let delivered = log(ctx, &appeal, embed).await;
+    let _ = msg.react(ctx, if delivered { '✅' } else { '❌' }).await;
+    Ok(())
+}
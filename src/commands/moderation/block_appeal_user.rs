@@ -1,2 +1,52 @@
 // BLocks a user from submitting appeals.
-// block_appeal_user <user> [reason] [duration]
\ No newline at end of file
+// block_appeal_user <user> [reason] [duration]
+
+use super::utils::{format_duration, parse_duration};
+use crate::data::appeals::store::{AppealBlock, AppealStore};
+use poise::serenity_prelude as serenity;
+
+/// Blocks a user from submitting appeals, permanently or for a while.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "BAN_MEMBERS")]
+pub async fn block_appeal_user(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User to block"] user: serenity::User,
+    #[description = "How long, e.g. 30d (permanent if omitted)"] duration: Option<String>,
+    #[rest]
+    #[description = "Reason for the block"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+
+    // A first word that isn't a duration belongs to the reason
+    let (duration, reason) = match duration {
+        Some(d) => match parse_duration(&d) {
+            Some(parsed) => (Some(parsed.as_secs()), reason),
+            None => (None, Some(match reason {
+                Some(r) => format!("{} {}", d, r),
+                None => d,
+            })),
+        },
+        None => (None, reason),
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let store = AppealStore::open(&ctx.data().db)?;
+    store.block(
+        guild_id.get(),
+        user.id.get(),
+        &AppealBlock {
+            until: duration.map(|d| now + d as i64),
+            reason: reason.clone(),
+            blocked_by: ctx.author().id.get(),
+            blocked_at: now,
+        },
+    )?;
+
+    let length = duration.map(|d| format!("for {}", format_duration(d))).unwrap_or_else(|| "until unblocked".to_string());
+    let mut msg = format!("<@{}> can't submit appeals {}.", user.id, length);
+    if store.open_for(user.id.get(), guild_id.get())?.is_some() {
+        msg.push_str(" Their open appeal stays open until it's decided.");
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,2 +1,39 @@
-// Closes a case.
-// caseclose <case id> [reason]
\ No newline at end of file
+// caseclose <case id> [reason]
+
+use super::cases::{find_case, lift_case};
+use super::utils::audit_reason;
+use crate::data::cases::store::CaseStore;
+
+/// Closes a case, lifting its ban or timeout if it is still active.
+#[poise::command(slash_command, prefix_command, rename = "caseclose", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn case_close(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Case ID"] case_id: u64,
+    #[rest]
+    #[description = "Reason for closing"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+    let Some(mut case) = find_case(&store, guild_id.get(), Some(case_id))? else {
+        ctx.say(format!("Case #{} does not exist.", case_id)).await?;
+        return Ok(());
+    };
+    if case.closed {
+        ctx.say(format!("Case #{} is already closed.", case.id)).await?;
+        return Ok(());
+    }
+
+    let audit = audit_reason(ctx.author(), Some(&format!("Case #{} closed: {}", case.id, reason.as_deref().unwrap_or("no reason"))));
+    let failures = lift_case(ctx.serenity_context(), &case, &audit).await;
+
+    case.close(Some(ctx.author().id.get()), reason);
+    store.save(&case)?;
+
+    let mut msg = format!("Closed case #{}.", case.id);
+    if !failures.is_empty() {
+        msg.push_str(&format!("\nCould not revert the {} for: {}", case.action.as_str(), failures.join(", ")));
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,2 +1,28 @@
-// Deletes a case.
-// casedelete <case id>
\ No newline at end of file
+// casedelete <case id>
+
+use crate::data::cases::store::{CaseEventKind, CaseStore};
+
+/// Deletes a case. The record is kept with its history but hidden from every case command.
+#[poise::command(slash_command, prefix_command, rename = "casedelete", guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn case_delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Case ID"] case_id: u64,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+    let Some(mut case) = store.get(guild_id.get(), case_id)?.filter(|c| !c.deleted) else {
+        ctx.say(format!("Case #{} does not exist.", case_id)).await?;
+        return Ok(());
+    };
+
+    case.deleted = true;
+    case.record(Some(ctx.author().id.get()), CaseEventKind::Deleted, None);
+    store.save(&case)?;
+
+    let mut msg = format!("Deleted case #{}.", case.id);
+    if !case.closed && case.expires_at.is_some() {
+        msg.push_str(&format!(" The {} will no longer be lifted automatically.", case.action.as_str()));
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
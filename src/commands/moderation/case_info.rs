@@ -1,2 +1,59 @@
-// Gets information about a case.
-// caseinfo [case id]
\ No newline at end of file
+// caseinfo [case id] [server id]
+
+use super::cases::{case_embed, find_case};
+use super::link_case_view::{guild_label, linked_view};
+use super::usernotes::notes_summary;
+use crate::data::cases::store::CaseStore;
+use poise::serenity_prelude as serenity;
+
+/// Gets information about a case.
+#[poise::command(slash_command, prefix_command, rename = "caseinfo", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn case_info(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Case ID (defaults to the newest case)"] case_id: Option<u64>,
+    #[description = "ID of a linked server to look the case up in"] server: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+
+    let case = match server.as_deref() {
+        Some(s) => {
+            let (owner, shared) = match linked_view(ctx, guild_id.get(), s)? {
+                Ok(v) => v,
+                Err(msg) => {
+                    ctx.say(msg).await?;
+                    return Ok(());
+                }
+            };
+            match case_id {
+                Some(id) => find_case(&store, owner, Some(id))?,
+                None => store.list(owner)?.into_iter().find(|c| shared.contains(&c.action)),
+            }
+            .filter(|c| shared.contains(&c.action))
+        }
+        None => find_case(&store, guild_id.get(), case_id)?,
+    };
+    let Some(case) = case else {
+        ctx.say(match case_id {
+            Some(id) => format!("Case #{} does not exist.", id),
+            None => "There are no cases yet.".to_string(),
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let mut embed = case_embed(&case);
+    // Notes are private to the guild that wrote them, so linked servers' cases don't get any
+    if case.guild_id == guild_id.get()
+        && let [target] = case.targets[..]
+        && let Some(summary) = notes_summary(&ctx.data().db, guild_id.get(), target)
+    {
+        embed = embed.field("User notes", summary, false);
+    }
+    if case.guild_id != guild_id.get() {
+        let label = guild_label(ctx, case.guild_id).await;
+        embed = embed.footer(serenity::CreateEmbedFooter::new(format!("Linked server: {}", label)));
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1,2 +1,114 @@
-// Gets a list of the newest cases (including the specified users).
-// caselist [user] [user]
\ No newline at end of file
+// caselist [user] [moderator] [action] [status] [server id]
+
+use super::cases::case_line;
+use super::link_case_view::{guild_label, linked_view};
+use crate::commands::pagination::paginate_embeds;
+use crate::data::cases::store::{CaseAction, CaseStore};
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, poise::ChoiceParameter)]
+pub enum ActionFilter {
+    Warn,
+    Mute,
+    Kick,
+    Ban,
+}
+
+impl From<ActionFilter> for CaseAction {
+    fn from(f: ActionFilter) -> Self {
+        match f {
+            ActionFilter::Warn => CaseAction::Warn,
+            ActionFilter::Mute => CaseAction::Mute,
+            ActionFilter::Kick => CaseAction::Kick,
+            ActionFilter::Ban => CaseAction::Ban,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum StatusFilter {
+    Open,
+    Closed,
+}
+
+/// Gets a list of the newest cases, optionally filtered by user, moderator, action and status.
+#[poise::command(slash_command, prefix_command, rename = "caselist", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn case_list(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Only cases against this user"] user: Option<serenity::User>,
+    #[description = "Only cases created by this moderator"] moderator: Option<serenity::User>,
+    #[description = "Only cases of this action"] action: Option<ActionFilter>,
+    #[description = "Only open or closed cases"] status: Option<StatusFilter>,
+    #[description = "ID of a linked server to list the user's cases from"] server: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+    let action: Option<CaseAction> = action.map(Into::into);
+
+    // Linked servers can only be searched for a specific user
+    let (owner, shared) = match server.as_deref() {
+        Some(s) => {
+            if user.is_none() {
+                ctx.say("Pick a user to look up in a linked server.").await?;
+                return Ok(());
+            }
+            match linked_view(ctx, guild_id.get(), s)? {
+                Ok((owner, shared)) => (owner, Some(shared)),
+                Err(msg) => {
+                    ctx.say(msg).await?;
+                    return Ok(());
+                }
+            }
+        }
+        None => (guild_id.get(), None),
+    };
+
+    let cases: Vec<_> = store
+        .list(owner)?
+        .into_iter()
+        .filter(|c| shared.as_ref().is_none_or(|s| s.contains(&c.action)))
+        .filter(|c| user.as_ref().is_none_or(|u| c.targets.contains(&u.id.get())))
+        .filter(|c| moderator.as_ref().is_none_or(|m| c.moderator_id == m.id.get()))
+        .filter(|c| action.is_none_or(|a| c.action == a))
+        .filter(|c| match status {
+            Some(StatusFilter::Open) => !c.closed,
+            Some(StatusFilter::Closed) => c.closed,
+            None => true,
+        })
+        .collect();
+
+    let mut filters: Vec<String> = Vec::new();
+    if let Some(u) = &user { filters.push(format!("user <@{}>", u.id)); }
+    if let Some(m) = &moderator { filters.push(format!("moderator <@{}>", m.id)); }
+    if let Some(a) = action { filters.push(format!("action `{}`", a.as_str())); }
+    if let Some(s) = status { filters.push(format!("status `{}`", if s == StatusFilter::Open { "open" } else { "closed" })); }
+    let mut title = if filters.is_empty() { "Cases".to_string() } else { format!("Cases ({})", filters.join(", ")) };
+    if owner != guild_id.get() {
+        title = format!("{} - {}", guild_label(ctx, owner).await, title);
+    }
+
+    if cases.is_empty() {
+        ctx.send(poise::CreateReply::default().embed(
+            serenity::CreateEmbed::default().title(title).description("No cases found.").color(0x808080),
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let total = cases.len();
+    let pages: Vec<serenity::CreateEmbed> = cases
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            let desc: Vec<String> = chunk.iter().map(case_line).collect();
+            serenity::CreateEmbed::default()
+                .title(&title)
+                .description(desc.join("\n\n"))
+                .color(0x3B82F6)
+                .footer(serenity::CreateEmbedFooter::new(format!("{} case(s)", total)))
+        })
+        .collect();
+
+    paginate_embeds(ctx, pages).await
+}
@@ -1,2 +1,42 @@
-// Splits a masscase into two cases, one with the specified user and the other with the rest.
-// casesplit <id> <@user|userid>
\ No newline at end of file
+// casesplit <id> <@user|userid>
+
+use super::cases::split_case;
+use crate::data::cases::store::CaseStore;
+use poise::serenity_prelude as serenity;
+
+/// Splits a mass case into two cases, one with the specified user and the other with the rest.
+#[poise::command(slash_command, prefix_command, rename = "casesplit", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn case_split(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the mass case"] case_id: u64,
+    #[description = "User to move into their own case"] user: serenity::User,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+    let Some(mut case) = store.get(guild_id.get(), case_id)?.filter(|c| !c.deleted) else {
+        ctx.say(format!("Case #{} does not exist.", case_id)).await?;
+        return Ok(());
+    };
+    if !case.is_mass() {
+        ctx.say(format!("Case #{} only has one user and can't be split.", case.id)).await?;
+        return Ok(());
+    }
+    let uid = user.id.get();
+    if !case.targets.contains(&uid) {
+        ctx.say(format!("<@{}> is not part of case #{}.", uid, case.id)).await?;
+        return Ok(());
+    }
+
+    let split = split_case(&store, &mut case, uid, ctx.author().id.get())?;
+
+    ctx.say(format!(
+        "Split <@{}> from case #{} into case #{}. Case #{} now has {} user(s).",
+        uid,
+        case.id,
+        split.id,
+        case.id,
+        case.targets.len()
+    ))
+    .await?;
+    Ok(())
+}
@@ -1,2 +1,126 @@
-// Updates a case.
-// caseupdate [case id] [reason|time [reason]]
\ No newline at end of file
+// caseupdate [case id] [reason|time [reason]]
+
+use super::cases::find_case;
+use super::mass::MAX_MUTE_SECS;
+use super::utils::{audit_reason, format_duration, parse_duration};
+use crate::data::cases::store::{CaseAction, CaseEventKind, CaseStore};
+use poise::serenity_prelude as serenity;
+
+/// Updates the reason and/or duration of a case.
+#[poise::command(slash_command, prefix_command, rename = "caseupdate", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn case_update(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Case ID (defaults to the newest case)"] case_id: Option<u64>,
+    #[description = "New duration counted from the case creation (e.g. 7d), or \"perm\""] duration: Option<String>,
+    #[rest]
+    #[description = "New reason"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+
+    // Prefix invocations can't tell a duration from the first word of a reason, so anything
+    // that isn't a duration is folded back into the reason.
+    let (duration, reason) = match duration.as_deref().map(str::trim) {
+        Some(d) if d.eq_ignore_ascii_case("perm") || d.eq_ignore_ascii_case("permanent") => (Some(None), reason),
+        Some(d) => match parse_duration(d) {
+            Some(parsed) => (Some(Some(parsed.as_secs())), reason),
+            None => (None, Some(match reason {
+                Some(r) => format!("{} {}", d, r),
+                None => d.to_string(),
+            })),
+        },
+        None => (None, reason),
+    };
+    let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+
+    if duration.is_none() && reason.is_none() {
+        ctx.say("Nothing to update. Usage: caseupdate [case id] [reason|time [reason]]").await?;
+        return Ok(());
+    }
+
+    let store = CaseStore::open(&ctx.data().db)?;
+    let Some(mut case) = find_case(&store, guild_id.get(), case_id)? else {
+        ctx.say("Case not found.").await?;
+        return Ok(());
+    };
+    let moderator = Some(ctx.author().id.get());
+    let mut changes: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    if let Some(new_reason) = reason {
+        let old = case.reason.replace(new_reason.clone());
+        case.record(
+            moderator,
+            CaseEventKind::ReasonUpdated,
+            Some(format!("{} -> {}", old.as_deref().unwrap_or("none"), new_reason)),
+        );
+        changes.push("reason".to_string());
+    }
+
+    if let Some(new_duration) = duration {
+        if case.closed {
+            ctx.say(format!("Case #{} is closed, its duration can't be changed.", case.id)).await?;
+            return Ok(());
+        }
+        match (case.action, new_duration) {
+            (CaseAction::Kick, _) => {
+                ctx.say("Kicks don't have a duration.").await?;
+                return Ok(());
+            }
+            (CaseAction::Mute, None) => {
+                ctx.say("Mutes can't be permanent (Discord timeouts last at most 28 days).").await?;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let old = case.expires_at;
+        case.expires_at = new_duration.map(|secs| case.created_at + secs as i64);
+
+        // Timeouts live on Discord's side, so a mute has to be re-applied with the new end time
+        if case.action == CaseAction::Mute && let Some(end) = case.expires_at {
+            let latest = chrono::Utc::now().timestamp() + MAX_MUTE_SECS as i64;
+            let end = end.min(latest);
+            case.expires_at = Some(end);
+            let audit = audit_reason(ctx.author(), Some(&format!("Case #{} duration updated", case.id)));
+            let builder = if end <= chrono::Utc::now().timestamp() {
+                serenity::EditMember::new().enable_communication()
+            } else {
+                serenity::EditMember::new()
+                    .disable_communication_until_datetime(serenity::Timestamp::from_unix_timestamp(end)?)
+            };
+            for target in &case.targets {
+                if let Err(e) = guild_id
+                    .edit_member(ctx, serenity::UserId::new(*target), builder.clone().audit_log_reason(&audit))
+                    .await
+                {
+                    warnings.push(format!("<@{}>: {}", target, e));
+                }
+            }
+        }
+
+        let describe = |v: Option<i64>| match v {
+            Some(ts) => format_duration((ts - case.created_at).max(0) as u64),
+            None => "permanent".to_string(),
+        };
+        case.record(
+            moderator,
+            CaseEventKind::DurationUpdated,
+            Some(format!("{} -> {}", describe(old), describe(case.expires_at))),
+        );
+        changes.push("duration".to_string());
+    }
+
+    // Saving moves the case to its new slot in the expiry schedule
+    store.save(&case)?;
+
+    let mut msg = format!("Updated {} of case #{}.", changes.join(" and "), case.id);
+    if let Some(exp) = case.expires_at.filter(|_| changes.iter().any(|c| c == "duration")) {
+        msg.push_str(&format!(" It now ends <t:{}:R>.", exp));
+    }
+    if !warnings.is_empty() {
+        msg.push_str(&format!("\nCould not update the timeout for: {}", warnings.join(", ")));
+    }
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+// Shared helpers for the case commands (caseinfo, caselist, caseupdate, ...).
+
+use super::utils::format_duration;
+use crate::data::cases::store::{Case, CaseAction, CaseEventKind, CaseStore, NewCase};
+use poise::serenity_prelude as serenity;
+
+pub(crate) fn action_color(action: CaseAction) -> u32 {
+    match action {
+        CaseAction::Warn => 0xEAB308,
+        CaseAction::Mute => 0xF97316,
+        CaseAction::Kick => 0xEF4444,
+        CaseAction::Ban => 0xB91C1C,
+    }
+}
+
+pub(crate) fn status_text(case: &Case) -> String {
+    if case.closed {
+        match &case.closed_reason {
+            Some(r) => format!("Closed ({})", r),
+            None => "Closed".to_string(),
+        }
+    } else {
+        "Open".to_string()
+    }
+}
+
+pub(crate) fn targets_text(case: &Case, limit: usize) -> String {
+    // Prunes whose members couldn't be listed have no targets
+    if case.targets.is_empty() {
+        return "Not recorded".to_string();
+    }
+    let mut out: Vec<String> = case.targets.iter().take(limit).map(|t| format!("<@{}>", t)).collect();
+    if case.targets.len() > limit {
+        out.push(format!("and {} more", case.targets.len() - limit));
+    }
+    out.join(", ")
+}
+
+/// One line per case, used by the paginated lists.
+pub(crate) fn case_line(case: &Case) -> String {
+    format!(
+        "**#{}** `{}` {} - by <@{}> <t:{}:R>{}\n{}",
+        case.id,
+        case.action.as_str(),
+        targets_text(case, 3),
+        case.moderator_id,
+        case.created_at,
+        if case.closed { " (closed)" } else { "" },
+        case.reason.as_deref().unwrap_or("No reason provided")
+    )
+}
+
+pub(crate) fn case_embed(case: &Case) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!(
+            "Case #{} - {}{}",
+            case.id,
+            case.action.as_str(),
+            if case.is_mass() { format!(" ({} users)", case.targets.len()) } else { String::new() }
+        ))
+        .color(action_color(case.action))
+        .field("Users", targets_text(case, 20), false)
+        .field("Moderator", format!("<@{}>", case.moderator_id), true)
+        .field("Created", format!("<t:{}:f>", case.created_at), true)
+        .field("Status", status_text(case), true)
+        .field("Reason", case.reason.as_deref().unwrap_or("No reason provided"), false);
+
+    if let Some(exp) = case.expires_at {
+        let len = (exp - case.created_at).max(0) as u64;
+        embed = embed.field("Duration", format!("{} (ends <t:{}:R>)", format_duration(len), exp), true);
+    }
+    if let Some(parent) = case.split_from {
+        embed = embed.field("Split from", format!("#{}", parent), true);
+    }
+    if !case.escalated_from.is_empty() {
+        let warns: Vec<String> = case.escalated_from.iter().map(|id| format!("#{}", id)).collect();
+        embed = embed.field("Escalated from", truncate(&warns.join(", "), 1024), true);
+    }
+    if !case.proof.is_empty() {
+        let proof: Vec<String> = case.proof.iter().enumerate().map(|(i, p)| format!("{}. {}", i + 1, p)).collect();
+        embed = embed.field("Proof", truncate(&proof.join("\n"), 1024), false);
+    }
+    if !case.history.is_empty() {
+        let lines: Vec<String> = case
+            .history
+            .iter()
+            .rev()
+            .take(10)
+            .map(|e| {
+                let who = e.moderator_id.map(|m| format!("<@{}>", m)).unwrap_or_else(|| "system".to_string());
+                match &e.detail {
+                    Some(d) => format!("<t:{}:d> {} by {}: {}", e.at, e.kind.as_str(), who, d),
+                    None => format!("<t:{}:d> {} by {}", e.at, e.kind.as_str(), who),
+                }
+            })
+            .collect();
+        embed = embed.field("History", truncate(&lines.join("\n"), 1024), false);
+    }
+    embed
+}
+
+/// Looks up a case by id, or the newest case of the guild when no id is given. Deleted cases are treated as missing.
+pub(crate) fn find_case(store: &CaseStore, guild_id: u64, id: Option<u64>) -> Result<Option<Case>, crate::Error> {
+    match id {
+        Some(id) => Ok(store.get(guild_id, id)?.filter(|c| !c.deleted)),
+        None => store.latest(guild_id),
+    }
+}
+
+/// Reverts what an open case still enforces on Discord: bans are lifted and timeouts removed.
+/// Returns a message for every target that could not be reverted.
+pub(crate) async fn lift_case(ctx: &serenity::Context, case: &Case, audit: &str) -> Vec<String> {
+    let guild_id = serenity::GuildId::new(case.guild_id);
+    let mut failures = Vec::new();
+    for target in &case.targets {
+        let user_id = serenity::UserId::new(*target);
+        let result = match case.action {
+            CaseAction::Ban => ctx.http.remove_ban(guild_id, user_id, Some(audit)).await,
+            CaseAction::Mute => guild_id
+                .edit_member(ctx, user_id, serenity::EditMember::new().enable_communication().audit_log_reason(audit))
+                .await
+                .map(|_| ()),
+            CaseAction::Warn | CaseAction::Kick => Ok(()),
+        };
+        if let Err(e) = result {
+            failures.push(format!("<@{}>: {}", target, e));
+        }
+    }
+    failures
+}
+
+/// Moves one user of a mass case into a case of their own and returns the new case.
+/// Both cases are saved and get a Split entry in their history.
+pub(crate) fn split_case(store: &CaseStore, case: &mut Case, uid: u64, moderator_id: u64) -> Result<Case, crate::Error> {
+    let moderator = Some(moderator_id);
+    let mut split = store.create(
+        case.guild_id,
+        NewCase {
+            action: case.action,
+            targets: vec![uid],
+            moderator_id: case.moderator_id,
+            reason: case.reason.clone(),
+            expires_at: case.expires_at,
+        },
+    )?;
+    // The split case stands in for the same action, so it keeps the original timing and proof
+    split.created_at = case.created_at;
+    split.closed = case.closed;
+    split.closed_reason = case.closed_reason.clone();
+    split.proof = case.proof.clone();
+    split.split_from = Some(case.id);
+    split.record(moderator, CaseEventKind::Split, Some(format!("split from #{}", case.id)));
+    store.save(&split)?;
+
+    case.targets.retain(|t| *t != uid);
+    case.record(moderator, CaseEventKind::Split, Some(format!("<@{}> moved to #{}", uid, split.id)));
+    store.save(case)?;
+    Ok(split)
+}
+
+pub(crate) fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max.saturating_sub(3)).collect();
+    out.push_str("...");
+    out
+}
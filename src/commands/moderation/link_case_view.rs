@@ -1,2 +1,271 @@
 // Links this server's case view with another server, so both servers can view each other's moderation cases.
-// linkcaseview [serverid]
\ No newline at end of file
+// linkcaseview <request|accept|share|revoke|list> [serverid]
+
+use crate::data::cases::links::{CaseLinkStore, LinkStatus, ALL_ACTIONS};
+use crate::data::cases::store::CaseAction;
+use poise::serenity_prelude as serenity;
+
+fn parse_guild_id(input: &str) -> Option<u64> {
+    input.trim().parse::<u64>().ok().filter(|id| *id > 0)
+}
+
+fn actions_text(actions: &[CaseAction]) -> String {
+    if actions.is_empty() {
+        "nothing".to_string()
+    } else {
+        actions.iter().map(|a| format!("`{}`", a.as_str())).collect::<Vec<_>>().join(", ")
+    }
+}
+
+async fn guild_name(ctx: poise::Context<'_, crate::Data, crate::Error>, guild_id: u64) -> Option<(String, Option<serenity::ChannelId>)> {
+    let id = serenity::GuildId::new(guild_id);
+    if let Some(g) = ctx.cache().guild(id) {
+        return Some((g.name.clone(), g.system_channel_id));
+    }
+    id.to_partial_guild(ctx).await.ok().map(|g| (g.name, g.system_channel_id))
+}
+
+pub(crate) async fn guild_label(ctx: poise::Context<'_, crate::Data, crate::Error>, guild_id: u64) -> String {
+    guild_name(ctx, guild_id).await.map(|(n, _)| n).unwrap_or_else(|| guild_id.to_string())
+}
+
+/// Best effort notice in the other server's system channel.
+async fn notify(ctx: poise::Context<'_, crate::Data, crate::Error>, channel: Option<serenity::ChannelId>, text: String) -> bool {
+    let Some(channel) = channel else { return false };
+    let embed = serenity::CreateEmbed::default().title("Case view link").description(text).color(0x3B82F6);
+    channel.send_message(ctx, serenity::CreateMessage::new().embed(embed)).await.is_ok()
+}
+
+/// Resolves the `server` option of the case commands: the guild id and the action types that
+/// server shares with the current one. On failure the error is a message for the user.
+pub(crate) fn linked_view(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    viewer: u64,
+    server: &str,
+) -> Result<Result<(u64, Vec<CaseAction>), String>, crate::Error> {
+    let Some(owner) = parse_guild_id(server) else {
+        return Ok(Err(format!("`{}` is not a valid server ID.", server)));
+    };
+    if owner == viewer {
+        return Ok(Ok((owner, ALL_ACTIONS.to_vec())));
+    }
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    match links.shared_with(viewer, owner)? {
+        Some(shared) if shared.is_empty() => Ok(Err("That server does not share any case types with this one.".to_string())),
+        Some(shared) => Ok(Ok((owner, shared))),
+        None => Ok(Err("This server has no active case view link with that server. See `linkcaseview list`.".to_string())),
+    }
+}
+
+/// Links this server's case view with another server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "linkcaseview",
+    guild_only,
+    required_permissions = "ADMINISTRATOR",
+    subcommands("request", "accept", "share", "revoke", "list"),
+    subcommand_required
+)]
+pub async fn link_case_view(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Asks another server to link case views. An admin there has to accept.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "ADMINISTRATOR")]
+async fn request(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the other server"] server: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(other) = parse_guild_id(&server) else {
+        ctx.say(format!("`{}` is not a valid server ID.", server)).await?;
+        return Ok(());
+    };
+    if other == guild_id.get() {
+        ctx.say("A server can't link with itself.").await?;
+        return Ok(());
+    }
+    let Some((other_name, other_channel)) = guild_name(ctx, other).await else {
+        ctx.say("I'm not a member of that server, so it can't be linked.").await?;
+        return Ok(());
+    };
+
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    if let Some(existing) = links.get(guild_id.get(), other)? {
+        let msg = match existing.status {
+            LinkStatus::Active => format!("This server is already linked with **{}**.", other_name),
+            LinkStatus::Outgoing => format!("A request to **{}** is already pending.", other_name),
+            LinkStatus::Incoming => format!(
+                "**{}** already asked to link with this server. Accept it with `linkcaseview accept {}`.",
+                other_name, other
+            ),
+        };
+        ctx.say(msg).await?;
+        return Ok(());
+    }
+
+    links.request(guild_id.get(), other, ctx.author().id.get())?;
+
+    let own_name = ctx.guild().map(|g| g.name.clone()).unwrap_or_else(|| guild_id.to_string());
+    let notified = notify(
+        ctx,
+        other_channel,
+        format!(
+            "**{}** (`{}`) asked to link case views with this server, so both can look up each other's moderation cases.\n\
+             An administrator can accept with `linkcaseview accept {}` or decline with `linkcaseview revoke {}`.",
+            own_name, guild_id, guild_id, guild_id
+        ),
+    )
+    .await;
+
+    ctx.say(format!(
+        "Sent a link request to **{}**.{}",
+        other_name,
+        if notified { "" } else { " I couldn't post a notice there, so let one of their admins know." }
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Accepts a link request another server sent to this one.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "ADMINISTRATOR")]
+async fn accept(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the server that sent the request"] server: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(other) = parse_guild_id(&server) else {
+        ctx.say(format!("`{}` is not a valid server ID.", server)).await?;
+        return Ok(());
+    };
+
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    if !links.accept(guild_id.get(), other)? {
+        ctx.say("There is no pending request from that server.").await?;
+        return Ok(());
+    }
+
+    let (other_name, other_channel) = guild_name(ctx, other).await.unwrap_or_else(|| (other.to_string(), None));
+    let own_name = ctx.guild().map(|g| g.name.clone()).unwrap_or_else(|| guild_id.to_string());
+    notify(ctx, other_channel, format!("**{}** accepted the case view link with this server.", own_name)).await;
+
+    ctx.say(format!(
+        "Linked case views with **{}**. This server currently shares {}; change it with `linkcaseview share`.",
+        other_name,
+        actions_text(&ALL_ACTIONS)
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Sets which case types this server shares with a linked server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "ADMINISTRATOR")]
+async fn share(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the linked server"] server: String,
+    #[rest]
+    #[description = "Case types to share: warn, mute, kick, ban, all or none"]
+    actions: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(other) = parse_guild_id(&server) else {
+        ctx.say(format!("`{}` is not a valid server ID.", server)).await?;
+        return Ok(());
+    };
+
+    let mut shared: Vec<CaseAction> = Vec::new();
+    for token in actions.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+        match token.to_lowercase().as_str() {
+            "all" => shared = ALL_ACTIONS.to_vec(),
+            "none" => shared.clear(),
+            t => match CaseAction::parse(t) {
+                Some(a) if !shared.contains(&a) => shared.push(a),
+                Some(_) => {}
+                None => {
+                    ctx.say(format!("Unknown case type `{}`. Use warn, mute, kick, ban, all or none.", token)).await?;
+                    return Ok(());
+                }
+            },
+        }
+    }
+
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    let Some(mut link) = links.get(guild_id.get(), other)? else {
+        ctx.say("This server has no link with that server.").await?;
+        return Ok(());
+    };
+    link.shared = shared;
+    links.save(&link)?;
+
+    ctx.say(format!("This server now shares {} with `{}`.", actions_text(&link.shared), other)).await?;
+    Ok(())
+}
+
+/// Removes a link or declines/cancels a pending request. Either server can do this at any time.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "ADMINISTRATOR")]
+async fn revoke(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "ID of the other server"] server: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(other) = parse_guild_id(&server) else {
+        ctx.say(format!("`{}` is not a valid server ID.", server)).await?;
+        return Ok(());
+    };
+
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    if !links.revoke(guild_id.get(), other)? {
+        ctx.say("This server has no link or pending request with that server.").await?;
+        return Ok(());
+    }
+
+    let other_channel = guild_name(ctx, other).await.and_then(|(_, c)| c);
+    let own_name = ctx.guild().map(|g| g.name.clone()).unwrap_or_else(|| guild_id.to_string());
+    notify(ctx, other_channel, format!("**{}** removed the case view link with this server.", own_name)).await;
+
+    ctx.say(format!("Removed the case view link with `{}`.", other)).await?;
+    Ok(())
+}
+
+/// Lists this server's case view links and pending requests.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "ADMINISTRATOR")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let links = CaseLinkStore::open(&ctx.data().db)?;
+    let all = links.list(guild_id.get())?;
+    if all.is_empty() {
+        ctx.say("This server has no case view links. Start one with `linkcaseview request <server id>`.").await?;
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+    for link in all {
+        let name = guild_name(ctx, link.other_guild_id).await.map(|(n, _)| n).unwrap_or_else(|| "unknown server".to_string());
+        let line = match link.status {
+            LinkStatus::Outgoing => format!("**{}** (`{}`) - request sent, waiting for them", name, link.other_guild_id),
+            LinkStatus::Incoming => format!("**{}** (`{}`) - wants to link, accept with `linkcaseview accept {}`", name, link.other_guild_id, link.other_guild_id),
+            LinkStatus::Active => {
+                let theirs = links
+                    .get(link.other_guild_id, guild_id.get())?
+                    .map(|l| actions_text(&l.shared))
+                    .unwrap_or_else(|| "nothing".to_string());
+                format!(
+                    "**{}** (`{}`) - linked\n  You share: {}\n  They share: {}",
+                    name,
+                    link.other_guild_id,
+                    actions_text(&link.shared),
+                    theirs
+                )
+            }
+        };
+        lines.push(line);
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .title("Case view links")
+        .description(lines.join("\n\n"))
+        .color(0x3B82F6);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -1,4 +1,68 @@
 // Lock a text or voice channel. When locking a voice channel, all connected users will be kicked from it.
 // Add +v or +t (or +vt) at the end to revoke view or thread permissions, too.
 // Add +s or (+vts) to silently lock the channel without an info message.
-// lock [channel] [reason] [+(v|t|s)]
\ No newline at end of file
+// lock [channel] [reason] [+(v|t|s)]
+
+use super::locking::{lock_channel, split_flags};
+use super::utils::audit_reason;
+use crate::data::locks::store::{LockSource, LockStore};
+use poise::serenity_prelude as serenity;
+
+/// Lock a text or voice channel. Voice users are disconnected. Flags: +v view, +t threads, +s silent.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES | MOVE_MEMBERS"
+)]
+pub async fn lock(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to lock (this one if omitted)"] channel: Option<serenity::GuildChannel>,
+    #[rest]
+    #[description = "Reason, optionally ending in +v, +t and/or +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let (reason, flags) = match split_flags(reason, "vts") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+    let channel = match channel {
+        Some(c) => c,
+        None => match ctx.guild_channel().await {
+            Some(c) => c,
+            None => {
+                ctx.say("Run this in a server channel or name one.").await?;
+                return Ok(());
+            }
+        },
+    };
+    if Some(channel.guild_id) != ctx.guild_id() {
+        ctx.say("That channel is not in this server.").await?;
+        return Ok(());
+    }
+
+    let store = LockStore::open(&ctx.data().db)?;
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let result = lock_channel(
+        ctx.serenity_context(),
+        &store,
+        &channel,
+        LockSource::Single,
+        &flags,
+        reason.as_deref(),
+        &audit,
+        ctx.author().id.get(),
+    )
+    .await?;
+    let msg = match result {
+        Ok(0) => format!("Locked <#{}>.", channel.id),
+        Ok(kicked) => format!("Locked <#{}> and disconnected {} user(s).", channel.id, kicked),
+        Err(msg) => msg,
+    };
+    ctx.send(poise::CreateReply::default().content(msg).ephemeral(flags.silent)).await?;
+    Ok(())
+}
@@ -3,3 +3,54 @@
 // Add +s or (+vs) to silently lock the channel without an info message.
 // lockall [reason] [+(v|t|s)
 
+use super::locking::{lock_many, split_flags};
+use super::utils::audit_reason;
+use crate::data::locks::store::{LockSource, LockStore};
+
+/// Locks all channels set with `lockconfig`. Flags: +v view, +t threads, +s silent.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "lockall",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES | MOVE_MEMBERS"
+)]
+pub async fn lock_all(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Reason, optionally ending in +v, +t and/or +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (reason, flags) = match split_flags(reason, "vts") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+    let store = LockStore::open(&ctx.data().db)?;
+    let config = store.config(guild_id.get())?;
+    if config.channels.is_empty() {
+        ctx.say("No channels are set for `lockall`. Add some with `lockconfig add`.").await?;
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let msg = lock_many(
+        ctx.serenity_context(),
+        &store,
+        guild_id,
+        &config.channels,
+        LockSource::LockAll,
+        &flags,
+        reason.as_deref(),
+        &audit,
+        ctx.author().id.get(),
+    )
+    .await?;
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Sets which channels lockall locks.
+// lockconfig <add|remove|list> [channel]
+
+use super::locking::is_lockable;
+use crate::data::locks::store::LockStore;
+use poise::serenity_prelude as serenity;
+
+/// Sets which channels lockall locks.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "lockconfig",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list"),
+    subcommand_required
+)]
+pub async fn lock_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Adds a channel to the lockall set.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Text, forum or voice channel"] channel: serenity::GuildChannel,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id || !is_lockable(channel.kind) {
+        ctx.say("Pick a text, forum or voice channel of this server.").await?;
+        return Ok(());
+    }
+    let store = LockStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    if config.channels.contains(&channel.id.get()) {
+        ctx.say(format!("<#{}> is already in the lockall set.", channel.id)).await?;
+        return Ok(());
+    }
+    config.channels.push(channel.id.get());
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("Added <#{}> to the lockall set ({} channel(s)).", channel.id, config.channels.len())).await?;
+    Ok(())
+}
+
+/// Removes a channel from the lockall set.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to remove"] channel: serenity::ChannelId,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = LockStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let before = config.channels.len();
+    config.channels.retain(|c| *c != channel.get());
+    if config.channels.len() == before {
+        ctx.say(format!("<#{}> is not in the lockall set.", channel)).await?;
+        return Ok(());
+    }
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("Removed <#{}> from the lockall set.", channel)).await?;
+    Ok(())
+}
+
+/// Lists the lockall set and the channels that are locked right now.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = LockStore::open(&ctx.data().db)?;
+    let config = store.config(guild_id.get())?;
+    let set = if config.channels.is_empty() {
+        "none".to_string()
+    } else {
+        config.channels.iter().map(|c| format!("<#{}>", c)).collect::<Vec<_>>().join(", ")
+    };
+    let locked: Vec<String> = store
+        .list(guild_id.get())?
+        .iter()
+        .map(|s| format!("<#{}> - {:?} by <@{}> <t:{}:R>", s.channel_id, s.source, s.locked_by, s.locked_at))
+        .collect();
+    let embed = serenity::CreateEmbed::default()
+        .title("Channel locks")
+        .field("Lockall set", super::cases::truncate(&set, 1024), false)
+        .field(
+            "Locked now",
+            if locked.is_empty() { "none".to_string() } else { super::cases::truncate(&locked.join("\n"), 1024) },
+            false,
+        )
+        .color(0x6B7280);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// Emergency lockdown: locks every channel regular members can see in one go, and lifts it again.
+// lockdown <start|end> [reason] [+(v|t|s)]
+
+use super::locking::{is_lockable, lock_many, split_flags, unlock_many};
+use super::utils::audit_reason;
+use crate::data::locks::store::{LockSource, LockStore};
+use poise::serenity_prelude as serenity;
+
+/// Emergency lockdown of the whole server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD | MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES | MOVE_MEMBERS",
+    subcommands("start", "end"),
+    subcommand_required
+)]
+pub async fn lockdown(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Locks every channel @everyone can see. Flags: +v view, +t threads, +s silent.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD | MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES | MOVE_MEMBERS"
+)]
+async fn start(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Reason, optionally ending in +v, +t and/or +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (reason, flags) = match split_flags(reason, "vts") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+
+    // Only public channels are touched; private staff channels keep working during a lockdown
+    let public: Vec<u64> = {
+        let Some(guild) = ctx.guild() else { return Ok(()) };
+        let everyone = guild.roles.get(&serenity::RoleId::new(guild_id.get())).map(|r| r.permissions).unwrap_or_default();
+        guild
+            .channels
+            .values()
+            .filter(|c| is_lockable(c.kind))
+            .filter(|c| {
+                let mut perms = everyone;
+                for o in &c.permission_overwrites {
+                    if o.kind == serenity::PermissionOverwriteType::Role(serenity::RoleId::new(guild_id.get())) {
+                        perms = (perms & !o.deny) | o.allow;
+                    }
+                }
+                perms.view_channel()
+            })
+            .map(|c| c.id.get())
+            .collect()
+    };
+    if public.is_empty() {
+        ctx.say("There are no public channels to lock.").await?;
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    let store = LockStore::open(&ctx.data().db)?;
+    let audit = audit_reason(ctx.author(), Some(&format!("Lockdown: {}", reason.as_deref().unwrap_or("no reason"))));
+    let summary = lock_many(
+        ctx.serenity_context(),
+        &store,
+        guild_id,
+        &public,
+        LockSource::Lockdown,
+        &flags,
+        reason.as_deref(),
+        &audit,
+        ctx.author().id.get(),
+    )
+    .await?;
+    ctx.say(format!("🚨 **Lockdown started.** {}\nLift it with `lockdown end`.", summary)).await?;
+    Ok(())
+}
+
+/// Lifts the lockdown, restoring every channel it locked. Add +s to unlock silently.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD | MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES"
+)]
+async fn end(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Reason, optionally ending in +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (reason, flags) = match split_flags(reason, "s") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+    ctx.defer().await?;
+
+    let store = LockStore::open(&ctx.data().db)?;
+    let audit = audit_reason(ctx.author(), Some(&format!("Lockdown lifted: {}", reason.as_deref().unwrap_or("no reason"))));
+    let summary = unlock_many(
+        ctx.serenity_context(),
+        &store,
+        guild_id,
+        |s| s.source == LockSource::Lockdown,
+        flags.silent,
+        reason.as_deref(),
+        &audit,
+    )
+    .await?;
+    ctx.say(format!("**Lockdown lifted.** {}", summary)).await?;
+    Ok(())
+}
@@ -0,0 +1,332 @@
+// Shared flow for lock, unlock, lockall, unlockall and lockdown.
+// Before a channel is locked its permission overwrites are stored, and unlocking writes exactly
+// those overwrites back, so nothing has to be guessed about the channel's previous state.
+
+use crate::data::locks::store::{LockSnapshot, LockSource, LockStore, OverwriteTarget, SavedOverwrite};
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub(crate) struct LockFlags {
+    pub view: bool,
+    pub threads: bool,
+    pub silent: bool,
+    pub lockall_only: bool,
+}
+
+/// Splits a trailing `+flags` token (e.g. `+vts`) off the reason. Only the letters in `allowed` are accepted.
+pub(crate) fn split_flags(reason: Option<String>, allowed: &str) -> Result<(Option<String>, LockFlags), String> {
+    let mut flags = LockFlags::default();
+    let Some(reason) = reason else { return Ok((None, flags)) };
+    let trimmed = reason.trim();
+    let (rest, token) = match trimmed.rsplit_once(char::is_whitespace) {
+        Some((rest, last)) if last.starts_with('+') => (rest.trim().to_string(), last),
+        None if trimmed.starts_with('+') => (String::new(), trimmed),
+        _ => return Ok((Some(trimmed.to_string()).filter(|r| !r.is_empty()), flags)),
+    };
+    for c in token[1..].chars() {
+        if !allowed.contains(c) {
+            return Err(format!("Unknown flag `+{}`. Allowed: {}", c, allowed.chars().map(|c| format!("+{}", c)).collect::<Vec<_>>().join(", ")));
+        }
+        match c {
+            'v' => flags.view = true,
+            't' => flags.threads = true,
+            's' => flags.silent = true,
+            'l' => flags.lockall_only = true,
+            _ => {}
+        }
+    }
+    Ok((Some(rest).filter(|r| !r.is_empty()), flags))
+}
+
+fn is_voice(kind: serenity::ChannelType) -> bool {
+    matches!(kind, serenity::ChannelType::Voice | serenity::ChannelType::Stage)
+}
+
+pub(crate) fn is_lockable(kind: serenity::ChannelType) -> bool {
+    is_voice(kind)
+        || matches!(
+            kind,
+            serenity::ChannelType::Text | serenity::ChannelType::News | serenity::ChannelType::Forum
+        )
+}
+
+/// The permissions a lock takes away from regular members in a channel.
+fn locked_permissions(kind: serenity::ChannelType, flags: &LockFlags) -> serenity::Permissions {
+    let mut perms = serenity::Permissions::SEND_MESSAGES | serenity::Permissions::ADD_REACTIONS;
+    if is_voice(kind) {
+        perms |= serenity::Permissions::CONNECT | serenity::Permissions::SPEAK;
+    }
+    if flags.threads {
+        perms |= serenity::Permissions::CREATE_PUBLIC_THREADS
+            | serenity::Permissions::CREATE_PRIVATE_THREADS
+            | serenity::Permissions::SEND_MESSAGES_IN_THREADS;
+    }
+    if flags.view {
+        perms |= serenity::Permissions::VIEW_CHANNEL;
+    }
+    perms
+}
+
+fn save_overwrite(o: &serenity::PermissionOverwrite) -> Option<SavedOverwrite> {
+    let (target, id) = match o.kind {
+        serenity::PermissionOverwriteType::Role(r) => (OverwriteTarget::Role, r.get()),
+        serenity::PermissionOverwriteType::Member(m) => (OverwriteTarget::Member, m.get()),
+        _ => return None,
+    };
+    Some(SavedOverwrite { target, id, allow: o.allow.bits(), deny: o.deny.bits() })
+}
+
+fn restore_overwrite(o: &SavedOverwrite) -> serenity::PermissionOverwrite {
+    serenity::PermissionOverwrite {
+        allow: serenity::Permissions::from_bits_truncate(o.allow),
+        deny: serenity::Permissions::from_bits_truncate(o.deny),
+        kind: match o.target {
+            OverwriteTarget::Role => serenity::PermissionOverwriteType::Role(serenity::RoleId::new(o.id)),
+            OverwriteTarget::Member => serenity::PermissionOverwriteType::Member(serenity::UserId::new(o.id)),
+        },
+    }
+}
+
+fn lock_embed(locked: bool, reason: Option<&str>) -> serenity::CreateEmbed {
+    let embed = serenity::CreateEmbed::default()
+        .title(if locked { "🔒 This channel has been locked" } else { "🔓 This channel has been unlocked" })
+        .color(if locked { 0xEF4444 } else { 0x22C55E });
+    match reason {
+        Some(r) => embed.description(format!("**Reason:** {}", r)),
+        None => embed,
+    }
+}
+
+/// Locks a channel for everyone without channel management rights. On refusal the error is a message for the moderator.
+/// Returns the number of users disconnected from a voice channel.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn lock_channel(
+    ctx: &serenity::Context,
+    store: &LockStore,
+    channel: &serenity::GuildChannel,
+    source: LockSource,
+    flags: &LockFlags,
+    reason: Option<&str>,
+    audit: &str,
+    moderator_id: u64,
+) -> Result<Result<usize, String>, crate::Error> {
+    let guild_id = channel.guild_id;
+    if !is_lockable(channel.kind) {
+        return Ok(Err(format!("<#{}> is not a text, forum or voice channel.", channel.id)));
+    }
+    if store.get(guild_id.get(), channel.id.get())?.is_some() {
+        return Ok(Err(format!("<#{}> is already locked.", channel.id)));
+    }
+
+    // Roles that can manage channels keep their access, so moderators can still talk in a locked channel
+    let (staff_roles, staff_members): (Vec<serenity::RoleId>, Vec<serenity::UserId>) = {
+        let Some(guild) = ctx.cache.guild(guild_id) else {
+            return Ok(Err("This server is not cached yet, try again in a moment.".to_string()));
+        };
+        let roles: Vec<serenity::RoleId> = guild
+            .roles
+            .values()
+            .filter(|r| r.permissions.administrator() || r.permissions.manage_channels())
+            .map(|r| r.id)
+            .collect();
+        let members = guild
+            .members
+            .values()
+            .filter(|m| m.roles.iter().any(|r| roles.contains(r)))
+            .map(|m| m.user.id)
+            .collect();
+        (roles, members)
+    };
+    let bot_id = ctx.cache.current_user().id;
+    let everyone = serenity::RoleId::new(guild_id.get());
+    let bits = locked_permissions(channel.kind, flags);
+
+    let mut overwrites = channel.permission_overwrites.clone();
+    let mut has_everyone = false;
+    for o in &mut overwrites {
+        match o.kind {
+            serenity::PermissionOverwriteType::Role(r) if r == everyone => {
+                has_everyone = true;
+                o.allow &= !bits;
+                o.deny |= bits;
+            }
+            serenity::PermissionOverwriteType::Role(r) if !staff_roles.contains(&r) => o.allow &= !bits,
+            // Members allowed to talk on their own lose that too; the snapshot gives it back on unlock
+            serenity::PermissionOverwriteType::Member(m) if m != bot_id && !staff_members.contains(&m) => o.allow &= !bits,
+            _ => {}
+        }
+    }
+    if !has_everyone {
+        overwrites.push(serenity::PermissionOverwrite {
+            allow: serenity::Permissions::empty(),
+            deny: bits,
+            kind: serenity::PermissionOverwriteType::Role(everyone),
+        });
+    }
+    // The bot has to keep posting and managing the channel while it's locked
+    let bot_access = bits | serenity::Permissions::VIEW_CHANNEL | serenity::Permissions::MANAGE_CHANNELS;
+    match overwrites.iter_mut().find(|o| o.kind == serenity::PermissionOverwriteType::Member(bot_id)) {
+        Some(o) => {
+            o.allow |= bot_access;
+            o.deny &= !bot_access;
+        }
+        None => overwrites.push(serenity::PermissionOverwrite {
+            allow: bot_access,
+            deny: serenity::Permissions::empty(),
+            kind: serenity::PermissionOverwriteType::Member(bot_id),
+        }),
+    }
+
+    // The snapshot is stored first, so a lock that half-failed can still be undone
+    store.save(&LockSnapshot {
+        guild_id: guild_id.get(),
+        channel_id: channel.id.get(),
+        overwrites: channel.permission_overwrites.iter().filter_map(save_overwrite).collect(),
+        source,
+        locked_by: moderator_id,
+        locked_at: chrono::Utc::now().timestamp(),
+        reason: reason.map(str::to_string),
+    })?;
+    if let Err(e) = channel
+        .id
+        .edit(ctx, serenity::EditChannel::new().permissions(overwrites.clone()).audit_log_reason(audit))
+        .await
+    {
+        store.remove(guild_id.get(), channel.id.get())?;
+        return Ok(Err(format!("Couldn't lock <#{}>: {}", channel.id, e)));
+    }
+
+    let mut kicked = 0;
+    if is_voice(channel.kind) {
+        let to_kick: Vec<serenity::UserId> = {
+            let mut locked = channel.clone();
+            locked.permission_overwrites = overwrites;
+            match ctx.cache.guild(guild_id) {
+                Some(guild) => guild
+                    .voice_states
+                    .values()
+                    .filter(|v| v.channel_id == Some(channel.id))
+                    .filter_map(|v| guild.members.get(&v.user_id))
+                    .filter(|m| !guild.user_permissions_in(&locked, m).connect())
+                    .map(|m| m.user.id)
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+        for user_id in to_kick {
+            if guild_id.disconnect_member(ctx, user_id).await.is_ok() {
+                kicked += 1;
+            }
+        }
+    }
+
+    if !flags.silent {
+        let _ = channel.id.send_message(ctx, serenity::CreateMessage::new().embed(lock_embed(true, reason))).await;
+    }
+    Ok(Ok(kicked))
+}
+
+/// Restores the overwrites a channel had before it was locked. On refusal the error is a message for the moderator.
+pub(crate) async fn unlock_channel(
+    ctx: &serenity::Context,
+    store: &LockStore,
+    snapshot: &LockSnapshot,
+    silent: bool,
+    reason: Option<&str>,
+    audit: &str,
+) -> Result<Result<(), String>, crate::Error> {
+    let channel_id = serenity::ChannelId::new(snapshot.channel_id);
+    let overwrites: Vec<serenity::PermissionOverwrite> = snapshot.overwrites.iter().map(restore_overwrite).collect();
+    match channel_id
+        .edit(ctx, serenity::EditChannel::new().permissions(overwrites).audit_log_reason(audit))
+        .await
+    {
+        Ok(_) => {}
+        // A deleted channel has nothing left to unlock
+        Err(serenity::Error::Http(e)) if e.status_code().is_some_and(|s| s.as_u16() == 404) => {
+            store.remove(snapshot.guild_id, snapshot.channel_id)?;
+            return Ok(Err(format!("<#{}> no longer exists.", channel_id)));
+        }
+        Err(e) => return Ok(Err(format!("Couldn't unlock <#{}>: {}", channel_id, e))),
+    }
+    store.remove(snapshot.guild_id, snapshot.channel_id)?;
+
+    if !silent {
+        let _ = channel_id.send_message(ctx, serenity::CreateMessage::new().embed(lock_embed(false, reason))).await;
+    }
+    Ok(Ok(()))
+}
+
+/// Locks several channels and summarizes the result in one message.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn lock_many(
+    ctx: &serenity::Context,
+    store: &LockStore,
+    guild_id: serenity::GuildId,
+    channel_ids: &[u64],
+    source: LockSource,
+    flags: &LockFlags,
+    reason: Option<&str>,
+    audit: &str,
+    moderator_id: u64,
+) -> Result<String, crate::Error> {
+    let channels: HashMap<serenity::ChannelId, serenity::GuildChannel> = guild_id.channels(ctx).await?;
+    let mut locked = 0;
+    let mut kicked = 0;
+    let mut problems = Vec::new();
+    for id in channel_ids {
+        let Some(channel) = channels.get(&serenity::ChannelId::new(*id)) else {
+            problems.push(format!("`{}` no longer exists.", id));
+            continue;
+        };
+        // Channels that are already locked stay as they are
+        if store.get(guild_id.get(), *id)?.is_some() {
+            continue;
+        }
+        match lock_channel(ctx, store, channel, source, flags, reason, audit, moderator_id).await? {
+            Ok(n) => {
+                locked += 1;
+                kicked += n;
+            }
+            Err(msg) => problems.push(msg),
+        }
+    }
+    let mut msg = format!("Locked {} channel(s).", locked);
+    if kicked > 0 {
+        msg.push_str(&format!(" Disconnected {} user(s) from voice.", kicked));
+    }
+    if !problems.is_empty() {
+        msg.push_str(&format!("\n{}", problems.join("\n")));
+    }
+    Ok(msg)
+}
+
+/// Unlocks every stored lock of a guild that matches the filter and summarizes the result.
+pub(crate) async fn unlock_many(
+    ctx: &serenity::Context,
+    store: &LockStore,
+    guild_id: serenity::GuildId,
+    filter: impl Fn(&LockSnapshot) -> bool,
+    silent: bool,
+    reason: Option<&str>,
+    audit: &str,
+) -> Result<String, crate::Error> {
+    let snapshots: Vec<LockSnapshot> = store.list(guild_id.get())?.into_iter().filter(|s| filter(s)).collect();
+    if snapshots.is_empty() {
+        return Ok("No channels are locked.".to_string());
+    }
+    let mut unlocked = 0;
+    let mut problems = Vec::new();
+    for snapshot in &snapshots {
+        match unlock_channel(ctx, store, snapshot, silent, reason, audit).await? {
+            Ok(()) => unlocked += 1,
+            Err(msg) => problems.push(msg),
+        }
+    }
+    let mut msg = format!("Unlocked {} channel(s).", unlocked);
+    if !problems.is_empty() {
+        msg.push_str(&format!("\n{}", problems.join("\n")));
+    }
+    Ok(msg)
+}
@@ -0,0 +1,426 @@
+// Shared flow for the mass moderation commands (massban, masskick, massmute, masswarn).
+// Targets are collected up front, reason and duration are asked for through a modal,
+// and every target gets its own outcome in the summary and the downloadable results file.
+
+use super::utils::{audit_reason, format_duration, parse_duration, parse_user_ids, Hierarchy};
+use super::warnings::{escalate, warn_expiry};
+use crate::data::cases::store::{CaseAction, CaseStore, NewCase};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const MAX_TARGETS: usize = 500;
+const MAX_FILE_BYTES: u32 = 1_000_000;
+// Discord caps member timeouts at 28 days
+pub(crate) const MAX_MUTE_SECS: u64 = 28 * 86_400;
+
+#[derive(Debug, poise::Modal)]
+#[name = "Mass action details"]
+struct MassActionModal {
+    #[name = "Reason"]
+    #[placeholder = "Shown in the case and the audit log"]
+    #[paragraph]
+    #[max_length = 500]
+    reason: Option<String>,
+    #[name = "Duration"]
+    #[placeholder = "e.g. 30m, 12h, 7d (empty = permanent, ignored for kicks)"]
+    #[max_length = 20]
+    duration: Option<String>,
+}
+
+pub(crate) enum TargetOutcome {
+    Success,
+    Hierarchy,
+    AlreadyApplied,
+    NotFound,
+    Failed(String),
+}
+
+impl TargetOutcome {
+    pub(crate) fn label(&self, action: CaseAction) -> String {
+        match self {
+            TargetOutcome::Success => action.past_tense().to_string(),
+            TargetOutcome::Hierarchy => "hierarchy failure".to_string(),
+            TargetOutcome::AlreadyApplied => format!("already {}", action.past_tense()),
+            TargetOutcome::NotFound => "not found".to_string(),
+            TargetOutcome::Failed(e) => format!("failed: {}", e),
+        }
+    }
+}
+
+/// Entry point for the mass commands: targets come from mentions/IDs and an optional text file of IDs.
+pub(crate) async fn run(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    action: CaseAction,
+    users: Option<String>,
+    file: Option<serenity::Attachment>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say("This command can only be used in a server.").await?;
+        return Ok(());
+    };
+
+    let mut input = users.unwrap_or_default();
+    if let Some(att) = &file {
+        if att.size > MAX_FILE_BYTES {
+            ctx.say("The uploaded file is too large (max 1 MB).").await?;
+            return Ok(());
+        }
+        let bytes = att.download().await?;
+        input.push('\n');
+        input.push_str(&String::from_utf8_lossy(&bytes));
+    }
+
+    let (targets, invalid) = parse_user_ids(&input);
+    if targets.is_empty() {
+        ctx.say(format!(
+            "No users given. Usage: mass{} <user1> <user2> ... (mentions or IDs), or attach a text file of IDs.",
+            action.as_str()
+        ))
+        .await?;
+        return Ok(());
+    }
+    if targets.len() > MAX_TARGETS {
+        ctx.say(format!("Too many users ({}). The limit is {} per command.", targets.len(), MAX_TARGETS)).await?;
+        return Ok(());
+    }
+
+    let prefix = ctx.id().to_string();
+    let details_id = format!("{}_details", prefix);
+    let cancel_id = format!("{}_cancel", prefix);
+
+    let mut desc = format!("**{}** user(s) queued.", targets.len());
+    if !invalid.is_empty() {
+        let shown: Vec<&str> = invalid.iter().take(10).map(|s| s.as_str()).collect();
+        desc.push_str(&format!(
+            "\nIgnored {} unrecognised entr{}: `{}`{}",
+            invalid.len(),
+            if invalid.len() == 1 { "y" } else { "ies" },
+            shown.join("`, `"),
+            if invalid.len() > shown.len() { ", ..." } else { "" }
+        ));
+    }
+    desc.push_str("\n\nPress **Set reason & duration** to continue.");
+
+    let prompt_embed = serenity::CreateEmbed::default()
+        .title(format!("Mass {}", action.as_str()))
+        .description(desc)
+        .color(0xF59E0B);
+    let buttons = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(&details_id)
+            .label("Set reason & duration")
+            .style(serenity::ButtonStyle::Primary),
+        serenity::CreateButton::new(&cancel_id)
+            .label("Cancel")
+            .style(serenity::ButtonStyle::Danger),
+    ]);
+    let prompt = ctx
+        .send(poise::CreateReply::default().embed(prompt_embed).components(vec![buttons]))
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    let Some(mci) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(120))
+        .filter(move |mci| mci.data.custom_id.starts_with(&filter_prefix))
+        .await
+    else {
+        prompt
+            .edit(ctx, poise::CreateReply::default().content("Timed out, nothing was done.").components(vec![]))
+            .await?;
+        return Ok(());
+    };
+
+    if mci.data.custom_id == cancel_id {
+        mci.create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .content("Cancelled, nothing was done.")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(details) =
+        poise::execute_modal_on_component_interaction::<MassActionModal>(ctx, mci, None, Some(Duration::from_secs(300))).await?
+    else {
+        prompt
+            .edit(ctx, poise::CreateReply::default().content("Timed out, nothing was done.").components(vec![]))
+            .await?;
+        return Ok(());
+    };
+
+    let reason = details.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let mut notes: Vec<String> = Vec::new();
+    let duration_secs: Option<u64> = match details.duration.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        None => None,
+        Some(_) if action == CaseAction::Kick => {
+            notes.push("Duration ignored for kicks.".to_string());
+            None
+        }
+        Some(raw) => match parse_duration(raw) {
+            Some(d) => Some(d.as_secs()),
+            None => {
+                prompt
+                    .edit(
+                        ctx,
+                        poise::CreateReply::default()
+                            .content(format!("Invalid duration `{}`. Use e.g. 30m, 12h, 7d. Nothing was done.", raw))
+                            .components(vec![]),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+    let duration_secs = if action == CaseAction::Mute {
+        let secs = duration_secs.unwrap_or(MAX_MUTE_SECS);
+        if secs > MAX_MUTE_SECS {
+            notes.push("Mute duration capped at 28 days (Discord timeout limit).".to_string());
+        }
+        Some(secs.min(MAX_MUTE_SECS))
+    } else {
+        duration_secs
+    };
+
+    let hierarchy = Hierarchy::load(ctx.serenity_context(), guild_id).await?;
+    let moderator = guild_id.member(ctx, ctx.author().id).await?;
+    let bot_id = ctx.cache().current_user().id;
+    let bot = guild_id.member(ctx, bot_id).await?;
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let now = chrono::Utc::now().timestamp();
+    let until = duration_secs.map(|s| now + s as i64);
+    // Warns without a duration of their own decay after the guild's configured time
+    let until = if action == CaseAction::Warn { warn_expiry(&ctx.data().db, guild_id.get(), until)? } else { until };
+
+    let mut outcomes: Vec<(u64, TargetOutcome)> = Vec::with_capacity(targets.len());
+    for (i, uid) in targets.iter().enumerate() {
+        if i > 0 && i % 25 == 0 {
+            let _ = prompt
+                .edit(
+                    ctx,
+                    poise::CreateReply::default()
+                        .content(format!("Processing... {}/{}", i, targets.len()))
+                        .components(vec![]),
+                )
+                .await;
+        }
+        let outcome = apply(ctx.serenity_context(), &ctx.data().db, guild_id, action, *uid, &hierarchy, &moderator, &bot, &audit, until).await;
+        outcomes.push((*uid, outcome));
+    }
+
+    let succeeded: Vec<u64> = outcomes
+        .iter()
+        .filter(|(_, o)| matches!(o, TargetOutcome::Success))
+        .map(|(id, _)| *id)
+        .collect();
+
+    let case = if succeeded.is_empty() {
+        None
+    } else {
+        let store = CaseStore::open(&ctx.data().db)?;
+        Some(store.create(
+            guild_id.get(),
+            NewCase {
+                action,
+                targets: succeeded.clone(),
+                moderator_id: ctx.author().id.get(),
+                reason: reason.clone(),
+                expires_at: until,
+            },
+        )?)
+    };
+
+    let mut escalations: Vec<String> = Vec::new();
+    if action == CaseAction::Warn {
+        for uid in &succeeded {
+            if let Some(line) = escalate(ctx.serenity_context(), &ctx.data().db, guild_id, *uid, &hierarchy, &moderator, &bot).await? {
+                escalations.push(line);
+            }
+        }
+    }
+
+    let count = |f: fn(&TargetOutcome) -> bool| outcomes.iter().filter(|(_, o)| f(o)).count();
+    let hierarchy_n = count(|o| matches!(o, TargetOutcome::Hierarchy));
+    let already_n = count(|o| matches!(o, TargetOutcome::AlreadyApplied));
+    let not_found_n = count(|o| matches!(o, TargetOutcome::NotFound));
+    let failed_n = count(|o| matches!(o, TargetOutcome::Failed(_)));
+
+    let duration_text = until.map(|u| format_duration((u - now).max(0) as u64)).unwrap_or_else(|| "permanent".to_string());
+    let mut report = format!("Mass {}", action.as_str());
+    if let Some(c) = &case {
+        report.push_str(&format!(" - case #{}", c.id));
+    }
+    report.push_str(&format!(
+        "\nModerator: {} ({})\nReason: {}\n",
+        ctx.author().name,
+        ctx.author().id,
+        reason.as_deref().unwrap_or("No reason provided")
+    ));
+    if action != CaseAction::Kick {
+        report.push_str(&format!("Duration: {}\n", duration_text));
+    }
+    report.push_str("\nuser_id\tresult\n");
+    for (uid, o) in &outcomes {
+        report.push_str(&format!("{}\t{}\n", uid, o.label(action)));
+    }
+    if !escalations.is_empty() {
+        report.push_str("\nEscalations\n");
+        for line in &escalations {
+            report.push_str(&format!("{}\n", line));
+        }
+    }
+
+    let mut summary = format!(
+        "**{}**: {}\n**Hierarchy failure**: {}\n**Already {}**: {}\n**Not found**: {}\n**Failed**: {}",
+        capitalize(action.past_tense()),
+        succeeded.len(),
+        hierarchy_n,
+        action.past_tense(),
+        already_n,
+        not_found_n,
+        failed_n
+    );
+    if !escalations.is_empty() {
+        summary.push_str(&format!("\n**Escalated**: {} (see the results file)", escalations.len()));
+    }
+    if !notes.is_empty() {
+        summary.push_str(&format!("\n\n{}", notes.join("\n")));
+    }
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(match &case {
+            Some(c) => format!("Mass {} - case #{}", action.as_str(), c.id),
+            None => format!("Mass {} - no case created", action.as_str()),
+        })
+        .description(summary)
+        .field("Reason", reason.as_deref().unwrap_or("No reason provided"), false)
+        .color(if succeeded.is_empty() { 0x808080 } else { 0x22C55E });
+    if action != CaseAction::Kick {
+        embed = embed.field("Duration", duration_text, true);
+    }
+
+    prompt
+        .edit(
+            ctx,
+            poise::CreateReply::default()
+                .content(format!("Processed {} user(s).", targets.len()))
+                .components(vec![]),
+        )
+        .await?;
+    let file_name = match &case {
+        Some(c) => format!("mass{}-case-{}.txt", action.as_str(), c.id),
+        None => format!("mass{}-results.txt", action.as_str()),
+    };
+    ctx.send(
+        poise::CreateReply::default()
+            .embed(embed)
+            .attachment(serenity::CreateAttachment::bytes(report.into_bytes(), file_name)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The server permission a moderator needs to take an action.
+pub(crate) fn action_permission(action: CaseAction) -> fn(serenity::Permissions) -> bool {
+    match action {
+        CaseAction::Warn | CaseAction::Mute => serenity::Permissions::moderate_members,
+        CaseAction::Kick => serenity::Permissions::kick_members,
+        CaseAction::Ban => serenity::Permissions::ban_members,
+    }
+}
+
+/// Applies one action to one user after checking hierarchy and whether it is already in effect.
+/// No case is created; callers record their own.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn apply(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: serenity::GuildId,
+    action: CaseAction,
+    uid: u64,
+    hierarchy: &Hierarchy,
+    moderator: &serenity::Member,
+    bot: &serenity::Member,
+    audit: &str,
+    until: Option<i64>,
+) -> TargetOutcome {
+    let user_id = serenity::UserId::new(uid);
+    let member = guild_id.member(ctx, user_id).await.ok();
+
+    if let Some(m) = &member {
+        if !hierarchy.outranks(moderator, m) || !hierarchy.outranks(bot, m) {
+            return TargetOutcome::Hierarchy;
+        }
+    } else if action != CaseAction::Ban {
+        return TargetOutcome::NotFound;
+    }
+
+    let result = match action {
+        CaseAction::Ban => {
+            if member.is_none() && user_id.to_user(ctx).await.is_err() {
+                return TargetOutcome::NotFound;
+            }
+            match is_banned(&ctx.http, guild_id, user_id).await {
+                Ok(true) => return TargetOutcome::AlreadyApplied,
+                Ok(false) => {}
+                Err(e) => return TargetOutcome::Failed(e.to_string()),
+            }
+            // Banned users can't be messaged once they share no server with the bot, so the notice goes first
+            if member.is_some() {
+                super::appeals::send_ban_notice(ctx, db, guild_id, user_id).await;
+            }
+            guild_id.ban_with_reason(ctx, user_id, 0, audit).await
+        }
+        CaseAction::Kick => guild_id.kick_with_reason(ctx, user_id, audit).await,
+        CaseAction::Mute => {
+            let now = serenity::Timestamp::now();
+            if member
+                .as_ref()
+                .and_then(|m| m.communication_disabled_until)
+                .is_some_and(|t| t > now)
+            {
+                return TargetOutcome::AlreadyApplied;
+            }
+            let Some(ts) = until.and_then(|u| serenity::Timestamp::from_unix_timestamp(u).ok()) else {
+                return TargetOutcome::Failed("invalid mute duration".to_string());
+            };
+            guild_id
+                .edit_member(ctx, user_id, serenity::EditMember::new().disable_communication_until_datetime(ts).audit_log_reason(audit))
+                .await
+                .map(|_| ())
+        }
+        // Warns only exist as cases, the member itself is left untouched
+        CaseAction::Warn => Ok(()),
+    };
+
+    match result {
+        Ok(()) => TargetOutcome::Success,
+        Err(e) => TargetOutcome::Failed(e.to_string()),
+    }
+}
+
+pub(crate) async fn is_banned(
+    http: &serenity::Http,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+) -> Result<bool, serenity::Error> {
+    // Bans are listed in ascending user id order, so asking for the first ban after id - 1 finds this user if banned
+    let after = serenity::UserId::new(user_id.get().saturating_sub(1).max(1));
+    let bans = http
+        .get_bans(guild_id, Some(serenity::UserPagination::After(after)), Some(1))
+        .await?;
+    Ok(bans.first().is_some_and(|b| b.user.id == user_id))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
@@ -1,2 +1,23 @@
-// Bans multiple users at once - time and reason can be set after execution.
-// massban <user1> <user2> ...
\ No newline at end of file
+// massban <user1> <user2> ...
+
+use crate::data::cases::store::CaseAction;
+use poise::serenity_prelude as serenity;
+
+/// Bans multiple users at once - time and reason can be set after execution.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "massban",
+    guild_only,
+    required_permissions = "BAN_MEMBERS",
+    required_bot_permissions = "BAN_MEMBERS"
+)]
+pub async fn mass_ban(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Text file with user IDs (one per line)"] file: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Users to ban (mentions or IDs, separated by spaces)"]
+    users: Option<String>,
+) -> Result<(), crate::Error> {
+    super::mass::run(ctx, CaseAction::Ban, users, file).await
+}
@@ -1,2 +1,23 @@
-// Kicks multiple users at once - time and reason can be set after execution.
-// masskick <user1> <user2> ...
\ No newline at end of file
+// masskick <user1> <user2> ...
+
+use crate::data::cases::store::CaseAction;
+use poise::serenity_prelude as serenity;
+
+/// Kicks multiple users at once - time and reason can be set after execution.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "masskick",
+    guild_only,
+    required_permissions = "KICK_MEMBERS",
+    required_bot_permissions = "KICK_MEMBERS"
+)]
+pub async fn mass_kick(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Text file with user IDs (one per line)"] file: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Users to kick (mentions or IDs, separated by spaces)"]
+    users: Option<String>,
+) -> Result<(), crate::Error> {
+    super::mass::run(ctx, CaseAction::Kick, users, file).await
+}
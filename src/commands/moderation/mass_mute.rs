@@ -1,2 +1,23 @@
-// Mutes multiple users at once - time and reason can be set after execution.
-// massmute <user1> <user2> ...
\ No newline at end of file
+// massmute <user1> <user2> ...
+
+use crate::data::cases::store::CaseAction;
+use poise::serenity_prelude as serenity;
+
+/// Mutes multiple users at once - time and reason can be set after execution.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "massmute",
+    guild_only,
+    required_permissions = "MODERATE_MEMBERS",
+    required_bot_permissions = "MODERATE_MEMBERS"
+)]
+pub async fn mass_mute(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Text file with user IDs (one per line)"] file: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Users to mute (mentions or IDs, separated by spaces)"]
+    users: Option<String>,
+) -> Result<(), crate::Error> {
+    super::mass::run(ctx, CaseAction::Mute, users, file).await
+}
@@ -1,44 +1,60 @@
 mod ban;
 mod warn;
-mod warns;
-mod usernotes;
-mod unwarn;
+pub mod warns;
+pub mod usernotes;
+pub mod unwarn;
 mod unban;
-mod purge;
-mod prune;
+pub mod purge;
+pub mod prune;
 mod mute;
 mod unmute;
-mod lock;
-mod lock_all;
-mod unlock;
-mod unlock_all;
+pub mod lock;
+pub mod lock_all;
+pub mod unlock;
+pub mod unlock_all;
 mod kick;
-mod set_slow_mode;
-mod unblock_appeal_user;
-mod time_warn;
+pub mod set_slow_mode;
+pub mod unblock_appeal_user;
+pub mod time_warn;
 mod time_mute;
 mod time_kick;
 mod time_ban;
-mod set_proof;
-mod send_to_appeal;
-mod restore_appeal_message;
-mod report;
-mod report_send_missing;
-mod report_ignore_all;
-mod report_blacklist;
+pub mod set_proof;
+pub mod send_to_appeal;
+pub mod restore_appeal_message;
+pub mod report;
+pub mod report_send_missing;
+pub mod report_ignore_all;
+pub mod report_blacklist;
 mod name_warn;
 mod name_mute;
 mod name_kick;
 mod name_ban;
-mod mass_warn;
-mod mass_kick;
-mod mass_mute;
-mod mass_ban;
-mod link_case_view;
-mod case_close;
-mod case_update;
-mod case_split;
-mod case_list;
-mod case_info;
-mod case_delete;
-mod block_appeal_user;
\ No newline at end of file
+pub mod mass_warn;
+pub mod mass_kick;
+pub mod mass_mute;
+pub mod mass_ban;
+pub mod link_case_view;
+pub mod case_close;
+pub mod case_update;
+pub mod case_split;
+pub mod case_list;
+pub mod case_info;
+pub mod case_delete;
+pub mod block_appeal_user;
+pub mod appeal;
+pub mod appeal_config;
+pub mod appeals;
+pub(crate) mod cases;
+pub mod lock_config;
+pub mod lockdown;
+pub mod notes;
+mod locking;
+pub mod report_config;
+pub mod reports;
+pub mod slow_mode_config;
+pub mod slowmode;
+mod mass;
+pub(crate) mod utils;
+pub mod warn_config;
+mod warnings;
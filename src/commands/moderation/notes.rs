@@ -0,0 +1,182 @@
+// Edits, deletes and searches moderator notes. Earlier versions of a note stay in its history.
+// notes edit <note id> <new text>
+// notes delete <note id> [reason]
+// notes history <note id>
+// notes search <text> [user]
+// notes filechannel [channel]
+
+use super::cases::truncate;
+use super::usernotes::note_line;
+use crate::commands::pagination::paginate_embeds_ephemeral;
+use crate::data::notes::store::{NoteChange, NoteConfig, NoteRevision, NoteStore};
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 8;
+const MAX_NOTE_CHARS: usize = 1500;
+
+/// Edits, deletes and searches moderator notes.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MODERATE_MEMBERS",
+    subcommands("edit", "delete", "history", "search", "filechannel"),
+    subcommand_required
+)]
+pub async fn notes(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Replaces the text of a note. The old text is kept in its history.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn edit(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Note ID"] id: u64,
+    #[rest]
+    #[description = "New text"]
+    text: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let text = text.trim().to_string();
+    if text.is_empty() || text.chars().count() > MAX_NOTE_CHARS {
+        ctx.say(format!("Notes must be between 1 and {} characters long.", MAX_NOTE_CHARS)).await?;
+        return Ok(());
+    }
+    let store = NoteStore::open(&ctx.data().db)?;
+    let Some(mut note) = store.get(guild_id.get(), id)?.filter(|n| !n.deleted) else {
+        ctx.say(format!("Note #{} does not exist.", id)).await?;
+        return Ok(());
+    };
+    let now = chrono::Utc::now().timestamp();
+    note.history.push(NoteRevision {
+        at: now,
+        by: ctx.author().id.get(),
+        change: NoteChange::Edited,
+        previous: std::mem::replace(&mut note.content, text),
+        reason: None,
+    });
+    note.edited_at = Some(now);
+    store.save(&note)?;
+    ctx.send(poise::CreateReply::default().content(format!("Updated note #{}.", id)).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Deletes a note. It stays viewable through its history.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Note ID"] id: u64,
+    #[rest]
+    #[description = "Why the note is deleted"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = NoteStore::open(&ctx.data().db)?;
+    let Some(mut note) = store.get(guild_id.get(), id)?.filter(|n| !n.deleted) else {
+        ctx.say(format!("Note #{} does not exist.", id)).await?;
+        return Ok(());
+    };
+    note.deleted = true;
+    note.history.push(NoteRevision {
+        at: chrono::Utc::now().timestamp(),
+        by: ctx.author().id.get(),
+        change: NoteChange::Deleted,
+        previous: note.content.clone(),
+        reason,
+    });
+    store.save(&note)?;
+    ctx.send(poise::CreateReply::default().content(format!("Deleted note #{}.", id)).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Shows every version of a note, including deleted notes.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn history(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Note ID"] id: u64,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(note) = NoteStore::open(&ctx.data().db)?.get(guild_id.get(), id)? else {
+        ctx.say(format!("Note #{} does not exist.", id)).await?;
+        return Ok(());
+    };
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("Note #{}{}", note.id, if note.deleted { " (deleted)" } else { "" }))
+        .description(note_line(&note))
+        .color(if note.deleted { 0x808080 } else { 0x6366F1 });
+    if note.history.is_empty() {
+        embed = embed.field("History", "Never changed", false);
+    }
+    for rev in note.history.iter().rev().take(10) {
+        let what = match rev.change {
+            NoteChange::Edited => "Edited",
+            NoteChange::Deleted => "Deleted",
+        };
+        let mut text = format!("by <@{}>", rev.by);
+        if let Some(r) = &rev.reason {
+            text.push_str(&format!(" ({})", r));
+        }
+        text.push_str(&format!(", before:\n{}", rev.previous));
+        embed = embed.field(format!("{} <t:{}:R>", what, rev.at), truncate(&text, 1024), false);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed).ephemeral(true)).await?;
+    Ok(())
+}
+
+/// Searches the text of all notes, optionally only those about one user.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn search(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Text to look for"] query: String,
+    #[description = "Only notes about this user"] user: Option<serenity::User>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let needle = query.to_lowercase();
+    let found: Vec<_> = NoteStore::open(&ctx.data().db)?
+        .list(guild_id.get())?
+        .into_iter()
+        .filter(|n| user.as_ref().is_none_or(|u| n.user_id == u.id.get()))
+        .filter(|n| {
+            n.content.to_lowercase().contains(&needle)
+                || n.attachments.iter().any(|a| a.filename.to_lowercase().contains(&needle))
+        })
+        .collect();
+    if found.is_empty() {
+        ctx.send(poise::CreateReply::default().content(format!("No notes match `{}`.", query)).ephemeral(true))
+            .await?;
+        return Ok(());
+    }
+    let pages = found
+        .chunks(PAGE_SIZE)
+        .map(|chunk| {
+            serenity::CreateEmbed::default()
+                .title(format!("Notes matching \"{}\"", truncate(&query, 100)))
+                .description(chunk.iter().map(note_line).collect::<Vec<_>>().join("\n\n"))
+                .color(0x6366F1)
+                .footer(serenity::CreateEmbedFooter::new(format!("{} match(es)", found.len())))
+        })
+        .collect();
+    paginate_embeds_ephemeral(ctx, pages).await
+}
+
+/// Sets the channel files attached to notes are uploaded to (no channel turns file uploads off).
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn filechannel(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel for note files, ideally only visible to moderators"] channel: Option<serenity::GuildChannel>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = NoteStore::open(&ctx.data().db)?;
+    let Some(channel) = channel else {
+        store.set_config(guild_id.get(), None)?;
+        ctx.say("Notes can no longer have files attached.").await?;
+        return Ok(());
+    };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel is not in this server.").await?;
+        return Ok(());
+    }
+    store.set_config(guild_id.get(), Some(&NoteConfig { file_channel_id: channel.id.get() }))?;
+    ctx.say(format!("Files attached to notes will be uploaded in <#{}>.", channel.id)).await?;
+    Ok(())
+}
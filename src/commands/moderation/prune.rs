@@ -1,4 +1,302 @@
 // Prune members which were inactive for <time ago> (between 1-30 days) and optionally have the
 // specified roles. If no roles are specified, only members without roles will be pruned.
 // Optionally, specify a reason for the prune.
-// prune <time ago> [[role1] [role2] ...] [reason]
\ No newline at end of file
+// prune <time ago> [[role1] [role2] ...] [reason]
+
+use super::utils::{audit_reason, fetch_members};
+use crate::data::cases::store::{CaseAction, CaseStore, NewCase};
+use poise::serenity_prelude as serenity;
+use std::collections::HashSet;
+use std::time::Duration;
+
+const MAX_DAYS: u8 = 30;
+// Invites sent to pruned members stay valid for a week
+const INVITE_MAX_AGE: u32 = 7 * 86_400;
+// DMs are capped and spaced out so a large prune neither hits rate limits nor outlives the interaction
+const MAX_INVITES: usize = 100;
+const INVITE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(serde::Deserialize)]
+struct PruneResult {
+    pruned: Option<u64>,
+}
+
+/// Prune inactive members (1-30 days). Only role-less members unless roles are given.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "KICK_MEMBERS",
+    required_bot_permissions = "KICK_MEMBERS | MANAGE_GUILD"
+)]
+pub async fn prune(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Days without activity (1-30)"]
+    #[min = 1]
+    #[max = 30]
+    days: u8,
+    #[flag]
+    #[description = "DM pruned members an invite back"]
+    invite: bool,
+    #[rest]
+    #[description = "Roles to include (mentions or IDs), then the reason"]
+    args: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        ctx.say("This command can only be used in a server.").await?;
+        return Ok(());
+    };
+    if !(1..=MAX_DAYS).contains(&days) {
+        ctx.say(format!("Days must be between 1 and {}.", MAX_DAYS)).await?;
+        return Ok(());
+    }
+
+    let cached: Option<HashSet<u64>> = ctx.guild().map(|g| g.roles.keys().map(|r| r.get()).collect());
+    let guild_roles = match cached {
+        Some(r) => r,
+        None => guild_id.roles(ctx).await?.keys().map(|r| r.get()).collect(),
+    };
+    let (roles, reason) = split_roles(args.as_deref().unwrap_or(""), &guild_roles, guild_id.get());
+
+    let estimate: PruneResult = ctx.http().fire(count_request(guild_id, days, &roles)).await?;
+    let estimate = estimate.pruned.unwrap_or(0);
+
+    let prefix = ctx.id().to_string();
+    let confirm_id = format!("{}_confirm", prefix);
+    let cancel_id = format!("{}_cancel", prefix);
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title("Prune members")
+        .description(format!(
+            "**{}** member(s) inactive for **{}** day(s) would be removed.\n\nPress **Prune** to continue.",
+            estimate, days
+        ))
+        .field("Roles", describe_roles(&roles), false)
+        .field("Reason", reason.as_deref().unwrap_or("No reason provided"), false)
+        .color(0xF59E0B);
+    if invite {
+        embed = embed.field("Invite", "Pruned members will be sent an invite back", false);
+    }
+    let buttons = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(&confirm_id)
+            .label("Prune")
+            .style(serenity::ButtonStyle::Danger)
+            .disabled(estimate == 0),
+        serenity::CreateButton::new(&cancel_id)
+            .label("Cancel")
+            .style(serenity::ButtonStyle::Secondary),
+    ]);
+    let prompt = ctx
+        .send(poise::CreateReply::default().embed(embed).components(vec![buttons]))
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    let Some(mci) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(120))
+        .filter(move |mci| mci.data.custom_id.starts_with(&filter_prefix))
+        .await
+    else {
+        prompt
+            .edit(ctx, poise::CreateReply::default().content("Timed out, nothing was done.").components(vec![]))
+            .await?;
+        return Ok(());
+    };
+
+    if mci.data.custom_id == cancel_id {
+        mci.create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .content("Cancelled, nothing was done.")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    mci.create_response(
+        ctx,
+        serenity::CreateInteractionResponse::UpdateMessage(
+            serenity::CreateInteractionResponseMessage::new()
+                .content("Pruning...")
+                .components(vec![]),
+        ),
+    )
+    .await?;
+
+    // Discord doesn't say who was pruned, so eligible members are listed before and after the prune
+    let before = prune_candidates(ctx.http(), guild_id, &roles).await?;
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let mut headers = http::HeaderMap::new();
+    headers.insert("X-Audit-Log-Reason", http::HeaderValue::from_str(&urlencoding::encode(&audit))?);
+    let body = serde_json::json!({
+        "days": days,
+        "include_roles": roles.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+        "compute_prune_count": true,
+    });
+    let result: PruneResult = ctx
+        .http()
+        .fire(
+            serenity::Request::new(serenity::Route::GuildPrune { guild_id }, serenity::LightMethod::Post)
+                .body(Some(serde_json::to_vec(&body)?))
+                .headers(Some(headers)),
+        )
+        .await?;
+    // A failed second listing still leaves the prune itself recorded, just without its members
+    let pruned: Vec<u64> = match prune_candidates(ctx.http(), guild_id, &roles).await {
+        Ok(after) => {
+            let mut left: Vec<u64> = before.difference(&after).copied().collect();
+            left.sort_unstable();
+            left
+        }
+        Err(e) => {
+            eprintln!("listing members after the prune of guild {} failed: {}", guild_id, e);
+            Vec::new()
+        }
+    };
+    let count = result.pruned.unwrap_or(pruned.len() as u64);
+
+    let case_reason = format!(
+        "Prune ({} day(s) inactive, {} member(s)): {}",
+        days,
+        count,
+        reason.as_deref().unwrap_or("No reason provided")
+    );
+    let case = if count == 0 {
+        None
+    } else {
+        let store = CaseStore::open(&ctx.data().db)?;
+        Some(store.create(
+            guild_id.get(),
+            NewCase {
+                action: CaseAction::Kick,
+                // Empty when the members couldn't be told apart, the count stays in the reason
+                targets: pruned.clone(),
+                moderator_id: ctx.author().id.get(),
+                reason: Some(case_reason),
+                expires_at: None,
+            },
+        )?)
+    };
+
+    let mut summary = format!("**Pruned**: {}", count);
+    if invite && !pruned.is_empty() {
+        let (delivered, skipped) = send_invites(ctx, guild_id, &pruned).await;
+        summary.push_str(&format!("\n**Invites delivered**: {}", delivered));
+        if skipped > 0 {
+            summary.push_str(&format!(" ({} not sent, at most {} are sent per prune)", skipped, MAX_INVITES));
+        }
+    }
+
+    let embed = serenity::CreateEmbed::default()
+        .title(match &case {
+            Some(c) => format!("Prune - case #{}", c.id),
+            None => "Prune - no case created".to_string(),
+        })
+        .description(summary)
+        .field("Inactive for", format!("{} day(s)", days), true)
+        .field("Roles", describe_roles(&roles), true)
+        .field("Reason", reason.as_deref().unwrap_or("No reason provided"), false)
+        .color(if count == 0 { 0x808080 } else { 0x22C55E });
+    prompt
+        .edit(ctx, poise::CreateReply::default().content("").embed(embed).components(vec![]))
+        .await?;
+
+    Ok(())
+}
+
+/// serenity's prune count helper has no way to pass included roles, so the request is built by hand.
+fn count_request<'a>(guild_id: serenity::GuildId, days: u8, roles: &[u64]) -> serenity::Request<'a> {
+    let mut params = vec![("days", days.to_string())];
+    if !roles.is_empty() {
+        params.push(("include_roles", roles.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(",")));
+    }
+    serenity::Request::new(serenity::Route::GuildPrune { guild_id }, serenity::LightMethod::Get).params(Some(params))
+}
+
+/// Splits leading role mentions or IDs off the input; everything from the first other token on is the reason.
+fn split_roles(input: &str, guild_roles: &HashSet<u64>, everyone: u64) -> (Vec<u64>, Option<String>) {
+    let mut roles = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(token) = rest.split_whitespace().next() {
+        let inner = token
+            .strip_prefix("<@&")
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or(token);
+        match inner.parse::<u64>() {
+            Ok(id) if guild_roles.contains(&id) && id != everyone => {
+                if !roles.contains(&id) {
+                    roles.push(id);
+                }
+                rest = rest[token.len()..].trim_start();
+            }
+            _ => break,
+        }
+    }
+    let reason = Some(rest.trim().to_string()).filter(|r| !r.is_empty());
+    (roles, reason)
+}
+
+fn describe_roles(roles: &[u64]) -> String {
+    if roles.is_empty() {
+        "None (only members without roles)".to_string()
+    } else {
+        roles.iter().map(|r| format!("<@&{}>", r)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Members that a prune with these roles could remove: everyone whose roles are all among the included ones.
+async fn prune_candidates(
+    http: &serenity::Http,
+    guild_id: serenity::GuildId,
+    roles: &[u64],
+) -> Result<HashSet<u64>, serenity::Error> {
+    Ok(fetch_members(http, guild_id)
+        .await?
+        .into_iter()
+        .filter(|m| m.roles.iter().all(|r| roles.contains(&r.get())))
+        .map(|m| m.user.id.get())
+        .collect())
+}
+
+/// DMs pruned members an invite to this channel, at most [`MAX_INVITES`] of them. Only members that
+/// still share a server with the bot can be reached. Returns how many were reached and how many
+/// weren't tried.
+async fn send_invites(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    guild_id: serenity::GuildId,
+    users: &[u64],
+) -> (usize, usize) {
+    let skipped = users.len().saturating_sub(MAX_INVITES);
+    let invite = match ctx
+        .channel_id()
+        .create_invite(ctx, serenity::CreateInvite::new().max_age(INVITE_MAX_AGE).unique(true))
+        .await
+    {
+        Ok(i) => i,
+        Err(_) => return (0, skipped),
+    };
+    let guild_name = ctx
+        .guild()
+        .map(|g| g.name.clone())
+        .unwrap_or_else(|| guild_id.to_string());
+    let text = format!(
+        "You were removed from **{}** for inactivity. You're welcome back any time: {}",
+        guild_name,
+        invite.url()
+    );
+    let mut delivered = 0;
+    for (i, uid) in users.iter().take(MAX_INVITES).enumerate() {
+        if i > 0 {
+            tokio::time::sleep(INVITE_DELAY).await;
+        }
+        if let Ok(dm) = serenity::UserId::new(*uid).create_dm_channel(ctx).await
+            && dm.say(ctx, &text).await.is_ok()
+        {
+            delivered += 1;
+        }
+    }
+    (delivered, skipped)
+}
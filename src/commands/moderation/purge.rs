@@ -3,4 +3,323 @@
 // all messages untin <message>
 // all messages between <message> and <message2>
 // optionally only deletes messages from [user]
-// purge <count|time|message url/id> [between second message url/id|user] [user]
\ No newline at end of file
+// purge <count|time|message url/id> [between second message url/id|user] [user]
+
+use super::cases::truncate;
+use super::utils::{parse_duration, parse_message_link, parse_user_ids};
+use crate::data::logging::store::{LogStore, LogType};
+use poise::serenity_prelude as serenity;
+
+const MAX_COUNT: usize = 1000;
+// Upper bound of messages looked at, so a narrow filter can't walk the whole channel history
+const MAX_SCANNED: usize = 10_000;
+// Discord refuses bulk deletes of messages older than 14 days; the margin covers slow runs
+const BULK_MAX_AGE_SECS: i64 = 14 * 86_400 - 3600;
+
+enum Range {
+    Count(usize),
+    Since(i64),                   // unix timestamp
+    Until(serenity::MessageId),   // everything newer than the message, including it
+    Between(serenity::MessageId, serenity::MessageId),
+}
+
+struct Filters {
+    user: Option<serenity::UserId>,
+    bots: bool,
+    attachments: bool,
+    links: bool,
+    embeds: bool,
+    invites: bool,
+    regex: Option<regex::Regex>,
+}
+
+impl Filters {
+    /// Every enabled filter has to match.
+    fn matches(&self, msg: &serenity::Message) -> bool {
+        let content = msg.content.to_lowercase();
+        self.user.is_none_or(|u| msg.author.id == u)
+            && (!self.bots || msg.author.bot)
+            && (!self.attachments || !msg.attachments.is_empty())
+            && (!self.links || content.contains("http://") || content.contains("https://"))
+            && (!self.embeds || !msg.embeds.is_empty())
+            && (!self.invites || ["discord.gg/", "discord.com/invite/", "discordapp.com/invite/"].iter().any(|p| content.contains(p)))
+            && self.regex.as_ref().is_none_or(|r| r.is_match(&msg.content))
+    }
+}
+
+/// A message reference in this channel: a link or a bare message id.
+fn parse_message_ref(input: &str, channel_id: serenity::ChannelId) -> Option<serenity::MessageId> {
+    if let Some((_, channel, message)) = parse_message_link(input) {
+        return (channel == channel_id.get()).then_some(serenity::MessageId::new(message));
+    }
+    let t = input.trim();
+    match t.parse::<u64>() {
+        Ok(id) if t.len() >= 15 => Some(serenity::MessageId::new(id)),
+        _ => None,
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn text_transcript(channel: &str, messages: &[serenity::Message]) -> String {
+    let mut out = format!("Transcript of {} deleted message(s) in #{}\n\n", messages.len(), channel);
+    for m in messages.iter().rev() {
+        out.push_str(&format!(
+            "[{}] {} ({}): {}\n",
+            m.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            m.author.name,
+            m.author.id,
+            m.content
+        ));
+        for a in &m.attachments {
+            out.push_str(&format!("    attachment: {}\n", a.url));
+        }
+        if !m.embeds.is_empty() {
+            out.push_str(&format!("    {} embed(s)\n", m.embeds.len()));
+        }
+    }
+    out
+}
+
+fn html_transcript(channel: &str, messages: &[serenity::Message]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>#{0}</title><style>\
+         body{{background:#313338;color:#dbdee1;font-family:sans-serif;padding:16px}}\
+         .m{{margin:8px 0}}.a{{font-weight:bold;color:#fff}}.t{{color:#949ba4;font-size:12px;margin-left:8px}}\
+         .c{{white-space:pre-wrap}}a{{color:#00a8fc}}\
+         </style></head><body><h2>#{0} - {1} deleted message(s)</h2>",
+        html_escape(channel),
+        messages.len()
+    );
+    for m in messages.iter().rev() {
+        out.push_str(&format!(
+            "<div class=\"m\"><span class=\"a\">{}</span><span class=\"t\">{} &middot; {}</span><div class=\"c\">{}</div>",
+            html_escape(&m.author.name),
+            m.author.id,
+            m.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            html_escape(&m.content)
+        ));
+        for a in &m.attachments {
+            out.push_str(&format!("<div><a href=\"{0}\">{1}</a></div>", html_escape(&a.url), html_escape(&a.filename)));
+        }
+        if !m.embeds.is_empty() {
+            out.push_str(&format!("<div class=\"t\">{} embed(s)</div>", m.embeds.len()));
+        }
+        out.push_str("</div>");
+    }
+    out.push_str("</body></html>");
+    out
+}
+
+/// Deletes recent messages by count, time window or message range, optionally filtered.
+#[allow(clippy::too_many_arguments)]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_MESSAGES",
+    required_bot_permissions = "MANAGE_MESSAGES | READ_MESSAGE_HISTORY"
+)]
+pub async fn purge(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Number of messages, time window (e.g. 30m) or message link/ID to purge up to"] target: String,
+    #[description = "Second message link/ID to purge between, or a user"] second: Option<String>,
+    #[description = "Only delete messages from this user"] user: Option<serenity::User>,
+    #[description = "Only messages from bots"] bots: Option<bool>,
+    #[description = "Only messages with attachments"] attachments: Option<bool>,
+    #[description = "Only messages with links"] links: Option<bool>,
+    #[description = "Only messages with embeds"] embeds: Option<bool>,
+    #[description = "Only messages with server invites"] invites: Option<bool>,
+    #[description = "Only messages whose content matches this regex"] regex: Option<String>,
+    #[description = "Channel for the transcript, instead of the bulk delete log channel"]
+    #[channel_types("Text")]
+    transcript_channel: Option<serenity::GuildChannel>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let channel_id = ctx.channel_id();
+    if transcript_channel.as_ref().is_some_and(|c| c.guild_id != guild_id) {
+        ctx.say("The transcript channel is not in this server.").await?;
+        return Ok(());
+    }
+
+    let mut user_id = user.map(|u| u.id);
+    let range = if let Some(first) = parse_message_ref(&target, channel_id) {
+        // The second argument is either the other end of the range or a user
+        match second.as_deref().and_then(|s| parse_message_ref(s, channel_id)) {
+            Some(other) if channel_id.message(ctx, other).await.is_ok() => Range::Between(first.min(other), first.max(other)),
+            _ => {
+                if let Some(id) = second.as_deref().and_then(|s| parse_user_ids(s).0.first().copied()) {
+                    user_id = user_id.or(Some(serenity::UserId::new(id)));
+                }
+                Range::Until(first)
+            }
+        }
+    } else {
+        if let Some(id) = second.as_deref().and_then(|s| parse_user_ids(s).0.first().copied()) {
+            user_id = user_id.or(Some(serenity::UserId::new(id)));
+        }
+        let t = target.trim();
+        match t.parse::<usize>() {
+            Ok(n) if (1..=MAX_COUNT).contains(&n) => Range::Count(n),
+            Ok(_) => {
+                ctx.say(format!("The count has to be between 1 and {}.", MAX_COUNT)).await?;
+                return Ok(());
+            }
+            // A bare number is a count, so only durations with a unit get here
+            Err(_) => match parse_duration(t) {
+                Some(d) => Range::Since(chrono::Utc::now().timestamp() - d.as_secs() as i64),
+                None => {
+                    ctx.say("Give a message count, a time window like `30m`, or a message link/ID from this channel.").await?;
+                    return Ok(());
+                }
+            },
+        }
+    };
+
+    let regex = match regex.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        Some(r) => match regex::RegexBuilder::new(r).size_limit(1 << 20).build() {
+            Ok(re) => Some(re),
+            Err(e) => {
+                ctx.say(format!("Invalid regex: {}", e)).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    let filters = Filters {
+        user: user_id,
+        bots: bots.unwrap_or(false),
+        attachments: attachments.unwrap_or(false),
+        links: links.unwrap_or(false),
+        embeds: embeds.unwrap_or(false),
+        invites: invites.unwrap_or(false),
+        regex,
+    };
+    ctx.defer_ephemeral().await?;
+
+    // Walk back from the newest message; the prefix invocation itself is left out and removed at the end
+    let invocation = match ctx {
+        poise::Context::Prefix(p) => Some(p.msg.id),
+        poise::Context::Application(_) => None,
+    };
+    let mut selected: Vec<serenity::Message> = Vec::new();
+    let mut before = invocation;
+    let mut scanned = 0;
+    'scan: while scanned < MAX_SCANNED {
+        let mut request = serenity::GetMessages::new().limit(100);
+        if let Some(b) = before {
+            request = request.before(b);
+        }
+        let page = channel_id.messages(ctx, request).await?;
+        if page.is_empty() {
+            break;
+        }
+        before = page.last().map(|m| m.id);
+        for msg in page {
+            scanned += 1;
+            match range {
+                Range::Count(n) if selected.len() >= n => break 'scan,
+                Range::Since(ts) if msg.timestamp.unix_timestamp() < ts => break 'scan,
+                Range::Until(id) if msg.id < id => break 'scan,
+                Range::Between(lo, _) if msg.id < lo => break 'scan,
+                Range::Between(_, hi) if msg.id > hi => continue,
+                _ => {}
+            }
+            if !msg.pinned && filters.matches(&msg) {
+                selected.push(msg);
+            }
+        }
+    }
+
+    if selected.is_empty() {
+        ctx.say("No messages matched.").await?;
+        return Ok(());
+    }
+
+    // The transcript is saved before anything is deleted
+    let channel_name = channel_id.name(ctx).await.unwrap_or_else(|_| channel_id.to_string());
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let files = vec![
+        serenity::CreateAttachment::bytes(
+            text_transcript(&channel_name, &selected).into_bytes(),
+            format!("purge-{}-{}.txt", channel_name, stamp),
+        ),
+        serenity::CreateAttachment::bytes(
+            html_transcript(&channel_name, &selected).into_bytes(),
+            format!("purge-{}-{}.html", channel_name, stamp),
+        ),
+    ];
+    let log_embed = serenity::CreateEmbed::default()
+        .title("Messages purged")
+        .description(format!("{} message(s) in <#{}> by <@{}>", selected.len(), channel_id, ctx.author().id))
+        .color(0xEF4444)
+        .timestamp(serenity::Timestamp::now());
+    let log_channel = match transcript_channel {
+        Some(c) => Some(c.id.get()),
+        None => LogStore::open(&ctx.data().db)?.channel_for(guild_id.get(), LogType::MessageBulkDelete)?,
+    };
+    let logged = match log_channel {
+        Some(id) => serenity::ChannelId::new(id)
+            .send_files(ctx, files.clone(), serenity::CreateMessage::new().embed(log_embed))
+            .await
+            .is_ok(),
+        None => false,
+    };
+    if log_channel.is_some() && !logged {
+        ctx.say("Couldn't save the transcript to the transcript channel, so nothing was deleted.").await?;
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let (recent, old): (Vec<_>, Vec<_>) = selected
+        .iter()
+        .partition(|m| now - m.timestamp.unix_timestamp() < BULK_MAX_AGE_SECS);
+    let mut deleted = 0;
+    let mut failed = 0;
+    for chunk in recent.chunks(100) {
+        let ids: Vec<serenity::MessageId> = chunk.iter().map(|m| m.id).collect();
+        // Bulk delete needs at least two messages
+        let result = if ids.len() == 1 {
+            channel_id.delete_message(ctx, ids[0]).await
+        } else {
+            channel_id.delete_messages(ctx, &ids).await
+        };
+        match result {
+            Ok(()) => deleted += ids.len(),
+            Err(_) => failed += ids.len(),
+        }
+    }
+    for msg in &old {
+        match channel_id.delete_message(ctx, msg.id).await {
+            Ok(()) => deleted += 1,
+            Err(_) => failed += 1,
+        }
+    }
+    if let Some(id) = invocation {
+        let _ = channel_id.delete_message(ctx, id).await;
+    }
+
+    let mut msg = format!("Deleted {} message(s).", deleted);
+    if !old.is_empty() {
+        msg.push_str(&format!(" {} were older than 14 days and deleted one by one.", old.len()));
+    }
+    if failed > 0 {
+        msg.push_str(&format!(" {} could not be deleted.", failed));
+    }
+    if scanned >= MAX_SCANNED {
+        msg.push_str(&format!(" Stopped after looking at {} messages.", MAX_SCANNED));
+    }
+    if log_channel.is_none() {
+        msg.push_str("\nNo transcript channel was given or set for purges, so the transcript is attached here.");
+    }
+    let mut reply = poise::CreateReply::default().content(truncate(&msg, 2000)).ephemeral(true);
+    if log_channel.is_none() {
+        for f in files {
+            reply = reply.attachment(f);
+        }
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
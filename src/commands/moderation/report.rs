@@ -1,2 +1,111 @@
 // Report a user to the server's moderation team.
-// report <user|message url> <reason> [comment]
\ No newline at end of file
+// report <user|message url> <reason> [comment]
+
+use super::reports::submit_report;
+use super::utils::{parse_message_link, parse_user_ids};
+use poise::serenity_prelude as serenity;
+
+#[derive(Debug, poise::Modal)]
+#[name = "Report message"]
+struct ReportModal {
+    #[name = "Reason"]
+    #[placeholder = "What is wrong with this message?"]
+    #[max_length = 200]
+    reason: String,
+    #[name = "Comment"]
+    #[placeholder = "Anything the moderators should know"]
+    #[paragraph]
+    #[max_length = 1000]
+    comment: Option<String>,
+}
+
+/// Report a user or a message to the server's moderation team.
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn report(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User mention/ID or message link"] target: String,
+    #[description = "What happened"] reason: String,
+    #[rest]
+    #[description = "Anything else the moderators should know"]
+    comment: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+
+    let (user, message) = if let Some((g, c, m)) = parse_message_link(&target) {
+        if g != guild_id.get() {
+            ctx.send(poise::CreateReply::default().content("That message is not from this server.").ephemeral(true)).await?;
+            return Ok(());
+        }
+        let Ok(msg) = serenity::ChannelId::new(c).message(ctx, serenity::MessageId::new(m)).await else {
+            ctx.send(poise::CreateReply::default().content("I couldn't find that message.").ephemeral(true)).await?;
+            return Ok(());
+        };
+        (msg.author.clone(), Some(msg))
+    } else {
+        let Some(uid) = parse_user_ids(&target).0.first().copied() else {
+            ctx.send(
+                poise::CreateReply::default()
+                    .content("Give a user mention, user ID or message link to report.")
+                    .ephemeral(true),
+            )
+            .await?;
+            return Ok(());
+        };
+        let Ok(user) = serenity::UserId::new(uid).to_user(ctx).await else {
+            ctx.send(poise::CreateReply::default().content("I couldn't find that user.").ephemeral(true)).await?;
+            return Ok(());
+        };
+        (user, None)
+    };
+
+    let result = submit_report(
+        ctx.serenity_context(),
+        &ctx.data().db,
+        guild_id.get(),
+        ctx.author(),
+        &user,
+        message.as_ref(),
+        reason,
+        comment,
+    )
+    .await?;
+    let reply = match result {
+        Ok(r) => format!("Report #{} was sent to the moderators. You'll get a message when it's resolved.", r.id),
+        Err(msg) => msg,
+    };
+    ctx.send(poise::CreateReply::default().content(reply).ephemeral(true)).await?;
+    // Prefix reports can't be ephemeral, so the invocation is removed to keep the reporter anonymous
+    if let poise::Context::Prefix(p) = ctx {
+        let _ = p.msg.delete(ctx).await;
+    }
+    Ok(())
+}
+
+/// Report this message to the server's moderation team.
+#[poise::command(context_menu_command = "Report message", guild_only)]
+pub async fn report_message(
+    ctx: poise::ApplicationContext<'_, crate::Data, crate::Error>,
+    #[description = "Message to report"] message: serenity::Message,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(form) = poise::execute_modal::<_, _, ReportModal>(ctx, None, Some(std::time::Duration::from_secs(600))).await? else {
+        return Ok(());
+    };
+    let result = submit_report(
+        ctx.serenity_context(),
+        &ctx.data().db,
+        guild_id.get(),
+        ctx.author(),
+        &message.author,
+        Some(&message),
+        form.reason,
+        form.comment,
+    )
+    .await?;
+    let reply = match result {
+        Ok(r) => format!("Report #{} was sent to the moderators. You'll get a message when it's resolved.", r.id),
+        Err(msg) => msg,
+    };
+    ctx.send(poise::CreateReply::default().content(reply).ephemeral(true)).await?;
+    Ok(())
+}
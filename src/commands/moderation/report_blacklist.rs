@@ -1,2 +1,81 @@
 // Block users from using the report command.
-// report_blacklist <show|add|remove> [user id]
\ No newline at end of file
+// report_blacklist <show|add|remove> [user id]
+
+use crate::data::reports::store::{BlacklistEntry, ReportStore};
+use poise::serenity_prelude as serenity;
+
+/// Block users from using the report command.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MODERATE_MEMBERS",
+    subcommands("show", "add", "remove"),
+    subcommand_required
+)]
+pub async fn report_blacklist(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Lists the users that can't send reports.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn show(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let entries = ReportStore::open(&ctx.data().db)?.blacklist(guild_id.get())?;
+    if entries.is_empty() {
+        ctx.say("Nobody is blacklisted from reporting.").await?;
+        return Ok(());
+    }
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|(uid, e)| format!("<@{}> (`{}`) - added by <@{}> <t:{}:R>", uid, uid, e.added_by, e.added_at))
+        .collect();
+    let pages = lines
+        .chunks(15)
+        .map(|chunk| {
+            serenity::CreateEmbed::default()
+                .title(format!("Report blacklist ({})", entries.len()))
+                .description(chunk.join("\n"))
+                .color(0x6B7280)
+        })
+        .collect();
+    crate::commands::pagination::paginate_embeds(ctx, pages).await?;
+    Ok(())
+}
+
+/// Stops a user from sending reports.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User to blacklist"] user: serenity::User,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = ReportStore::open(&ctx.data().db)?;
+    if store.is_blacklisted(guild_id.get(), user.id.get())? {
+        ctx.say(format!("<@{}> is already blacklisted.", user.id)).await?;
+        return Ok(());
+    }
+    store.blacklist_add(
+        guild_id.get(),
+        user.id.get(),
+        &BlacklistEntry { added_by: ctx.author().id.get(), added_at: chrono::Utc::now().timestamp() },
+    )?;
+    ctx.say(format!("<@{}> can no longer send reports.", user.id)).await?;
+    Ok(())
+}
+
+/// Lets a blacklisted user send reports again.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User to remove from the blacklist"] user: serenity::User,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let msg = if ReportStore::open(&ctx.data().db)?.blacklist_remove(guild_id.get(), user.id.get())? {
+        format!("<@{}> can send reports again.", user.id)
+    } else {
+        format!("<@{}> is not blacklisted.", user.id)
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// Sets up where user reports are posted.
+// reportconfig <channel|disable> [channel]
+
+use super::utils::{disable_posts, set_post_channel};
+use crate::data::reports::store::{ReportConfig, ReportStore};
+use poise::serenity_prelude as serenity;
+
+/// Sets up where user reports are posted.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "reportconfig",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("channel", "disable"),
+    subcommand_required
+)]
+pub async fn report_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Accepts reports and posts them in the given channel.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn channel(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel for reports, ideally only visible to moderators"] channel: serenity::GuildChannel,
+) -> Result<(), crate::Error> {
+    let store = ReportStore::open(&ctx.data().db)?;
+    set_post_channel(ctx, &channel, "Reports", |guild_id, channel_id| {
+        store.set_config(guild_id, Some(&ReportConfig { channel_id }))
+    })
+    .await
+}
+
+/// Stops accepting new reports. Open reports can still be resolved.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn disable(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let store = ReportStore::open(&ctx.data().db)?;
+    disable_posts(ctx, "reports", |guild_id| store.set_config(guild_id, None)).await
+}
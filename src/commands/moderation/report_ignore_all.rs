@@ -1,2 +1,30 @@
 // Ignore all open reports in your server at once without notifying reporters.
-// report_ignore_all
\ No newline at end of file
+// report_ignore_all
+
+use super::reports::refresh_post;
+use crate::data::reports::store::{ReportStatus, ReportStore};
+
+/// Ignore all open reports in your server at once without notifying reporters.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn report_ignore_all(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = ReportStore::open(&ctx.data().db)?;
+    let open = store.list_open(guild_id.get())?;
+    if open.is_empty() {
+        ctx.say("There are no open reports.").await?;
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    let now = chrono::Utc::now().timestamp();
+    let count = open.len();
+    for mut report in open {
+        report.status = ReportStatus::Ignored;
+        report.resolved_by = Some(ctx.author().id.get());
+        report.resolved_at = Some(now);
+        store.save(&report)?;
+        refresh_post(ctx.serenity_context(), &ctx.data().db, &report).await;
+    }
+    ctx.say(format!("Ignored {} open report(s).", count)).await?;
+    Ok(())
+}
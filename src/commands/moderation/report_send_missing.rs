@@ -1,2 +1,36 @@
 // Report info messages are not supposed to be deleted. Execute this command to bring back deleted report messages.
-// report_send_missing
\ No newline at end of file
+// report_send_missing
+
+use super::reports::post_report;
+use super::utils::repost_missing;
+use crate::data::reports::store::ReportStore;
+
+/// Brings back deleted report messages of open reports.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn report_send_missing(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = ReportStore::open(&ctx.data().db)?;
+    let Some(config) = store.config(guild_id.get())? else {
+        ctx.say("Reports are not set up in this server. See `reportconfig channel`.").await?;
+        return Ok(());
+    };
+    ctx.defer().await?;
+
+    let db = &ctx.data().db;
+    let (serenity_ctx, channel_id, store) = (ctx.serenity_context(), config.channel_id, &store);
+    let msg = repost_missing(
+        serenity_ctx,
+        store.list_open(guild_id.get())?,
+        "report",
+        |r| (r.id, r.channel_id, r.message_id),
+        |mut report| {
+            Box::pin(async move {
+                post_report(serenity_ctx, db, &mut report, channel_id).await?;
+                store.save(&report)
+            })
+        },
+    )
+    .await;
+    ctx.say(msg).await?;
+    Ok(())
+}
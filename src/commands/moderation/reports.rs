@@ -0,0 +1,457 @@
+// Shared parts of the report queue: submitting a report, the tracked report message in the mod channel
+// and its Claim/Action/Ignore buttons, and telling the reporter once a report is resolved.
+//
+// Buttons, menus and modals use custom ids of the form `report:<action>:<guild id>:<report id>[:<extra>]`,
+// so they keep working after a restart and are routed here from the event handler.
+
+use super::cases::truncate;
+use super::mass::{action_permission, apply, TargetOutcome, MAX_MUTE_SECS};
+use super::usernotes::notes_summary;
+use super::utils::{audit_reason, parse_duration, parse_user_ids, Hierarchy};
+use super::warnings::{escalate, warn_expiry};
+use crate::commands::general::features::{refusal, Feature};
+use crate::data::cases::store::{CaseAction, CaseStore, NewCase};
+use crate::handlers::component_router::{component_id, ComponentId};
+use crate::data::reports::store::{Report, ReportStatus, ReportStore, ReportedMessage};
+use poise::serenity_prelude as serenity;
+use poise::Modal;
+
+pub(crate) const PREFIX: &str = "report";
+
+#[derive(Debug, poise::Modal)]
+#[name = "Moderation action"]
+struct ActionModal {
+    #[name = "User ID"]
+    #[max_length = 25]
+    user: String,
+    #[name = "Reason"]
+    #[placeholder = "Shown in the case and the audit log"]
+    #[paragraph]
+    #[max_length = 500]
+    reason: Option<String>,
+    #[name = "Duration"]
+    #[placeholder = "e.g. 30m, 12h, 7d (empty = permanent, ignored for warns and kicks)"]
+    #[max_length = 20]
+    duration: Option<String>,
+}
+
+struct Route {
+    action: String,
+    guild_id: u64,
+    report_id: u64,
+    extra: Option<String>,
+}
+
+impl Route {
+    fn parse(custom_id: &str) -> Option<Self> {
+        let id = ComponentId::parse(custom_id).filter(|id| id.prefix == PREFIX)?;
+        Some(Self {
+            guild_id: id.arg(0)?,
+            report_id: id.arg(1)?,
+            extra: id.args.get(2).cloned(),
+            action: id.action,
+        })
+    }
+}
+
+fn custom_id(action: &str, report: &Report) -> String {
+    component_id(PREFIX, action, [report.guild_id, report.id])
+}
+
+fn status_color(status: ReportStatus) -> u32 {
+    match status {
+        ReportStatus::Open => 0xEAB308,
+        ReportStatus::Claimed => 0x3B82F6,
+        ReportStatus::Actioned => 0x22C55E,
+        ReportStatus::Ignored => 0x6B7280,
+    }
+}
+
+fn status_text(report: &Report) -> String {
+    let who = |m: Option<u64>| m.map(|m| format!("<@{}>", m)).unwrap_or_else(|| "unknown".to_string());
+    match report.status {
+        ReportStatus::Open => "Open".to_string(),
+        ReportStatus::Claimed => format!("Claimed by {}", who(report.claimed_by)),
+        ReportStatus::Actioned => format!(
+            "Actioned by {}: {}",
+            who(report.resolved_by),
+            report.resolution.as_deref().unwrap_or("action taken")
+        ),
+        ReportStatus::Ignored => format!("Ignored by {}", who(report.resolved_by)),
+    }
+}
+
+pub(crate) fn report_embed(db: &sled::Db, report: &Report) -> serenity::CreateEmbed {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(format!("Report #{}", report.id))
+        .color(status_color(report.status))
+        .field(
+            "Reported user",
+            format!("<@{}> ({})\n`{}`", report.target_id, report.target_name, report.target_id),
+            true,
+        )
+        .field("Reporter", format!("<@{}>", report.reporter_id), true)
+        .field("Reported", format!("<t:{}:f>", report.created_at), true)
+        .field("Reason", truncate(&report.reason, 1024), false);
+    if let Some(comment) = &report.comment {
+        embed = embed.field("Comment", truncate(comment, 1024), false);
+    }
+    if let Some(m) = &report.message {
+        let mut text = format!(
+            "[Jump to message](https://discord.com/channels/{}/{}/{}) in <#{}>",
+            report.guild_id, m.channel_id, m.message_id, m.channel_id
+        );
+        if !m.content.is_empty() {
+            text.push_str(&format!("\n>>> {}", m.content));
+        }
+        for a in &m.attachments {
+            text.push_str(&format!("\n{}", a));
+        }
+        embed = embed.field("Message", truncate(&text, 1024), false);
+    }
+    if let Some(summary) = notes_summary(db, report.guild_id, report.target_id) {
+        embed = embed.field("User notes", summary, false);
+    }
+    embed.field("Status", status_text(report), false)
+}
+
+fn report_buttons(report: &Report) -> Vec<serenity::CreateActionRow> {
+    let resolved = !report.status.is_open();
+    vec![serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(custom_id("claim", report))
+            .label(if report.status == ReportStatus::Claimed { "Claimed" } else { "Claim" })
+            .style(serenity::ButtonStyle::Primary)
+            .disabled(resolved || report.status == ReportStatus::Claimed),
+        serenity::CreateButton::new(custom_id("act", report))
+            .label("Action")
+            .style(serenity::ButtonStyle::Danger)
+            .disabled(resolved),
+        serenity::CreateButton::new(custom_id("ignore", report))
+            .label("Ignore")
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(resolved),
+    ])]
+}
+
+/// Posts the tracked message for a report and stores where it went. The caller saves the report.
+pub(crate) async fn post_report(ctx: &serenity::Context, db: &sled::Db, report: &mut Report, channel_id: u64) -> Result<(), crate::Error> {
+    let msg = serenity::ChannelId::new(channel_id)
+        .send_message(
+            ctx,
+            serenity::CreateMessage::new().embed(report_embed(db, report)).components(report_buttons(report)),
+        )
+        .await?;
+    report.channel_id = channel_id;
+    report.message_id = msg.id.get();
+    Ok(())
+}
+
+/// Updates the tracked message so it reflects the report's current state.
+pub(crate) async fn refresh_post(ctx: &serenity::Context, db: &sled::Db, report: &Report) {
+    let _ = serenity::ChannelId::new(report.channel_id)
+        .edit_message(
+            ctx,
+            serenity::MessageId::new(report.message_id),
+            serenity::EditMessage::new().embed(report_embed(db, report)).components(report_buttons(report)),
+        )
+        .await;
+}
+
+/// Creates a report and posts it in the guild's report channel. On refusal the error is a message for the reporter.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn submit_report(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: u64,
+    reporter: &serenity::User,
+    target: &serenity::User,
+    message: Option<&serenity::Message>,
+    reason: String,
+    comment: Option<String>,
+) -> Result<Result<Report, String>, crate::Error> {
+    let store = ReportStore::open(db)?;
+    let Some(config) = store.config(guild_id)? else {
+        return Ok(Err("Reports are not set up in this server.".to_string()));
+    };
+    if store.is_blacklisted(guild_id, reporter.id.get())? {
+        return Ok(Err("You are not allowed to send reports in this server.".to_string()));
+    }
+    if target.id == reporter.id {
+        return Ok(Err("You can't report yourself.".to_string()));
+    }
+    let message_id = message.map(|m| m.id.get());
+    let duplicate = store.list_open(guild_id)?.into_iter().any(|r| {
+        r.reporter_id == reporter.id.get() && r.target_id == target.id.get() && r.message.as_ref().map(|m| m.message_id) == message_id
+    });
+    if duplicate {
+        return Ok(Err("You already reported this and the report is still open.".to_string()));
+    }
+
+    let mut report = Report {
+        id: store.next_id(guild_id)?,
+        guild_id,
+        reporter_id: reporter.id.get(),
+        target_id: target.id.get(),
+        target_name: target.name.clone(),
+        message: message.map(|m| ReportedMessage {
+            channel_id: m.channel_id.get(),
+            message_id: m.id.get(),
+            content: truncate(&m.content, 800),
+            attachments: m.attachments.iter().map(|a| a.url.clone()).collect(),
+        }),
+        reason,
+        comment: comment.filter(|c| !c.trim().is_empty()),
+        status: ReportStatus::Open,
+        created_at: chrono::Utc::now().timestamp(),
+        channel_id: config.channel_id,
+        message_id: 0,
+        claimed_by: None,
+        resolved_by: None,
+        resolved_at: None,
+        resolution: None,
+        case_id: None,
+    };
+    if let Err(e) = post_report(ctx, db, &mut report, config.channel_id).await {
+        eprintln!("failed to post report in guild {}: {}", guild_id, e);
+        return Ok(Err("Your report couldn't be delivered to the moderators. Please try again later.".to_string()));
+    }
+    store.save(&report)?;
+    Ok(Ok(report))
+}
+
+/// Tells the reporter their report was handled, without saying what was done.
+async fn notify_reporter(ctx: &serenity::Context, report: &Report) {
+    let guild = serenity::GuildId::new(report.guild_id).name(&ctx.cache).unwrap_or_else(|| "the server".to_string());
+    let outcome = match report.status {
+        ReportStatus::Actioned => "The moderators took action. Thanks for helping keep the server safe.",
+        _ => "The moderators reviewed it and decided no action was needed.",
+    };
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Your report #{} in {} was resolved", report.id, guild))
+        .description(outcome)
+        .color(status_color(report.status));
+    let _ = serenity::UserId::new(report.reporter_id)
+        .direct_message(ctx, serenity::CreateMessage::new().embed(embed))
+        .await;
+}
+
+fn ephemeral(text: impl Into<String>) -> serenity::CreateInteractionResponse {
+    serenity::CreateInteractionResponse::Message(
+        serenity::CreateInteractionResponseMessage::new().content(text).ephemeral(true),
+    )
+}
+
+fn has_permission(member: Option<&serenity::Member>, check: fn(serenity::Permissions) -> bool) -> bool {
+    member.and_then(|m| m.permissions).is_some_and(check)
+}
+
+/// Loads the open report a button, menu or modal refers to. On failure the error is a message for the moderator.
+fn load_open(
+    store: &ReportStore,
+    route: &Route,
+    guild_id: Option<serenity::GuildId>,
+    member: Option<&serenity::Member>,
+) -> Result<Result<Report, &'static str>, crate::Error> {
+    if guild_id.map(|g| g.get()) != Some(route.guild_id) || !has_permission(member, serenity::Permissions::moderate_members) {
+        return Ok(Err("You need the Timeout Members permission to handle reports."));
+    }
+    let Some(report) = store.get(route.guild_id, route.report_id)? else {
+        return Ok(Err("This report no longer exists."));
+    };
+    if !report.status.is_open() {
+        return Ok(Err("This report was already resolved."));
+    }
+    Ok(Ok(report))
+}
+
+/// Handles report buttons, menus and modals. Other interactions are ignored.
+pub async fn handle_interaction(ctx: &serenity::Context, interaction: &serenity::Interaction, data: &crate::Data) -> Result<(), crate::Error> {
+    match interaction {
+        serenity::Interaction::Component(mci) => {
+            let Some(route) = Route::parse(&mci.data.custom_id) else { return Ok(()) };
+            if let Some(msg) = refusal(&data.db, route.guild_id, Feature::Moderation) {
+                mci.create_response(ctx, ephemeral(msg)).await?;
+                return Ok(());
+            }
+            handle_component(ctx, mci, route, data).await
+        }
+        serenity::Interaction::Modal(modal) => {
+            let Some(route) = Route::parse(&modal.data.custom_id) else { return Ok(()) };
+            if let Some(msg) = refusal(&data.db, route.guild_id, Feature::Moderation) {
+                modal.create_response(ctx, ephemeral(msg)).await?;
+                return Ok(());
+            }
+            handle_modal(ctx, modal, route, data).await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn handle_component(ctx: &serenity::Context, mci: &serenity::ComponentInteraction, route: Route, data: &crate::Data) -> Result<(), crate::Error> {
+    let store = ReportStore::open(&data.db)?;
+    let mut report = match load_open(&store, &route, mci.guild_id, mci.member.as_ref())? {
+        Ok(r) => r,
+        Err(msg) => {
+            mci.create_response(ctx, ephemeral(msg)).await?;
+            return Ok(());
+        }
+    };
+
+    match route.action.as_str() {
+        "claim" => {
+            if let Some(other) = report.claimed_by.filter(|c| *c != mci.user.id.get()) {
+                mci.create_response(ctx, ephemeral(format!("<@{}> already claimed this report.", other))).await?;
+                return Ok(());
+            }
+            report.status = ReportStatus::Claimed;
+            report.claimed_by = Some(mci.user.id.get());
+            store.save(&report)?;
+            mci.create_response(
+                ctx,
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(report_embed(&data.db, &report))
+                        .components(report_buttons(&report)),
+                ),
+            )
+            .await?;
+        }
+        "act" => {
+            let options = [CaseAction::Warn, CaseAction::Mute, CaseAction::Kick, CaseAction::Ban]
+                .iter()
+                .map(|a| serenity::CreateSelectMenuOption::new(a.as_str(), a.as_str()))
+                .collect();
+            let menu = serenity::CreateSelectMenu::new(custom_id("pick", &report), serenity::CreateSelectMenuKind::String { options })
+                .placeholder("Choose an action");
+            mci.create_response(
+                ctx,
+                serenity::CreateInteractionResponse::Message(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .content(format!("Action against <@{}> for report #{}:", report.target_id, report.id))
+                        .components(vec![serenity::CreateActionRow::SelectMenu(menu)])
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        }
+        "pick" => {
+            let serenity::ComponentInteractionDataKind::StringSelect { values } = &mci.data.kind else { return Ok(()) };
+            let Some(action) = values.first().and_then(|v| CaseAction::parse(v)) else { return Ok(()) };
+            let defaults = ActionModal {
+                user: report.target_id.to_string(),
+                reason: Some(truncate(&format!("Report #{}: {}", report.id, report.reason), 500)),
+                duration: None,
+            };
+            let id = format!("{}:{}", custom_id("apply", &report), action.as_str());
+            mci.create_response(ctx, ActionModal::create(Some(defaults), id)).await?;
+        }
+        "ignore" => {
+            report.status = ReportStatus::Ignored;
+            report.resolved_by = Some(mci.user.id.get());
+            report.resolved_at = Some(chrono::Utc::now().timestamp());
+            store.save(&report)?;
+            mci.create_response(
+                ctx,
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(report_embed(&data.db, &report))
+                        .components(report_buttons(&report)),
+                ),
+            )
+            .await?;
+            notify_reporter(ctx, &report).await;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn handle_modal(ctx: &serenity::Context, modal: &serenity::ModalInteraction, route: Route, data: &crate::Data) -> Result<(), crate::Error> {
+    if route.action != "apply" {
+        return Ok(());
+    }
+    let Some(action) = route.extra.as_deref().and_then(CaseAction::parse) else { return Ok(()) };
+    let store = ReportStore::open(&data.db)?;
+    let mut report = match load_open(&store, &route, modal.guild_id, modal.member.as_ref())? {
+        Ok(r) => r,
+        Err(msg) => {
+            modal.create_response(ctx, ephemeral(msg)).await?;
+            return Ok(());
+        }
+    };
+    if !has_permission(modal.member.as_ref(), action_permission(action)) {
+        modal
+            .create_response(ctx, ephemeral(format!("You don't have permission to {} members.", action.as_str())))
+            .await?;
+        return Ok(());
+    }
+    let Ok(form) = ActionModal::parse(modal.data.clone()) else { return Ok(()) };
+    let Some(uid) = parse_user_ids(&form.user).0.first().copied() else {
+        modal.create_response(ctx, ephemeral(format!("`{}` is not a valid user ID.", form.user))).await?;
+        return Ok(());
+    };
+    let duration_secs = match form.duration.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(raw) if matches!(action, CaseAction::Mute | CaseAction::Ban) => match parse_duration(raw) {
+            Some(d) => Some(d.as_secs()),
+            None => {
+                modal
+                    .create_response(ctx, ephemeral(format!("Invalid duration `{}`. Use e.g. 30m, 12h, 7d.", raw)))
+                    .await?;
+                return Ok(());
+            }
+        },
+        _ => None,
+    };
+    // Timeouts always need an end, and Discord caps them at 28 days
+    let duration_secs = match action {
+        CaseAction::Mute => Some(duration_secs.unwrap_or(MAX_MUTE_SECS).min(MAX_MUTE_SECS)),
+        _ => duration_secs,
+    };
+    modal.defer_ephemeral(ctx).await?;
+
+    let Some(guild_id) = modal.guild_id else { return Ok(()) };
+    let Some(moderator) = modal.member.as_ref() else { return Ok(()) };
+    let reason = form.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let hierarchy = Hierarchy::load(ctx, guild_id).await?;
+    let bot_id = ctx.cache.current_user().id;
+    let bot = guild_id.member(ctx, bot_id).await?;
+    let audit = audit_reason(&modal.user, reason.as_deref());
+    let until = duration_secs.map(|s| chrono::Utc::now().timestamp() + s as i64);
+    let until = if action == CaseAction::Warn { warn_expiry(&data.db, guild_id.get(), until)? } else { until };
+
+    let outcome = apply(ctx, &data.db, guild_id, action, uid, &hierarchy, moderator, &bot, &audit, until).await;
+    let text = match outcome {
+        TargetOutcome::Success => {
+            let case = CaseStore::open(&data.db)?.create(
+                guild_id.get(),
+                NewCase {
+                    action,
+                    targets: vec![uid],
+                    moderator_id: modal.user.id.get(),
+                    reason,
+                    expires_at: until,
+                },
+            )?;
+            report.status = ReportStatus::Actioned;
+            report.resolved_by = Some(modal.user.id.get());
+            report.resolved_at = Some(chrono::Utc::now().timestamp());
+            report.resolution = Some(format!("{} (case #{})", action.as_str(), case.id));
+            report.case_id = Some(case.id);
+            store.save(&report)?;
+            refresh_post(ctx, &data.db, &report).await;
+            notify_reporter(ctx, &report).await;
+            let mut text = format!("<@{}> was {} (case #{}). Report #{} is resolved.", uid, action.past_tense(), case.id, report.id);
+            if action == CaseAction::Warn
+                && let Some(line) = escalate(ctx, &data.db, guild_id, uid, &hierarchy, moderator, &bot).await?
+            {
+                text.push_str(&format!("\n{}", line));
+            }
+            text
+        }
+        other => format!("<@{}>: {}. The report stays open.", uid, other.label(action)),
+    };
+    modal
+        .create_followup(ctx, serenity::CreateInteractionResponseFollowup::new().content(text).ephemeral(true))
+        .await?;
+    Ok(())
+}
@@ -1,2 +1,43 @@
 // Restore submission info message in case they were deleted.
-// restore_appeal_messages
\ No newline at end of file
+// restore_appeal_messages
+
+use super::appeals::post_appeal;
+use super::utils::repost_missing;
+use crate::data::appeals::store::{AppealStatus, AppealStore};
+
+/// Restore submission info message in case they were deleted.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "restore_appeal_messages",
+    guild_only,
+    required_permissions = "BAN_MEMBERS"
+)]
+pub async fn restore_appeal_message(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = AppealStore::open(&ctx.data().db)?;
+    let Some(config) = store.config(guild_id.get())? else {
+        ctx.say("Appeals are not set up in this server. See `appealconfig channel`.").await?;
+        return Ok(());
+    };
+    ctx.defer().await?;
+
+    let open = store.list(guild_id.get())?.into_iter().filter(|a| a.status == AppealStatus::Open).collect();
+    let (serenity_ctx, channel_id, store) = (ctx.serenity_context(), config.channel_id, &store);
+    let msg = repost_missing(
+        serenity_ctx,
+        open,
+        "submission",
+        |a| (a.id, a.channel_id, a.message_id),
+        // Reposted into the current appeal channel; an existing thread is kept
+        |mut appeal| {
+            Box::pin(async move {
+                post_appeal(serenity_ctx, &mut appeal, channel_id).await?;
+                store.save(&appeal)
+            })
+        },
+    )
+    .await;
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,2 +1,41 @@
 // Send a message to a user who submitted an appeal.
-// send_to_appeal [message] [anonymous]
\ No newline at end of file
+// send_to_appeal [message] [anonymous]
+
+use super::appeals::message_user;
+use crate::data::appeals::store::{AppealStatus, AppealStore};
+
+/// Send a message to a user who submitted an appeal. Use it in the appeal's thread.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "BAN_MEMBERS")]
+pub async fn send_to_appeal(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[flag]
+    #[description = "Hide your name from the user"]
+    anonymous: bool,
+    #[rest]
+    #[description = "Message for the user"]
+    message: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(message) = message.filter(|m| !m.trim().is_empty()) else {
+        ctx.say("Give a message to send, e.g. `send_to_appeal anonymous We are looking into it.`").await?;
+        return Ok(());
+    };
+    let store = AppealStore::open(&ctx.data().db)?;
+    let Some(appeal) = store.find_by_thread(guild_id.get(), ctx.channel_id().get())? else {
+        ctx.say("Use this command in the thread of an appeal.").await?;
+        return Ok(());
+    };
+    if appeal.status != AppealStatus::Open {
+        ctx.say(format!("Appeal #{} was already decided.", appeal.id)).await?;
+        return Ok(());
+    }
+
+    let sent = message_user(ctx.serenity_context(), &appeal, ctx.author(), &message, anonymous).await;
+    let reply = if sent {
+        "Message sent.".to_string()
+    } else {
+        format!("<@{}> can't be messaged (DMs closed or no shared server).", appeal.user_id)
+    };
+    ctx.send(poise::CreateReply::default().content(reply).ephemeral(true)).await?;
+    Ok(())
+}
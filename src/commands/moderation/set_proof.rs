@@ -1,2 +1,73 @@
-// Sets the proof for a case.
-// setproof [case id] <proof>
\ No newline at end of file
+// setproof [case id] <proof>
+
+use super::cases::find_case;
+use crate::data::cases::store::{CaseEventKind, CaseStore};
+use poise::serenity_prelude as serenity;
+
+const MAX_PROOF: usize = 20;
+
+/// Adds proof (attachments or message links) to a case.
+#[poise::command(slash_command, prefix_command, rename = "setproof", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn set_proof(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Case ID (defaults to the newest case)"] case_id: Option<u64>,
+    #[description = "Screenshot or file to attach as proof"] attachment: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Message links or URLs, separated by spaces"]
+    proof: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+
+    let mut items: Vec<String> = Vec::new();
+    let mut rejected: Vec<String> = Vec::new();
+    for token in proof.as_deref().unwrap_or("").split_whitespace() {
+        let t = token.trim_matches(|c| c == '<' || c == '>');
+        if t.starts_with("https://") || t.starts_with("http://") {
+            items.push(t.to_string());
+        } else {
+            rejected.push(token.to_string());
+        }
+    }
+    let mut files: Vec<&serenity::Attachment> = attachment.iter().collect();
+    // Attachments sent with a prefix command beyond the first one are picked up as well
+    if let poise::Context::Prefix(p) = ctx {
+        files.extend(p.msg.attachments.iter().skip(1));
+    }
+
+    if items.is_empty() && files.is_empty() {
+        ctx.say("No proof given. Attach a file or add message links / URLs.").await?;
+        return Ok(());
+    }
+
+    let store = CaseStore::open(&ctx.data().db)?;
+    let Some(mut case) = find_case(&store, guild_id.get(), case_id)? else {
+        ctx.say("Case not found.").await?;
+        return Ok(());
+    };
+    let added = items.len() + usize::from(!files.is_empty());
+    if case.proof.len() + added > MAX_PROOF {
+        ctx.say(format!("A case can hold at most {} proof entries (case #{} has {}).", MAX_PROOF, case.id, case.proof.len())).await?;
+        return Ok(());
+    }
+
+    let mut msg = format!("Added {} proof entr{} to case #{}.", added, if added == 1 { "y" } else { "ies" }, case.id);
+    if !rejected.is_empty() {
+        msg.push_str(&format!(" Ignored (not a link): {}", rejected.join(" ")));
+    }
+    let mut reply = poise::CreateReply::default().content(msg);
+    for file in &files {
+        reply = reply.attachment(serenity::CreateAttachment::bytes(file.download().await?, file.filename.clone()));
+    }
+    let handle = ctx.send(reply).await?;
+    // Attachment URLs expire, so the files are uploaded again with the reply, whose link is kept
+    if !files.is_empty() {
+        items.insert(0, handle.message().await?.link());
+    }
+
+    for item in &items {
+        case.record(Some(ctx.author().id.get()), CaseEventKind::ProofAdded, Some(item.clone()));
+    }
+    case.proof.extend(items.iter().cloned());
+    store.save(&case)?;
+    Ok(())
+}
@@ -1,2 +1,54 @@
 // Sets the slow mode for a channel.
-// setslowmode [channel[ <time>
\ No newline at end of file
+// setslowmode [channel] <time>
+
+use super::slowmode::{format_delay, parse_delay, set_delay};
+use super::utils::audit_reason;
+use crate::data::slowmode::store::SlowModeStore;
+use poise::serenity_prelude as serenity;
+
+/// Sets the slow mode of a channel (e.g. 10, 30s, 5m, off). Shows the current one without a time.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "setslowmode",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS"
+)]
+pub async fn set_slow_mode(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel (this one if omitted)"] channel: Option<serenity::GuildChannel>,
+    #[description = "Delay between messages, up to 6h (off to disable)"] time: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let channel = match channel {
+        Some(c) => c,
+        None => match ctx.guild_channel().await {
+            Some(c) => c,
+            None => {
+                ctx.say("Run this in a server channel or name one.").await?;
+                return Ok(());
+            }
+        },
+    };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel isn't in this server.").await?;
+        return Ok(());
+    }
+    let old = channel.rate_limit_per_user.unwrap_or(0);
+    let Some(time) = time else {
+        ctx.say(format!("Slow mode in <#{}> is **{}**.", channel.id, format_delay(old))).await?;
+        return Ok(());
+    };
+    let Some(new) = parse_delay(&time) else {
+        ctx.say(format!("Invalid time `{}`. Use e.g. 10, 30s, 5m or off (max 6h).", time)).await?;
+        return Ok(());
+    };
+
+    let cause = audit_reason(ctx.author(), Some("Slow mode set by hand"));
+    set_delay(ctx.serenity_context(), &ctx.data().db, guild_id.get(), channel.id.get(), old, new, &cause, Some(ctx.author().id.get())).await?;
+    // A manual change replaces whatever auto slow mode or a running window had applied
+    SlowModeStore::open(&ctx.data().db)?.clear_raised(guild_id.get(), channel.id.get())?;
+    ctx.say(format!("Slow mode in <#{}>: {} -> **{}**.", channel.id, format_delay(old), format_delay(new))).await?;
+    Ok(())
+}
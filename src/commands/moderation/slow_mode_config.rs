@@ -0,0 +1,240 @@
+// Configures auto slow mode for message bursts and scheduled slow mode windows.
+// slowmodeconfig auto <channel> <msgs/sec> [max delay] [decay]
+// slowmodeconfig disable <channel>
+// slowmodeconfig schedule <channel> <delay> <starts in> <length> [label]
+// slowmodeconfig unschedule <id>
+// slowmodeconfig list
+
+use super::slowmode::{format_delay, parse_delay, MAX_DELAY};
+use super::utils::{format_duration, parse_duration};
+use crate::data::slowmode::store::{AutoSlowMode, SlowModeSource, SlowModeStore, SlowModeWindow};
+use poise::serenity_prelude as serenity;
+
+const DEFAULT_MAX_DELAY: u16 = 60;
+const DEFAULT_DECAY_SECS: u32 = 120;
+// Windows can be scheduled up to a year ahead and last up to a week
+const MAX_SCHEDULE_AHEAD_SECS: u64 = 365 * 86_400;
+const MAX_WINDOW_SECS: u64 = 7 * 86_400;
+
+/// Configures auto slow mode and scheduled slow mode windows.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "slowmodeconfig",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    subcommands("auto", "disable", "schedule", "unschedule", "list"),
+    subcommand_required
+)]
+pub async fn slow_mode_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Raises a channel's slow mode when messages per second go over a threshold.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn auto(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to watch"] channel: serenity::GuildChannel,
+    #[description = "Messages per second that trigger a raise, e.g. 1.5"] threshold: f64,
+    #[description = "Highest delay to raise to (default 60s)"] max_delay: Option<String>,
+    #[description = "Calm time before stepping down one level (default 2m)"] decay: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel isn't in this server.").await?;
+        return Ok(());
+    }
+    if !(threshold > 0.0 && threshold <= 50.0) {
+        ctx.say("The threshold must be above 0 and at most 50 messages per second.").await?;
+        return Ok(());
+    }
+    let max_delay = match max_delay.as_deref().map(parse_delay) {
+        None => DEFAULT_MAX_DELAY,
+        Some(Some(d)) if d > 0 => d,
+        Some(_) => {
+            ctx.say(format!("Invalid max delay. Use e.g. 30s or 5m, up to {}.", format_delay(MAX_DELAY))).await?;
+            return Ok(());
+        }
+    };
+    let decay_secs = match decay.as_deref().map(parse_duration) {
+        None => DEFAULT_DECAY_SECS,
+        Some(Some(d)) if d.as_secs() <= 86_400 => d.as_secs() as u32,
+        Some(_) => {
+            ctx.say("Invalid decay time. Use e.g. 2m or 10m, up to 1d.").await?;
+            return Ok(());
+        }
+    };
+
+    let config = AutoSlowMode { threshold, max_delay, decay_secs };
+    SlowModeStore::open(&ctx.data().db)?.set_auto(guild_id.get(), channel.id.get(), Some(&config))?;
+    ctx.say(format!(
+        "Auto slow mode on for <#{}>: above {} msgs/s the delay goes up one step (up to {}), and back down after {} of calm.",
+        channel.id,
+        threshold,
+        format_delay(max_delay),
+        format_duration(decay_secs as u64)
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Turns auto slow mode off for a channel. A raised delay steps back on the next check.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn disable(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel"] channel: serenity::ChannelId,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = SlowModeStore::open(&ctx.data().db)?;
+    if store.auto(guild_id.get(), channel.get())?.is_none() {
+        ctx.say(format!("Auto slow mode isn't on for <#{}>.", channel)).await?;
+        return Ok(());
+    }
+    store.set_auto(guild_id.get(), channel.get(), None)?;
+    ctx.say(format!("Auto slow mode off for <#{}>.", channel)).await?;
+    Ok(())
+}
+
+/// Schedules a slow mode window, e.g. for an event.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn schedule(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel"] channel: serenity::GuildChannel,
+    #[description = "Delay during the window, e.g. 30s"] delay: String,
+    #[description = "Starts in, e.g. 0m, 2h, 1d"] starts_in: String,
+    #[description = "Length, e.g. 3h"] length: String,
+    #[rest]
+    #[description = "Label, e.g. the event name"]
+    label: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel isn't in this server.").await?;
+        return Ok(());
+    }
+    let Some(delay) = parse_delay(&delay).filter(|d| *d > 0) else {
+        ctx.say(format!("Invalid delay `{}`. Use e.g. 10, 30s or 5m (max 6h).", delay)).await?;
+        return Ok(());
+    };
+    // "0" means right away, which parse_duration doesn't accept
+    let starts_in = match starts_in.trim() {
+        "0" | "now" => Some(0),
+        s => parse_duration(s).map(|d| d.as_secs()),
+    };
+    let Some(starts_in) = starts_in.filter(|s| *s <= MAX_SCHEDULE_AHEAD_SECS) else {
+        ctx.say("Invalid start. Use e.g. now, 30m, 2h or 1d (up to a year ahead).").await?;
+        return Ok(());
+    };
+    let Some(length) = parse_duration(&length).map(|d| d.as_secs()).filter(|s| *s <= MAX_WINDOW_SECS) else {
+        ctx.say("Invalid length. Use e.g. 30m or 3h (up to a week).").await?;
+        return Ok(());
+    };
+
+    let store = SlowModeStore::open(&ctx.data().db)?;
+    let now = chrono::Utc::now().timestamp();
+    let window = SlowModeWindow {
+        id: store.next_window_id(guild_id.get())?,
+        guild_id: guild_id.get(),
+        channel_id: channel.id.get(),
+        delay,
+        starts_at: now + starts_in as i64,
+        ends_at: now + (starts_in + length) as i64,
+        created_by: ctx.author().id.get(),
+        label: label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()),
+        started: false,
+    };
+    store.save_window(&window)?;
+    ctx.say(format!(
+        "Scheduled window #{}: <#{}> gets a {} slow mode from <t:{}:f> to <t:{}:f>.",
+        window.id,
+        channel.id,
+        format_delay(delay),
+        window.starts_at,
+        window.ends_at
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Deletes a scheduled window. A running window ends right away.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn unschedule(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Window ID"] id: u64,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = SlowModeStore::open(&ctx.data().db)?;
+    let Some(window) = store.window(guild_id.get(), id)? else {
+        ctx.say(format!("There's no slow mode window #{}.", id)).await?;
+        return Ok(());
+    };
+    if window.started {
+        let cause = format!("Scheduled slow mode window #{} removed by {}", id, ctx.author().name);
+        crate::tasks::slow_mode::end_window(ctx.serenity_context(), &ctx.data().db, &store, guild_id.get(), window.channel_id, id, &cause).await?;
+        ctx.say(format!("Ended and removed window #{}.", id)).await?;
+    } else {
+        store.remove_window(guild_id.get(), id)?;
+        ctx.say(format!("Removed window #{}.", id)).await?;
+    }
+    Ok(())
+}
+
+/// Lists auto slow mode channels, raised delays and scheduled windows.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_CHANNELS")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = SlowModeStore::open(&ctx.data().db)?;
+    let auto: Vec<String> = store
+        .auto_list(guild_id.get())?
+        .iter()
+        .map(|(c, a)| {
+            let raised = store
+                .raised(guild_id.get(), *c)
+                .ok()
+                .flatten()
+                .filter(|r| r.source == SlowModeSource::Auto)
+                .map(|r| format!(" - raised to {} now", format_delay(r.current)))
+                .unwrap_or_default();
+            format!(
+                "<#{}>: {} msgs/s, max {}, decay {}{}",
+                c,
+                a.threshold,
+                format_delay(a.max_delay),
+                format_duration(a.decay_secs as u64),
+                raised
+            )
+        })
+        .collect();
+    let mut windows = store.windows(guild_id.get())?;
+    windows.sort_by_key(|w| w.starts_at);
+    let windows: Vec<String> = windows
+        .iter()
+        .map(|w| {
+            format!(
+                "#{} <#{}>: {} from <t:{}:f> to <t:{}:f>{}{}",
+                w.id,
+                w.channel_id,
+                format_delay(w.delay),
+                w.starts_at,
+                w.ends_at,
+                w.label.as_deref().map(|l| format!(" - {}", l)).unwrap_or_default(),
+                if w.started { " (running)" } else { "" }
+            )
+        })
+        .collect();
+    let embed = serenity::CreateEmbed::default()
+        .title("Slow mode")
+        .field(
+            "Auto slow mode",
+            if auto.is_empty() { "none".to_string() } else { super::cases::truncate(&auto.join("\n"), 1024) },
+            false,
+        )
+        .field(
+            "Scheduled windows",
+            if windows.is_empty() { "none".to_string() } else { super::cases::truncate(&windows.join("\n"), 1024) },
+            false,
+        )
+        .color(0x6B7280);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
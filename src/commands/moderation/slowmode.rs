@@ -0,0 +1,152 @@
+// Shared slow mode handling: applying and logging delay changes, and the auto slow mode that
+// raises the delay of a channel under message bursts. Stepping back down and scheduled windows
+// are handled by the slow mode task.
+
+use super::utils::parse_duration;
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::logging::delivery::{send_log, Subject};
+use crate::data::logging::store::LogType;
+use crate::data::slowmode::store::{RaisedSlowMode, SlowModeSource, SlowModeStore};
+use poise::serenity_prelude as serenity;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Discord caps slow mode at 6 hours
+pub(crate) const MAX_DELAY: u16 = 21_600;
+// The delays auto slow mode steps through, in seconds
+const LEVELS: [u16; 13] = [5, 10, 15, 30, 60, 120, 300, 600, 900, 1800, 3600, 7200, 21_600];
+// Message rates are measured over this window
+const SAMPLE: Duration = Duration::from_secs(10);
+// Minimum time between two raises of the same channel
+const RAISE_COOLDOWN_SECS: i64 = 10;
+
+/// Recent message times per channel, used to measure message rates for auto slow mode.
+#[derive(Default)]
+pub struct RateTracker {
+    channels: Mutex<HashMap<u64, VecDeque<Instant>>>,
+}
+
+impl RateTracker {
+    /// Records a message and returns the channel's rate in messages per second.
+    fn record(&self, channel_id: u64) -> f64 {
+        let now = Instant::now();
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        let times = channels.entry(channel_id).or_default();
+        times.push_back(now);
+        while times.front().is_some_and(|t| now.duration_since(*t) > SAMPLE) {
+            times.pop_front();
+        }
+        times.len() as f64 / SAMPLE.as_secs_f64()
+    }
+
+    fn reset(&self, channel_id: u64) {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner()).remove(&channel_id);
+    }
+}
+
+/// Parses a slow mode delay. `off` is 0, a bare number is seconds, anything else a duration like `30s` or `2m`.
+pub(crate) fn parse_delay(input: &str) -> Option<u16> {
+    let s = input.trim().to_lowercase();
+    if s == "off" || s == "none" {
+        return Some(0);
+    }
+    let secs = match s.parse::<u64>() {
+        Ok(n) => n,
+        Err(_) => parse_duration(&s)?.as_secs(),
+    };
+    u16::try_from(secs).ok().filter(|d| *d <= MAX_DELAY)
+}
+
+pub(crate) fn format_delay(secs: u16) -> String {
+    if secs == 0 { "off".to_string() } else { super::utils::format_duration(secs as u64) }
+}
+
+/// The next auto slow mode level above `current`.
+pub(crate) fn level_above(current: u16) -> u16 {
+    LEVELS.iter().copied().find(|l| *l > current).unwrap_or(MAX_DELAY)
+}
+
+/// The next auto slow mode level below `current`, or 0.
+pub(crate) fn level_below(current: u16) -> u16 {
+    LEVELS.iter().rev().copied().find(|l| *l < current).unwrap_or(0)
+}
+
+/// The slow mode a channel has right now.
+pub(crate) async fn current_delay(http: &serenity::Http, channel_id: u64) -> Result<u16, serenity::Error> {
+    match serenity::ChannelId::new(channel_id).to_channel(http).await? {
+        serenity::Channel::Guild(c) => Ok(c.rate_limit_per_user.unwrap_or(0)),
+        _ => Ok(0),
+    }
+}
+
+/// Sets the slow mode of a channel and posts the change to the slow mode log channel.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn set_delay(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: u64,
+    channel_id: u64,
+    old: u16,
+    new: u16,
+    cause: &str,
+    moderator_id: Option<u64>,
+) -> Result<(), crate::Error> {
+    serenity::ChannelId::new(channel_id)
+        .edit(ctx, serenity::EditChannel::new().rate_limit_per_user(new).audit_log_reason(cause))
+        .await?;
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title("Slow mode changed")
+        .description(format!("<#{}>: {} -> **{}**", channel_id, format_delay(old), format_delay(new)))
+        .field("Cause", cause, false)
+        .color(if new > old { 0xF59E0B } else { 0x22C55E });
+    if let Some(m) = moderator_id {
+        embed = embed.field("Moderator", format!("<@{}>", m), true);
+    }
+    send_log(ctx, db, guild_id, LogType::SlowMode, Subject::default(), embed).await
+}
+
+/// Measures the message rate of channels with auto slow mode and raises the delay one level when it
+/// goes over the configured threshold.
+pub async fn track_message(ctx: &serenity::Context, msg: &serenity::Message, data: &crate::Data) -> Result<(), crate::Error> {
+    let Some(guild_id) = msg.guild_id else { return Ok(()) };
+    if msg.author.bot || !enabled(&data.db, guild_id.get(), Feature::Moderation) {
+        return Ok(());
+    }
+    let store = SlowModeStore::open(&data.db)?;
+    let Some(auto) = store.auto(guild_id.get(), msg.channel_id.get())? else { return Ok(()) };
+    let rate = data.message_rates.record(msg.channel_id.get());
+    if rate <= auto.threshold {
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let raised = store.raised(guild_id.get(), msg.channel_id.get())?;
+    let current = match &raised {
+        // Scheduled windows own the channel while they run
+        Some(r) if r.source == SlowModeSource::Window => return Ok(()),
+        Some(r) if now - r.changed_at < RAISE_COOLDOWN_SECS => return Ok(()),
+        Some(r) => r.current,
+        None => current_delay(&ctx.http, msg.channel_id.get()).await?,
+    };
+    let next = level_above(current).min(auto.max_delay);
+    if next <= current {
+        return Ok(());
+    }
+
+    let cause = format!("Auto slow mode: {:.1} msgs/s over the {:.1} msgs/s threshold", rate, auto.threshold);
+    set_delay(ctx, &data.db, guild_id.get(), msg.channel_id.get(), current, next, &cause, None).await?;
+    store.set_raised(&RaisedSlowMode {
+        guild_id: guild_id.get(),
+        channel_id: msg.channel_id.get(),
+        base: raised.map(|r| r.base).unwrap_or(current),
+        current: next,
+        source: SlowModeSource::Auto,
+        changed_at: now,
+        window_id: None,
+    })?;
+    // The rate is measured afresh under the new delay
+    data.message_rates.reset(msg.channel_id.get());
+    Ok(())
+}
@@ -1,2 +1,160 @@
 // Warns all users who either created their account <time ago?, or joined <time ago> for [reason]
-// timewarn <created|joined> <time ago> [reason]
\ No newline at end of file
+// timewarn <created|joined> <time ago> [reason]
+
+use super::mass::{apply, TargetOutcome};
+use super::utils::{audit_reason, fetch_members, format_duration, parse_duration, Hierarchy};
+use super::warnings::{escalate, warn_expiry};
+use crate::data::cases::store::{CaseAction, CaseStore, NewCase};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const MAX_TARGETS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum TimeFilter {
+    Created,
+    Joined,
+}
+
+/// Warns every member whose account was created or who joined within the given time.
+#[poise::command(slash_command, prefix_command, rename = "timewarn", guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn time_warn(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Match by account creation or join time"] filter: TimeFilter,
+    #[description = "How far back, e.g. 30m, 2h, 1d"] time_ago: String,
+    #[rest]
+    #[description = "Reason"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let Some(window) = parse_duration(&time_ago) else {
+        ctx.say(format!("Invalid time `{}`. Use e.g. 30m, 2h, 1d.", time_ago)).await?;
+        return Ok(());
+    };
+    let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let since = chrono::Utc::now().timestamp() - window.as_secs() as i64;
+
+    let targets: Vec<u64> = fetch_members(ctx.http(), guild_id)
+        .await?
+        .into_iter()
+        .filter(|m| !m.user.bot)
+        .filter(|m| match filter {
+            TimeFilter::Created => m.user.created_at().unix_timestamp() >= since,
+            TimeFilter::Joined => m.joined_at.is_some_and(|j| j.unix_timestamp() >= since),
+        })
+        .map(|m| m.user.id.get())
+        .collect();
+    let what = match filter {
+        TimeFilter::Created => "created their account",
+        TimeFilter::Joined => "joined",
+    };
+    if targets.is_empty() {
+        ctx.say(format!("No members {} in the last {}.", what, format_duration(window.as_secs()))).await?;
+        return Ok(());
+    }
+    if targets.len() > MAX_TARGETS {
+        ctx.say(format!("Too many members match ({}). The limit is {} per command.", targets.len(), MAX_TARGETS)).await?;
+        return Ok(());
+    }
+
+    let prefix = ctx.id().to_string();
+    let confirm_id = format!("{}_confirm", prefix);
+    let cancel_id = format!("{}_cancel", prefix);
+    let prompt_embed = serenity::CreateEmbed::default()
+        .title("Time warn")
+        .description(format!(
+            "**{}** member(s) {} in the last {} will be warned.",
+            targets.len(),
+            what,
+            format_duration(window.as_secs())
+        ))
+        .field("Reason", reason.as_deref().unwrap_or("No reason provided"), false)
+        .color(0xF59E0B);
+    let buttons = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(&confirm_id).label("Warn").style(serenity::ButtonStyle::Primary),
+        serenity::CreateButton::new(&cancel_id).label("Cancel").style(serenity::ButtonStyle::Danger),
+    ]);
+    let prompt = ctx
+        .send(poise::CreateReply::default().embed(prompt_embed).components(vec![buttons]))
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    let Some(mci) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(Duration::from_secs(120))
+        .filter(move |mci| mci.data.custom_id.starts_with(&filter_prefix))
+        .await
+    else {
+        prompt
+            .edit(ctx, poise::CreateReply::default().content("Timed out, nothing was done.").components(vec![]))
+            .await?;
+        return Ok(());
+    };
+    let confirmed = mci.data.custom_id == confirm_id;
+    mci.create_response(
+        ctx,
+        serenity::CreateInteractionResponse::UpdateMessage(
+            serenity::CreateInteractionResponseMessage::new()
+                .content(if confirmed { "Warning..." } else { "Cancelled, nothing was done." })
+                .components(vec![]),
+        ),
+    )
+    .await?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let hierarchy = Hierarchy::load(ctx.serenity_context(), guild_id).await?;
+    let moderator = guild_id.member(ctx, ctx.author().id).await?;
+    let bot_id = ctx.cache().current_user().id;
+    let bot = guild_id.member(ctx, bot_id).await?;
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let until = warn_expiry(&ctx.data().db, guild_id.get(), None)?;
+
+    let mut succeeded = Vec::new();
+    let mut skipped = 0;
+    for uid in &targets {
+        match apply(ctx.serenity_context(), &ctx.data().db, guild_id, CaseAction::Warn, *uid, &hierarchy, &moderator, &bot, &audit, until).await {
+            TargetOutcome::Success => succeeded.push(*uid),
+            _ => skipped += 1,
+        }
+    }
+    if succeeded.is_empty() {
+        prompt
+            .edit(ctx, poise::CreateReply::default().content("Nobody could be warned (hierarchy or missing members)."))
+            .await?;
+        return Ok(());
+    }
+
+    let case = CaseStore::open(&ctx.data().db)?.create(
+        guild_id.get(),
+        NewCase {
+            action: CaseAction::Warn,
+            targets: succeeded.clone(),
+            moderator_id: ctx.author().id.get(),
+            reason: reason.clone(),
+            expires_at: until,
+        },
+    )?;
+    let mut escalations = Vec::new();
+    for uid in &succeeded {
+        if let Some(line) = escalate(ctx.serenity_context(), &ctx.data().db, guild_id, *uid, &hierarchy, &moderator, &bot).await? {
+            escalations.push(line);
+        }
+    }
+
+    let mut summary = format!("**Warned**: {}\n**Skipped**: {}", succeeded.len(), skipped);
+    if !escalations.is_empty() {
+        summary.push_str(&format!("\n\n**Escalations**\n{}", escalations.join("\n")));
+    }
+    let embed = serenity::CreateEmbed::default()
+        .title(format!("Time warn - case #{}", case.id))
+        .description(super::cases::truncate(&summary, 4096))
+        .field("Reason", reason.as_deref().unwrap_or("No reason provided"), false)
+        .color(0x22C55E);
+    prompt
+        .edit(ctx, poise::CreateReply::default().content("").embed(embed))
+        .await?;
+    Ok(())
+}
@@ -1,2 +1,22 @@
 // Unblock a user from submitting appeals.
-// unblock_appeal_user <user>
\ No newline at end of file
+// unblock_appeal_user <user>
+
+use crate::data::appeals::store::AppealStore;
+use poise::serenity_prelude as serenity;
+
+/// Unblock a user from submitting appeals.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "BAN_MEMBERS")]
+pub async fn unblock_appeal_user(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User to unblock"] user: serenity::User,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = AppealStore::open(&ctx.data().db)?;
+    let msg = if store.active_block(guild_id.get(), user.id.get())?.is_some() && store.unblock(guild_id.get(), user.id.get())? {
+        format!("<@{}> can submit appeals again.", user.id)
+    } else {
+        format!("<@{}> is not blocked from appealing.", user.id)
+    };
+    ctx.say(msg).await?;
+    Ok(())
+}
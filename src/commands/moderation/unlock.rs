@@ -1,3 +1,47 @@
 // Unlock a text or music channel.
 // add +s at the end to silently unlock the channel without an info message.
-// unlock [channel] [reason] [+s]
\ No newline at end of file
+// unlock [channel] [reason] [+s]
+
+use super::locking::{split_flags, unlock_channel};
+use super::utils::audit_reason;
+use crate::data::locks::store::LockStore;
+use poise::serenity_prelude as serenity;
+
+/// Unlock a channel, restoring its permissions from before the lock. Add +s to unlock silently.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES"
+)]
+pub async fn unlock(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Channel to unlock (this one if omitted)"] channel: Option<serenity::GuildChannel>,
+    #[rest]
+    #[description = "Reason, optionally ending in +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (reason, flags) = match split_flags(reason, "s") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+    let channel_id = channel.map(|c| c.id).unwrap_or_else(|| ctx.channel_id());
+
+    let store = LockStore::open(&ctx.data().db)?;
+    let Some(snapshot) = store.get(guild_id.get(), channel_id.get())? else {
+        ctx.say(format!("<#{}> was not locked with `lock`, `lockall` or `lockdown`.", channel_id)).await?;
+        return Ok(());
+    };
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let msg = match unlock_channel(ctx.serenity_context(), &store, &snapshot, flags.silent, reason.as_deref(), &audit).await? {
+        Ok(()) => format!("Unlocked <#{}>.", channel_id),
+        Err(msg) => msg,
+    };
+    ctx.send(poise::CreateReply::default().content(msg).ephemeral(flags.silent)).await?;
+    Ok(())
+}
@@ -1,4 +1,50 @@
 // Unlock all locked channels.
 // Add +l at the end to only unlock channels locked with lockall.
 // Add +s at the end to silently unlock the channels without an info message.
-// unlockall [reason] [+(l|s)
\ No newline at end of file
+// unlockall [reason] [+(l|s)
+
+use super::locking::{split_flags, unlock_many};
+use super::utils::audit_reason;
+use crate::data::locks::store::{LockSource, LockStore};
+
+/// Unlock all locked channels. Flags: +l only channels locked with lockall, +s silent.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "unlockall",
+    guild_only,
+    required_permissions = "MANAGE_CHANNELS",
+    required_bot_permissions = "MANAGE_CHANNELS | MANAGE_ROLES"
+)]
+pub async fn unlock_all(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[rest]
+    #[description = "Reason, optionally ending in +l and/or +s"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let (reason, flags) = match split_flags(reason, "ls") {
+        Ok(r) => r,
+        Err(msg) => {
+            ctx.say(msg).await?;
+            return Ok(());
+        }
+    };
+    ctx.defer().await?;
+
+    let store = LockStore::open(&ctx.data().db)?;
+    let audit = audit_reason(ctx.author(), reason.as_deref());
+    let only_lockall = flags.lockall_only;
+    let msg = unlock_many(
+        ctx.serenity_context(),
+        &store,
+        guild_id,
+        |s| !only_lockall || s.source == LockSource::LockAll,
+        flags.silent,
+        reason.as_deref(),
+        &audit,
+    )
+    .await?;
+    ctx.say(msg).await?;
+    Ok(())
+}
@@ -1,2 +1,41 @@
 // Closes all warns from a user.
-// unwarn [user]
\ No newline at end of file
+// unwarn [user]
+
+use super::cases::split_case;
+use super::warnings::{is_active, user_warns};
+use crate::data::cases::store::CaseStore;
+use poise::serenity_prelude as serenity;
+
+/// Closes all active warns of a user. Mass warns are split off first so other users keep theirs.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn unwarn(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User whose warns to close"] user: serenity::User,
+    #[rest]
+    #[description = "Reason for closing"]
+    reason: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = CaseStore::open(&ctx.data().db)?;
+    let now = chrono::Utc::now().timestamp();
+    let moderator_id = ctx.author().id.get();
+
+    let mut closed = Vec::new();
+    for mut case in user_warns(&store, guild_id.get(), user.id.get())? {
+        if !is_active(&case, now) {
+            continue;
+        }
+        let mut case = if case.is_mass() { split_case(&store, &mut case, user.id.get(), moderator_id)? } else { case };
+        case.close(Some(moderator_id), reason.clone());
+        store.save(&case)?;
+        closed.push(format!("#{}", case.id));
+    }
+
+    if closed.is_empty() {
+        ctx.say(format!("{} has no active warns.", user.name)).await?;
+    } else {
+        closed.reverse();
+        ctx.say(format!("Closed {} warn(s) of {}: {}.", closed.len(), user.name, closed.join(", "))).await?;
+    }
+    Ok(())
+}
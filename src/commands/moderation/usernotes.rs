@@ -1,2 +1,154 @@
 // Gets all notes about a user or adds on.
-// usernotes <user> [note to add]
\ No newline at end of file
+// usernotes <user> [note to add]
+
+use super::cases::truncate;
+use crate::commands::pagination::paginate_embeds_ephemeral;
+use crate::data::notes::store::{NoteAttachment, NoteStore, UserNote};
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 8;
+const MAX_NOTE_CHARS: usize = 1500;
+const MAX_ATTACHMENT_BYTES: u32 = 25 * 1024 * 1024;
+
+/// One note as shown in the lists.
+pub(crate) fn note_line(note: &UserNote) -> String {
+    let mut line = format!(
+        "**#{}** about <@{}> by <@{}> <t:{}:R>{}\n{}",
+        note.id,
+        note.user_id,
+        note.author_id,
+        note.created_at,
+        if note.edited_at.is_some() { " (edited)" } else { "" },
+        truncate(&note.content, 600)
+    );
+    for a in &note.attachments {
+        line.push_str(&format!("\n[{}]({})", a.filename, a.message_link));
+    }
+    line
+}
+
+/// A short summary of the notes about a user, shown next to cases and reports. None when there are no notes.
+pub(crate) fn notes_summary(db: &sled::Db, guild_id: u64, user_id: u64) -> Option<String> {
+    let notes = NoteStore::open(db).and_then(|s| s.for_user(guild_id, user_id)).ok()?;
+    let latest = notes.first()?;
+    let text = format!(
+        "{} note(s). Latest (#{}) by <@{}> <t:{}:R>:\n{}",
+        notes.len(),
+        latest.id,
+        latest.author_id,
+        latest.created_at,
+        latest.content
+    );
+    Some(truncate(&text, 1024))
+}
+
+/// Uploads files again in the note file channel of the guild, whose message then hosts them for as
+/// long as it exists (attachment URLs expire). None when no file channel is set up.
+async fn rehost(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    guild_id: serenity::GuildId,
+    files: &[&serenity::Attachment],
+    caption: String,
+) -> Result<Option<serenity::Message>, crate::Error> {
+    let Some(config) = NoteStore::open(&ctx.data().db)?.config(guild_id.get())? else { return Ok(None) };
+    let mut message = serenity::CreateMessage::new().content(caption).allowed_mentions(serenity::CreateAllowedMentions::new());
+    for a in files {
+        message = message.add_file(serenity::CreateAttachment::bytes(a.download().await?, a.filename.clone()));
+    }
+    Ok(Some(serenity::ChannelId::new(config.file_channel_id).send_message(ctx.serenity_context(), message).await?))
+}
+
+/// Lists the notes about a user, or adds one (optionally with a file).
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn usernotes(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User the notes are about"] user: serenity::User,
+    #[description = "File to attach to the new note"] attachment: Option<serenity::Attachment>,
+    #[rest]
+    #[description = "Note to add (lists the notes if empty)"]
+    note: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = NoteStore::open(&ctx.data().db)?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    if note.is_none() && attachment.is_none() {
+        let notes = store.for_user(guild_id.get(), user.id.get())?;
+        if notes.is_empty() {
+            ctx.send(poise::CreateReply::default().content(format!("There are no notes about {}.", user.name)).ephemeral(true))
+                .await?;
+            return Ok(());
+        }
+        let pages = notes
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                serenity::CreateEmbed::default()
+                    .title(format!("Notes about {}", user.name))
+                    .description(chunk.iter().map(note_line).collect::<Vec<_>>().join("\n\n"))
+                    .color(0x6366F1)
+                    .footer(serenity::CreateEmbedFooter::new(format!("{} note(s)", notes.len())))
+            })
+            .collect();
+        return paginate_embeds_ephemeral(ctx, pages).await;
+    }
+
+    let content = note.unwrap_or_default();
+    if content.chars().count() > MAX_NOTE_CHARS {
+        ctx.send(
+            poise::CreateReply::default()
+                .content(format!("Notes can be at most {} characters long.", MAX_NOTE_CHARS))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+    if attachment.as_ref().is_some_and(|a| a.size > MAX_ATTACHMENT_BYTES) {
+        ctx.send(poise::CreateReply::default().content("The attachment is too large (max 25 MB).").ephemeral(true)).await?;
+        return Ok(());
+    }
+    if attachment.is_some() && store.config(guild_id.get())?.is_none() {
+        ctx.send(
+            poise::CreateReply::default()
+                .content("Set up a channel for note files first with `/notes filechannel`.")
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut entry = UserNote {
+        id: store.next_id(guild_id.get())?,
+        guild_id: guild_id.get(),
+        user_id: user.id.get(),
+        author_id: ctx.author().id.get(),
+        content,
+        attachments: Vec::new(),
+        created_at: chrono::Utc::now().timestamp(),
+        edited_at: None,
+        deleted: false,
+        history: Vec::new(),
+    };
+    if let Some(a) = &attachment {
+        let caption = format!("File of note #{} about <@{}>, added by <@{}>", entry.id, user.id, ctx.author().id);
+        if let Some(msg) = rehost(ctx, guild_id, &[a], caption).await? {
+            entry.attachments = msg
+                .attachments
+                .iter()
+                .map(|a| NoteAttachment {
+                    filename: a.filename.clone(),
+                    url: a.url.clone(),
+                    message_link: msg.link(),
+                })
+                .collect();
+        }
+    }
+    store.save(&entry)?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("Added note #{} about <@{}>.", entry.id, user.id))
+            .allowed_mentions(serenity::CreateAllowedMentions::new())
+            .ephemeral(true),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+
+/// Extracts user IDs from mentions (`<@id>`, `<@!id>`) and raw IDs separated by whitespace,
+/// commas or newlines. Returns the IDs in input order without duplicates, plus every token
+/// that could not be parsed.
+pub(crate) fn parse_user_ids(input: &str) -> (Vec<u64>, Vec<String>) {
+    let mut ids: Vec<u64> = Vec::new();
+    let mut invalid: Vec<String> = Vec::new();
+    for token in input.split(|c: char| c.is_whitespace() || c == ',' || c == ';') {
+        let t = token.trim();
+        if t.is_empty() { continue; }
+        let inner = t
+            .strip_prefix("<@")
+            .and_then(|r| r.strip_suffix('>'))
+            .map(|r| r.trim_start_matches('!'))
+            .unwrap_or(t);
+        match inner.parse::<u64>() {
+            Ok(id) if (15..=21).contains(&inner.len()) => {
+                if !ids.contains(&id) { ids.push(id); }
+            }
+            _ => invalid.push(t.to_string()),
+        }
+    }
+    (ids, invalid)
+}
+
+/// Parses a message link (`https://discord.com/channels/<guild>/<channel>/<message>`) into its three ids.
+pub(crate) fn parse_message_link(input: &str) -> Option<(u64, u64, u64)> {
+    let rest = input.trim().trim_matches(|c| c == '<' || c == '>');
+    let rest = rest.split("/channels/").nth(1)?;
+    let mut ids = rest.split('/').map(|p| p.parse::<u64>().ok());
+    Some((ids.next()??, ids.next()??, ids.next()??))
+}
+
+/// Parses durations like `30m`, `2h`, `1d12h` or `1w`. A bare number is read as minutes.
+pub(crate) fn parse_duration(input: &str) -> Option<std::time::Duration> {
+    let s = input.trim().to_lowercase();
+    if s.is_empty() { return None; }
+    if let Ok(mins) = s.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(mins * 60));
+    }
+
+    let mut total: u64 = 0;
+    let mut num = String::new();
+    for ch in s.chars() {
+        if ch.is_ascii_digit() {
+            num.push(ch);
+            continue;
+        }
+        if ch.is_whitespace() { continue; }
+        let n: u64 = num.parse().ok()?;
+        num.clear();
+        let unit = match ch {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return None,
+        };
+        total = total.checked_add(n.checked_mul(unit)?)?;
+    }
+    if !num.is_empty() || total == 0 { return None; }
+    Some(std::time::Duration::from_secs(total))
+}
+
+pub(crate) fn format_duration(secs: u64) -> String {
+    if secs == 0 { return "0s".to_string(); }
+    let units = [("w", 604_800), ("d", 86_400), ("h", 3600), ("m", 60), ("s", 1)];
+    let mut rest = secs;
+    let mut parts = Vec::new();
+    for (label, size) in units {
+        if rest >= size {
+            parts.push(format!("{}{}", rest / size, label));
+            rest %= size;
+        }
+    }
+    parts.join(" ")
+}
+
+/// Discord limits audit log reasons to 512 characters.
+pub(crate) fn audit_reason(moderator: &serenity::User, reason: Option<&str>) -> String {
+    let text = format!("{}: {}", moderator.name, reason.unwrap_or("No reason provided"));
+    text.chars().take(512).collect()
+}
+
+/// Snapshot of a guild's role positions, used to check who may act on whom.
+pub(crate) struct Hierarchy {
+    owner_id: serenity::UserId,
+    positions: HashMap<serenity::RoleId, u16>,
+}
+
+impl Hierarchy {
+    pub(crate) async fn load(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Result<Self, crate::Error> {
+        if let Some(guild) = ctx.cache.guild(guild_id) {
+            return Ok(Self {
+                owner_id: guild.owner_id,
+                positions: guild.roles.iter().map(|(id, r)| (*id, r.position)).collect(),
+            });
+        }
+        let guild = guild_id.to_partial_guild(&ctx.http).await?;
+        Ok(Self {
+            owner_id: guild.owner_id,
+            positions: guild.roles.iter().map(|(id, r)| (*id, r.position)).collect(),
+        })
+    }
+
+    fn top_position(&self, member: &serenity::Member) -> u16 {
+        member.roles.iter().filter_map(|r| self.positions.get(r)).copied().max().unwrap_or(0)
+    }
+
+    /// Whether `actor` is strictly above `target` in the role hierarchy.
+    pub(crate) fn outranks(&self, actor: &serenity::Member, target: &serenity::Member) -> bool {
+        if target.user.id == self.owner_id { return false; }
+        if actor.user.id == self.owner_id { return true; }
+        self.top_position(actor) > self.top_position(target)
+    }
+}
+
+/// Every member of a guild, fetched page by page over HTTP so the result doesn't depend on the cache.
+pub(crate) async fn fetch_members(
+    http: &serenity::Http,
+    guild_id: serenity::GuildId,
+) -> Result<Vec<serenity::Member>, serenity::Error> {
+    let mut out = Vec::new();
+    let mut after = None;
+    loop {
+        let page = guild_id.members(http, Some(1000), after).await?;
+        let full = page.len() == 1000;
+        after = page.last().map(|m| m.user.id);
+        out.extend(page);
+        if !full {
+            break;
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) type Repost<'a> = std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), crate::Error>> + Send + 'a>>;
+
+/// Reposts the message of every item whose message was deleted and builds the summary reply.
+/// `location` gives the id, channel and message of an item, `repost` posts and saves it again.
+pub(crate) async fn repost_missing<'a, T>(
+    ctx: &serenity::Context,
+    items: Vec<T>,
+    what: &str,
+    location: impl Fn(&T) -> (u64, u64, u64),
+    mut repost: impl FnMut(T) -> Repost<'a>,
+) -> String {
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    for item in items {
+        let (id, channel_id, message_id) = location(&item);
+        let lookup = serenity::ChannelId::new(channel_id)
+            .message(ctx, serenity::MessageId::new(message_id))
+            .await;
+        // Only a confirmed missing message is reposted, other errors could be temporary
+        match lookup {
+            Err(serenity::Error::Http(e)) if e.status_code().is_some_and(|s| s.as_u16() == 404) => {}
+            _ => continue,
+        }
+        match repost(item).await {
+            Ok(()) => restored.push(format!("#{}", id)),
+            Err(e) => failed.push(format!("#{}: {}", id, e)),
+        }
+    }
+
+    let mut msg = if restored.is_empty() {
+        format!("No {} messages were missing.", what)
+    } else {
+        format!("Restored {} {} message(s): {}", restored.len(), what, restored.join(", "))
+    };
+    if !failed.is_empty() {
+        msg.push_str(&format!("\nCould not restore: {}", failed.join(", ")));
+    }
+    msg
+}
+
+/// The `channel` subcommand of reportconfig and appealconfig. `save` stores the channel id for the guild.
+pub(crate) async fn set_post_channel(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    channel: &serenity::GuildChannel,
+    what: &str,
+    save: impl FnOnce(u64, u64) -> Result<(), crate::Error>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id {
+        ctx.say("That channel is not in this server.").await?;
+        return Ok(());
+    }
+    save(guild_id.get(), channel.id.get())?;
+    ctx.say(format!("{} will be posted in <#{}>.", what, channel.id)).await?;
+    Ok(())
+}
+
+/// The `disable` subcommand of reportconfig and appealconfig. `clear` removes the guild's config.
+pub(crate) async fn disable_posts(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    what: &str,
+    clear: impl FnOnce(u64) -> Result<(), crate::Error>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    clear(guild_id.get())?;
+    ctx.say(format!("This server no longer accepts {}.", what)).await?;
+    Ok(())
+}
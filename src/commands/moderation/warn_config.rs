@@ -0,0 +1,153 @@
+// Sets how long warns stay active and what happens once a user collects enough of them.
+// warnconfig decay <duration|off>
+// warnconfig rule <warn count> <mute|kick|ban> [duration]
+// warnconfig removerule <warn count>
+// warnconfig list
+
+use super::utils::{format_duration, parse_duration};
+use super::warnings::rule_text;
+use crate::data::cases::store::CaseAction;
+use crate::data::warns::store::{EscalationRule, WarnStore};
+use poise::serenity_prelude as serenity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum EscalationAction {
+    Mute,
+    Kick,
+    Ban,
+}
+
+impl From<EscalationAction> for CaseAction {
+    fn from(a: EscalationAction) -> Self {
+        match a {
+            EscalationAction::Mute => CaseAction::Mute,
+            EscalationAction::Kick => CaseAction::Kick,
+            EscalationAction::Ban => CaseAction::Ban,
+        }
+    }
+}
+
+/// Sets warn decay and the escalation rules applied when warns add up.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "warnconfig",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("decay", "rule", "removerule", "list"),
+    subcommand_required
+)]
+pub async fn warn_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Sets how long warns without their own duration stay active (off = forever).
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn decay(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "e.g. 30d, 12w or off"] time: String,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let decay_secs = match time.trim().to_lowercase().as_str() {
+        "off" | "none" | "never" => None,
+        raw => match parse_duration(raw) {
+            Some(d) => Some(d.as_secs()),
+            None => {
+                ctx.say(format!("Invalid time `{}`. Use e.g. 30d, 12w or off.", time)).await?;
+                return Ok(());
+            }
+        },
+    };
+    let store = WarnStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.decay_secs = decay_secs;
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(match decay_secs {
+        Some(d) => format!("New warns now expire after {} unless they are given their own duration.", format_duration(d)),
+        None => "New warns now stay active until closed unless they are given their own duration.".to_string(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Adds or replaces the action taken when a user reaches a number of active warns.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn rule(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Number of active warns"]
+    #[min = 1]
+    #[max = 100]
+    count: u32,
+    #[description = "Action to take"] action: EscalationAction,
+    #[description = "Duration for mutes and bans, e.g. 1d (empty = permanent)"] duration: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if !(1..=100).contains(&count) {
+        ctx.say("The warn count must be between 1 and 100.").await?;
+        return Ok(());
+    }
+    let action = CaseAction::from(action);
+    let duration_secs = match duration.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        None => None,
+        Some(_) if action == CaseAction::Kick => None,
+        Some(raw) => match parse_duration(raw) {
+            Some(d) => Some(d.as_secs()),
+            None => {
+                ctx.say(format!("Invalid duration `{}`. Use e.g. 30m, 12h, 7d.", raw)).await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let store = WarnStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.rules.retain(|r| r.count != count);
+    config.rules.push(EscalationRule { count, action, duration_secs });
+    config.rules.sort_by_key(|r| r.count);
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("At {} active warn(s): {}.", count, rule_text(action, duration_secs))).await?;
+    Ok(())
+}
+
+/// Removes the escalation rule for a warn count.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn removerule(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Number of active warns"] count: u32,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = WarnStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    let before = config.rules.len();
+    config.rules.retain(|r| r.count != count);
+    if config.rules.len() == before {
+        ctx.say(format!("There's no rule for {} warn(s).", count)).await?;
+        return Ok(());
+    }
+    store.set_config(guild_id.get(), &config)?;
+    ctx.say(format!("Removed the rule for {} warn(s).", count)).await?;
+    Ok(())
+}
+
+/// Shows warn decay and the escalation rules.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_GUILD")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let config = WarnStore::open(&ctx.data().db)?.config(guild_id.get())?;
+    let rules: Vec<String> = config
+        .rules
+        .iter()
+        .map(|r| format!("{} warn(s): {}", r.count, rule_text(r.action, r.duration_secs)))
+        .collect();
+    let embed = serenity::CreateEmbed::default()
+        .title("Warn settings")
+        .field(
+            "Decay",
+            config.decay_secs.map(format_duration).unwrap_or_else(|| "never".to_string()),
+            false,
+        )
+        .field("Escalation", if rules.is_empty() { "none".to_string() } else { rules.join("\n") }, false)
+        .color(0xEAB308);
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+// Shared warn handling: which warns are still active, warn decay, and the escalation rules that
+// turn a number of active warns into a mute, kick or ban with a case of its own.
+
+use super::mass::{action_permission, apply, TargetOutcome, MAX_MUTE_SECS};
+use super::utils::{format_duration, Hierarchy};
+use crate::data::cases::store::{Case, CaseAction, CaseEventKind, CaseStore, NewCase};
+use crate::data::warns::store::WarnStore;
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+
+/// Whether a warn still counts: not closed and not past its expiry.
+pub(crate) fn is_active(case: &Case, now: i64) -> bool {
+    !case.closed && !case.deleted && case.expires_at.is_none_or(|e| e > now)
+}
+
+/// Every warn of a user in a guild, newest first.
+pub(crate) fn user_warns(store: &CaseStore, guild_id: u64, uid: u64) -> Result<Vec<Case>, crate::Error> {
+    Ok(store
+        .list(guild_id)?
+        .into_iter()
+        .filter(|c| c.action == CaseAction::Warn && c.targets.contains(&uid))
+        .collect())
+}
+
+/// When a new warn runs out: its own duration if it has one, otherwise the guild's decay time.
+pub(crate) fn warn_expiry(db: &sled::Db, guild_id: u64, until: Option<i64>) -> Result<Option<i64>, crate::Error> {
+    if until.is_some() {
+        return Ok(until);
+    }
+    let decay = WarnStore::open(db)?.config(guild_id)?.decay_secs;
+    Ok(decay.map(|d| chrono::Utc::now().timestamp() + d as i64))
+}
+
+pub(crate) fn rule_text(action: CaseAction, duration_secs: Option<u64>) -> String {
+    match (action, duration_secs) {
+        (CaseAction::Kick, _) => "kick".to_string(),
+        (_, Some(d)) => format!("{} for {}", action.as_str(), format_duration(d)),
+        (CaseAction::Mute, None) => format!("mute for {}", format_duration(MAX_MUTE_SECS)),
+        (_, None) => format!("permanent {}", action.as_str()),
+    }
+}
+
+fn role_permissions(
+    guild_id: serenity::GuildId,
+    owner_id: serenity::UserId,
+    roles: &HashMap<serenity::RoleId, serenity::Role>,
+    member: &serenity::Member,
+) -> serenity::Permissions {
+    if member.user.id == owner_id {
+        return serenity::Permissions::all();
+    }
+    let everyone = serenity::RoleId::new(guild_id.get());
+    let perms = roles
+        .iter()
+        .filter(|(id, _)| **id == everyone || member.roles.contains(id))
+        .fold(serenity::Permissions::empty(), |p, (_, r)| p | r.permissions);
+    if perms.administrator() { serenity::Permissions::all() } else { perms }
+}
+
+/// The server-wide permissions of a member. Members from interactions carry them, others are
+/// worked out from their roles.
+async fn guild_permissions(
+    ctx: &serenity::Context,
+    guild_id: serenity::GuildId,
+    member: &serenity::Member,
+) -> Result<serenity::Permissions, crate::Error> {
+    if let Some(p) = member.permissions {
+        return Ok(p);
+    }
+    let cached = ctx.cache.guild(guild_id).map(|g| role_permissions(guild_id, g.owner_id, &g.roles, member));
+    match cached {
+        Some(p) => Ok(p),
+        None => {
+            let guild = guild_id.to_partial_guild(ctx).await?;
+            Ok(role_permissions(guild_id, guild.owner_id, &guild.roles, member))
+        }
+    }
+}
+
+/// Checks the escalation rules after a user was warned. When the user's active warn count hits a rule,
+/// the rule's action is applied and recorded as a case that references the warns behind it, provided
+/// the moderator holds the permission that action needs. Returns a line describing what happened,
+/// or None when no rule matched.
+pub(crate) async fn escalate(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    guild_id: serenity::GuildId,
+    uid: u64,
+    hierarchy: &Hierarchy,
+    moderator: &serenity::Member,
+    bot: &serenity::Member,
+) -> Result<Option<String>, crate::Error> {
+    let config = WarnStore::open(db)?.config(guild_id.get())?;
+    if config.rules.is_empty() {
+        return Ok(None);
+    }
+    let store = CaseStore::open(db)?;
+    let now = chrono::Utc::now().timestamp();
+    let mut warns: Vec<Case> = user_warns(&store, guild_id.get(), uid)?
+        .into_iter()
+        .filter(|c| is_active(c, now))
+        .collect();
+    // Only reaching a count triggers its rule, so further warns don't repeat the same action
+    let Some(rule) = config.rules.iter().find(|r| r.count as usize == warns.len()) else { return Ok(None) };
+
+    let until = match rule.action {
+        CaseAction::Mute => Some(now + rule.duration_secs.unwrap_or(MAX_MUTE_SECS).min(MAX_MUTE_SECS) as i64),
+        CaseAction::Kick => None,
+        _ => rule.duration_secs.map(|d| now + d as i64),
+    };
+    let mut ids: Vec<u64> = warns.iter().map(|c| c.id).collect();
+    ids.sort_unstable();
+    let id_list = ids.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ");
+    let reason = format!("Reached {} active warns ({})", warns.len(), id_list);
+    let audit: String = format!("Warn escalation: {}", reason).chars().take(512).collect();
+
+    // A moderator who may only warn can't trigger a kick or ban through the rules
+    if !action_permission(rule.action)(guild_permissions(ctx, guild_id, moderator).await?) {
+        return Ok(Some(format!(
+            "<@{}> reached {} warns, but you can't {} members, so only the warn was recorded.",
+            uid,
+            warns.len(),
+            rule.action.as_str()
+        )));
+    }
+
+    let outcome = apply(ctx, db, guild_id, rule.action, uid, hierarchy, moderator, bot, &audit, until).await;
+    if !matches!(outcome, TargetOutcome::Success) {
+        return Ok(Some(format!(
+            "<@{}> reached {} warns, but the {} failed: {}",
+            uid,
+            warns.len(),
+            rule.action.as_str(),
+            outcome.label(rule.action)
+        )));
+    }
+
+    let mut case = store.create(
+        guild_id.get(),
+        NewCase {
+            action: rule.action,
+            targets: vec![uid],
+            moderator_id: moderator.user.id.get(),
+            reason: Some(reason),
+            expires_at: until,
+        },
+    )?;
+    case.escalated_from = ids;
+    store.save(&case)?;
+    for warn in &mut warns {
+        warn.record(
+            Some(moderator.user.id.get()),
+            CaseEventKind::Escalated,
+            Some(format!("<@{}> {} in #{}", uid, rule.action.past_tense(), case.id)),
+        );
+        store.save(warn)?;
+    }
+
+    Ok(Some(format!(
+        "<@{}> reached {} warns: {} (case #{})",
+        uid,
+        warns.len(),
+        rule_text(rule.action, rule.duration_secs),
+        case.id
+    )))
+}
@@ -1,2 +1,85 @@
-// Gets all warns about a user.
-// warns [user]
\ No newline at end of file
+// warns [user] [server id]
+
+use super::cases::case_line;
+use super::link_case_view::{guild_label, linked_view};
+use super::warnings::{is_active, user_warns};
+use crate::commands::pagination::paginate_embeds;
+use crate::data::cases::store::{Case, CaseAction, CaseStore};
+use poise::serenity_prelude as serenity;
+
+const PAGE_SIZE: usize = 10;
+
+/// Gets all warns about a user, active and expired ones separately.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MODERATE_MEMBERS")]
+pub async fn warns(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "User to look up"] user: serenity::User,
+    #[description = "ID of a linked server to look the warns up in"] server: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+
+    let owner = match server.as_deref() {
+        Some(s) => match linked_view(ctx, guild_id.get(), s)? {
+            Ok((owner, shared)) if shared.contains(&CaseAction::Warn) => owner,
+            Ok(_) => {
+                ctx.say("That server does not share its warns with this one.").await?;
+                return Ok(());
+            }
+            Err(msg) => {
+                ctx.say(msg).await?;
+                return Ok(());
+            }
+        },
+        None => guild_id.get(),
+    };
+
+    let store = CaseStore::open(&ctx.data().db)?;
+    let warns = user_warns(&store, owner, user.id.get())?;
+
+    let mut title = format!("Warns for {}", user.name);
+    if owner != guild_id.get() {
+        title = format!("{} - {}", guild_label(ctx, owner).await, title);
+    }
+    if warns.is_empty() {
+        ctx.send(poise::CreateReply::default().embed(
+            serenity::CreateEmbed::default().title(title).description("No warns found.").color(0x808080),
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let (active, expired): (Vec<_>, Vec<_>) = warns.iter().partition(|c| is_active(c, now));
+    let footer = format!("{} active, {} expired or closed", active.len(), expired.len());
+    let mut pages: Vec<serenity::CreateEmbed> = Vec::new();
+    // Active warns come first so the ones that count towards escalation are on the first pages
+    for (label, list, color) in [("Active", &active, 0xEAB308), ("Expired", &expired, 0x808080)] {
+        if list.is_empty() {
+            continue;
+        }
+        for chunk in list.chunks(PAGE_SIZE) {
+            let desc: Vec<String> = chunk.iter().map(|c| warn_line(c, now)).collect();
+            pages.push(
+                serenity::CreateEmbed::default()
+                    .title(format!("{} - {} ({})", title, label, list.len()))
+                    .description(desc.join("\n\n"))
+                    .color(color)
+                    .footer(serenity::CreateEmbedFooter::new(&footer)),
+            );
+        }
+    }
+
+    paginate_embeds(ctx, pages).await
+}
+
+fn warn_line(case: &Case, now: i64) -> String {
+    let mut line = case_line(case);
+    match case.expires_at {
+        Some(e) if e > now && !case.closed => line.push_str(&format!("\nExpires <t:{}:R>", e)),
+        Some(e) if !case.closed || case.closed_reason.as_deref() == Some("Expired") => {
+            line.push_str(&format!("\nExpired <t:{}:R>", e))
+        }
+        _ => {}
+    }
+    line
+}
@@ -0,0 +1,81 @@
+use poise::serenity_prelude as serenity;
+
+/// Sends `pages` as a single embed with previous/next buttons, like `poise::builtins::paginate`
+/// but for full embeds. Only the command author can flip pages; the buttons are removed after
+/// ten minutes without interaction.
+pub async fn paginate_embeds(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    pages: Vec<serenity::CreateEmbed>,
+) -> Result<(), crate::Error> {
+    paginate(ctx, pages, false).await
+}
+
+/// Like [`paginate_embeds`], but only the command author can see the pages (for slash commands).
+pub async fn paginate_embeds_ephemeral(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    pages: Vec<serenity::CreateEmbed>,
+) -> Result<(), crate::Error> {
+    paginate(ctx, pages, true).await
+}
+
+async fn paginate(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    pages: Vec<serenity::CreateEmbed>,
+    ephemeral: bool,
+) -> Result<(), crate::Error> {
+    let Some(first) = pages.first().cloned() else { return Ok(()) };
+    if pages.len() == 1 {
+        ctx.send(poise::CreateReply::default().embed(first).ephemeral(ephemeral)).await?;
+        return Ok(());
+    }
+
+    let ctx_id = ctx.id();
+    let prev_button_id = format!("{}_prev", ctx_id);
+    let next_button_id = format!("{}_next", ctx_id);
+    let buttons = |page: usize| {
+        serenity::CreateActionRow::Buttons(vec![
+            serenity::CreateButton::new(&prev_button_id).emoji('◀'),
+            serenity::CreateButton::new(format!("{}_page", ctx_id))
+                .label(format!("{}/{}", page + 1, pages.len()))
+                .style(serenity::ButtonStyle::Secondary)
+                .disabled(true),
+            serenity::CreateButton::new(&next_button_id).emoji('▶'),
+        ])
+    };
+
+    let reply = ctx
+        .send(poise::CreateReply::default().embed(first).components(vec![buttons(0)]).ephemeral(ephemeral))
+        .await?;
+
+    let mut current_page = 0;
+    while let Some(press) = serenity::ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(std::time::Duration::from_secs(600))
+        .await
+    {
+        if press.data.custom_id == next_button_id {
+            current_page = (current_page + 1) % pages.len();
+        } else if press.data.custom_id == prev_button_id {
+            current_page = current_page.checked_sub(1).unwrap_or(pages.len() - 1);
+        } else {
+            continue;
+        }
+
+        press
+            .create_response(
+                ctx.serenity_context(),
+                serenity::CreateInteractionResponse::UpdateMessage(
+                    serenity::CreateInteractionResponseMessage::new()
+                        .embed(pages[current_page].clone())
+                        .components(vec![buttons(current_page)]),
+                ),
+            )
+            .await?;
+    }
+
+    reply
+        .edit(ctx, poise::CreateReply::default().embed(pages[current_page].clone()).components(vec![]))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod panels;
+#[allow(clippy::module_inception)]
+pub mod reaction_roles;
+pub mod reaction_roles_config;
+pub mod reactions;
+pub mod role_panel;
+pub(crate) mod roles;
@@ -0,0 +1,300 @@
+// Renders role panels and applies the roles members choose on them. Components carry custom_ids
+// like `rolepanel:toggle:<panel>:<role>` so posted panels keep working after restarts.
+
+use super::reactions::{parse_emoji, role_change};
+use super::roles::update_roles;
+use crate::commands::general::features::{enabled, Feature};
+use crate::data::reaction_roles::store::ReactionRoleMode;
+use crate::data::role_panels::store::{PanelStyle, RolePanel, RolePanelStore};
+use crate::handlers::component_router::{component_id, ComponentId};
+use poise::serenity_prelude as serenity;
+
+pub(crate) const PREFIX: &str = "rolepanel";
+const REASON: &str = "Role panel";
+
+fn mentions(roles: &[u64]) -> String {
+    roles.iter().map(|r| format!("<@&{}>", r)).collect::<Vec<_>>().join(", ")
+}
+
+/// The embed and components of a posted panel.
+pub(crate) fn panel_message(panel: &RolePanel) -> (serenity::CreateEmbed, Vec<serenity::CreateActionRow>) {
+    let mut embed = serenity::CreateEmbed::default()
+        .title(&panel.title)
+        .color(0x8B5CF6)
+        .footer(serenity::CreateEmbedFooter::new(format!("Role panel #{}", panel.id)));
+    if let Some(d) = &panel.description {
+        embed = embed.description(d);
+    }
+    if panel.options.is_empty() {
+        return (embed, Vec::new());
+    }
+    let rows = match panel.style {
+        PanelStyle::Buttons => panel
+            .options
+            .chunks(5)
+            .map(|chunk| {
+                serenity::CreateActionRow::Buttons(
+                    chunk
+                        .iter()
+                        .map(|o| {
+                            let mut button = serenity::CreateButton::new(component_id(PREFIX, "toggle", [panel.id, o.role_id]))
+                                .label(&o.label)
+                                .style(serenity::ButtonStyle::Secondary);
+                            if let Some(emoji) = o.emoji.as_deref().and_then(parse_emoji) {
+                                button = button.emoji(emoji);
+                            }
+                            button
+                        })
+                        .collect(),
+                )
+            })
+            .collect(),
+        PanelStyle::Select => {
+            let menu = select_menu(panel, component_id(PREFIX, "select", [panel.id]), &[]);
+            vec![serenity::CreateActionRow::SelectMenu(menu)]
+        }
+    };
+    (embed, rows)
+}
+
+/// The role menu of a select panel with the `chosen` options preselected. The posted panel can't show
+/// what each member chose, so members also get their own menu, under the `choose` action.
+fn select_menu(panel: &RolePanel, custom_id: String, chosen: &[u64]) -> serenity::CreateSelectMenu {
+    let options = panel
+        .options
+        .iter()
+        .map(|o| {
+            let mut option = serenity::CreateSelectMenuOption::new(&o.label, o.role_id.to_string())
+                .default_selection(chosen.contains(&o.role_id));
+            if let Some(d) = &o.description {
+                option = option.description(d);
+            }
+            if let Some(emoji) = o.emoji.as_deref().and_then(parse_emoji) {
+                option = option.emoji(emoji);
+            }
+            option
+        })
+        .collect();
+    let max = if panel.mode == ReactionRoleMode::Unique {
+        1
+    } else {
+        panel.max_selections.unwrap_or(u32::MAX).min(panel.options.len() as u32)
+    };
+    serenity::CreateSelectMenu::new(custom_id, serenity::CreateSelectMenuKind::String { options })
+        .placeholder("Choose your roles")
+        .min_values(0)
+        .max_values(max as u8)
+}
+
+/// Updates the posted message of a panel after its options or settings changed.
+pub(crate) async fn refresh(http: &serenity::Http, panel: &RolePanel) -> Result<(), serenity::Error> {
+    let (Some(channel_id), Some(message_id)) = (panel.channel_id, panel.message_id) else { return Ok(()) };
+    let (embed, components) = panel_message(panel);
+    serenity::ChannelId::new(channel_id)
+        .edit_message(http, message_id, serenity::EditMessage::new().embed(embed).components(components))
+        .await?;
+    Ok(())
+}
+
+/// Roles to add and remove, plus the ones the panel's mode doesn't let the member change.
+#[derive(Default)]
+struct RoleChanges {
+    add: Vec<u64>,
+    remove: Vec<u64>,
+    refused: Vec<u64>,
+}
+
+/// Role changes that turn the member's current choices into the wanted ones, following the panel's
+/// mode. Errors explain why the choice isn't allowed.
+fn plan(panel: &RolePanel, current: &[u64], wanted: &[u64]) -> Result<RoleChanges, String> {
+    let chosen: Vec<u64> = wanted.iter().filter(|r| !current.contains(r)).copied().collect();
+    let mut dropped: Vec<u64> = current.iter().filter(|r| !wanted.contains(r)).copied().collect();
+    if panel.mode == ReactionRoleMode::Unique {
+        if chosen.len() > 1 {
+            return Err("You can only choose one option on this panel.".to_string());
+        }
+        if !chosen.is_empty() {
+            // Choosing another option undoes the previous one
+            dropped = current.to_vec();
+        }
+    } else if let Some(max) = panel.max_selections {
+        let kept = current.len() - dropped.len() + chosen.len();
+        if kept > max as usize {
+            return Err(format!("You can choose at most {} option(s) on this panel.", max));
+        }
+    }
+    let mut changes = RoleChanges::default();
+    for (roles, reacted) in [(&chosen, true), (&dropped, false)] {
+        for role in roles {
+            match role_change(panel.mode, panel.reversed, reacted) {
+                Some(true) => changes.add.push(*role),
+                Some(false) => changes.remove.push(*role),
+                None => changes.refused.push(*role),
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Handles clicks and selections on posted role panels. Choosing on the posted menu only adds roles,
+/// the reply carries the member's own menu with their current choices for removing them.
+pub async fn handle_interaction(ctx: &serenity::Context, interaction: &serenity::Interaction, data: &crate::Data) -> Result<(), crate::Error> {
+    let serenity::Interaction::Component(mci) = interaction else { return Ok(()) };
+    let Some(id) = ComponentId::parse(&mci.data.custom_id).filter(|id| id.prefix == PREFIX) else { return Ok(()) };
+    let (Some(guild_id), Some(member)) = (mci.guild_id, mci.member.as_ref()) else { return Ok(()) };
+    let Some(panel_id) = id.arg::<u64>(0) else { return Ok(()) };
+    // The member's own menu is updated in place, everything else gets a new private reply
+    if id.action == "choose" {
+        mci.defer(ctx).await?;
+    } else {
+        mci.defer_ephemeral(ctx).await?;
+    }
+
+    let reply = |text: String| serenity::EditInteractionResponse::new().content(text).components(vec![]);
+    if !enabled(&data.db, guild_id.get(), Feature::ReactionRoles) {
+        mci.edit_response(ctx, reply("Role panels are turned off on this server.".to_string())).await?;
+        return Ok(());
+    }
+    let Some(panel) = RolePanelStore::open(&data.db)?.get(guild_id.get(), panel_id)? else {
+        mci.edit_response(ctx, reply("This panel no longer exists.".to_string())).await?;
+        return Ok(());
+    };
+    let mut holds: Vec<u64> = member.roles.iter().map(|r| r.get()).collect();
+    // An option counts as chosen when the member has its role, or lacks it on reversed panels
+    let chosen = |holds: &[u64]| -> Vec<u64> {
+        panel.options.iter().map(|o| o.role_id).filter(|r| holds.contains(r) != panel.reversed).collect()
+    };
+    let current = chosen(&holds);
+    let picked = |values: &[String]| -> Vec<u64> {
+        values
+            .iter()
+            .filter_map(|v| v.parse::<u64>().ok())
+            .filter(|r| panel.options.iter().any(|o| o.role_id == *r))
+            .collect()
+    };
+    let wanted: Vec<u64> = match (id.action.as_str(), &mci.data.kind) {
+        ("toggle", _) => {
+            let Some(role) = id.arg::<u64>(1).filter(|r| panel.options.iter().any(|o| o.role_id == *r)) else {
+                mci.edit_response(ctx, reply("That option was removed from the panel.".to_string())).await?;
+                return Ok(());
+            };
+            if current.contains(&role) {
+                current.iter().filter(|r| **r != role).copied().collect()
+            } else {
+                current.iter().copied().chain([role]).collect()
+            }
+        }
+        ("select", serenity::ComponentInteractionDataKind::StringSelect { values }) => {
+            let mut wanted = current.clone();
+            wanted.extend(picked(values).into_iter().filter(|r| !current.contains(r)));
+            wanted
+        }
+        ("choose", serenity::ComponentInteractionDataKind::StringSelect { values }) => picked(values),
+        _ => return Ok(()),
+    };
+
+    let mut lines = Vec::new();
+    match plan(&panel, &current, &wanted) {
+        Ok(RoleChanges { add, remove, refused }) => {
+            let applied = update_roles(&ctx.http, guild_id, mci.user.id, &add, &remove, REASON).await;
+            holds.retain(|r| !applied.removed.contains(r));
+            holds.extend(&applied.added);
+            if !applied.added.is_empty() {
+                lines.push(format!("Added {}.", mentions(&applied.added)));
+            }
+            if !applied.removed.is_empty() {
+                lines.push(format!("Removed {}.", mentions(&applied.removed)));
+            }
+            let failed: Vec<u64> = add
+                .iter()
+                .chain(&remove)
+                .filter(|r| !applied.added.contains(r) && !applied.removed.contains(r))
+                .copied()
+                .collect();
+            if !failed.is_empty() {
+                lines.push(format!("I couldn't change {}.", mentions(&failed)));
+            }
+            if !refused.is_empty() {
+                lines.push(format!("This panel doesn't let you change {}.", mentions(&refused)));
+            }
+            if lines.is_empty() {
+                lines.push("Nothing changed.".to_string());
+            }
+        }
+        Err(problem) => lines.push(problem),
+    }
+
+    let mut response = reply(lines.join("\n")).allowed_mentions(serenity::CreateAllowedMentions::new());
+    if panel.style == PanelStyle::Select {
+        let menu = select_menu(&panel, component_id(PREFIX, "choose", [panel.id]), &chosen(&holds));
+        response = response.components(vec![serenity::CreateActionRow::SelectMenu(menu)]);
+    }
+    mci.edit_response(ctx, response).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::role_panels::store::PanelOption;
+
+    fn panel(mode: ReactionRoleMode, reversed: bool, max_selections: Option<u32>) -> RolePanel {
+        RolePanel {
+            id: 1,
+            guild_id: 1,
+            title: "Roles".to_string(),
+            description: None,
+            style: PanelStyle::Select,
+            options: (1..=4)
+                .map(|r| PanelOption { role_id: r, label: r.to_string(), emoji: None, description: None })
+                .collect(),
+            mode,
+            reversed,
+            max_selections,
+            channel_id: None,
+            message_id: None,
+            created_by: 1,
+        }
+    }
+
+    fn changes(panel: &RolePanel, current: &[u64], wanted: &[u64]) -> (Vec<u64>, Vec<u64>, Vec<u64>) {
+        let c = plan(panel, current, wanted).unwrap();
+        (c.add, c.remove, c.refused)
+    }
+
+    #[test]
+    fn normal_adds_and_removes_the_difference() {
+        let p = panel(ReactionRoleMode::Normal, false, None);
+        assert_eq!(changes(&p, &[1, 2], &[2, 3]), (vec![3], vec![1], vec![]));
+        assert_eq!(changes(&p, &[1], &[1]), (vec![], vec![], vec![]));
+    }
+
+    #[test]
+    fn unique_replaces_the_previous_choice() {
+        let p = panel(ReactionRoleMode::Unique, false, None);
+        assert_eq!(changes(&p, &[1], &[1, 2]), (vec![2], vec![1], vec![]));
+        assert_eq!(changes(&p, &[1], &[]), (vec![], vec![1], vec![]));
+        assert!(plan(&p, &[], &[2, 3]).is_err());
+    }
+
+    #[test]
+    fn max_selections_counts_kept_choices() {
+        let p = panel(ReactionRoleMode::Normal, false, Some(2));
+        assert!(plan(&p, &[1, 2], &[1, 2, 3]).is_err());
+        assert_eq!(changes(&p, &[1, 2], &[1, 3]), (vec![3], vec![2], vec![]));
+    }
+
+    #[test]
+    fn verify_and_drop_refuse_one_direction() {
+        let verify = panel(ReactionRoleMode::Verify, false, None);
+        assert_eq!(changes(&verify, &[1], &[2]), (vec![2], vec![], vec![1]));
+        let drop = panel(ReactionRoleMode::Drop, false, None);
+        assert_eq!(changes(&drop, &[1], &[2]), (vec![], vec![2], vec![1]));
+    }
+
+    #[test]
+    fn reversed_panels_swap_adding_and_removing() {
+        let p = panel(ReactionRoleMode::Normal, true, None);
+        assert_eq!(changes(&p, &[1], &[2]), (vec![1], vec![2], vec![]));
+    }
+}
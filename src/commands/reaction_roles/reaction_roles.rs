@@ -1,3 +1,381 @@
 // Manges Reaction Roles.
 // reactionroles [<message url> [delete|reversed|roles [mode|add <role1> [role2] ..[remove] [role1] [role2]
-//...]|maxreacts <number|/>|add <emoji> <role1> [role2] ...[remove] <emoji> [role1] [role2] ...]
\ No newline at end of file
+//...]|maxreacts <number|/>|add <emoji> <role1> [role2] ...[remove] <emoji> [role1] [role2] ...]
+
+use super::reactions::{emoji_key, parse_emoji};
+use super::roles::{check_assignable, parse_role_ids};
+use crate::commands::moderation::cases::truncate;
+use crate::commands::moderation::utils::parse_message_link;
+use crate::data::reaction_roles::store::{ReactionRoleEntry, ReactionRoleMessage, ReactionRoleMode, ReactionRoleStore};
+use poise::serenity_prelude as serenity;
+
+// Discord allows this many different reactions on a message
+const MAX_EMOJIS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum ModeChoice {
+    Normal,
+    Unique,
+    Verify,
+    Drop,
+}
+
+impl From<ModeChoice> for ReactionRoleMode {
+    fn from(m: ModeChoice) -> Self {
+        match m {
+            ModeChoice::Normal => ReactionRoleMode::Normal,
+            ModeChoice::Unique => ReactionRoleMode::Unique,
+            ModeChoice::Verify => ReactionRoleMode::Verify,
+            ModeChoice::Drop => ReactionRoleMode::Drop,
+        }
+    }
+}
+
+fn mode_text(mode: ReactionRoleMode) -> &'static str {
+    match mode {
+        ReactionRoleMode::Normal => "normal (react to get, unreact to lose)",
+        ReactionRoleMode::Unique => "unique (one reaction at a time)",
+        ReactionRoleMode::Verify => "verify (roles can only be gained)",
+        ReactionRoleMode::Drop => "drop (roles can only be lost)",
+    }
+}
+
+fn role_mentions(roles: &[u64]) -> String {
+    if roles.is_empty() {
+        "none".to_string()
+    } else {
+        roles.iter().map(|r| format!("<@&{}>", r)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn message_link(message: &ReactionRoleMessage) -> String {
+    format!("https://discord.com/channels/{}/{}/{}", message.guild_id, message.channel_id, message.message_id)
+}
+
+/// Resolves a message link of this guild to its stored reaction roles, or a new empty set when
+/// `create` is true and the message exists. Replies with the problem and returns None otherwise.
+async fn load(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    link: &str,
+    create: bool,
+) -> Result<Option<(ReactionRoleStore, ReactionRoleMessage)>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let Some((link_guild, channel_id, message_id)) = parse_message_link(link).filter(|(g, _, _)| *g == guild_id.get()) else {
+        ctx.say("That's not a link to a message of this server.").await?;
+        return Ok(None);
+    };
+    let store = ReactionRoleStore::open(&ctx.data().db)?;
+    if let Some(existing) = store.get(link_guild, message_id)? {
+        return Ok(Some((store, existing)));
+    }
+    if !create {
+        ctx.say("That message has no reaction roles.").await?;
+        return Ok(None);
+    }
+    if serenity::ChannelId::new(channel_id).message(ctx, message_id).await.is_err() {
+        ctx.say("I can't find that message.").await?;
+        return Ok(None);
+    }
+    let message = ReactionRoleMessage {
+        guild_id: link_guild,
+        channel_id,
+        message_id,
+        entries: Vec::new(),
+        mode: ReactionRoleMode::Normal,
+        reversed: false,
+        max_reacts: None,
+        required_roles: Vec::new(),
+    };
+    Ok(Some((store, message)))
+}
+
+/// Parses a role list and checks the bot may hand the roles out. Replies with the problem otherwise.
+async fn roles_arg(ctx: poise::Context<'_, crate::Data, crate::Error>, input: &str, check: bool) -> Result<Option<Vec<u64>>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let (roles, invalid) = parse_role_ids(input);
+    if !invalid.is_empty() {
+        ctx.say(format!("Couldn't read `{}` as roles.", invalid.join("`, `"))).await?;
+        return Ok(None);
+    }
+    if check && let Err(problem) = check_assignable(ctx.serenity_context(), guild_id, &roles) {
+        ctx.say(problem).await?;
+        return Ok(None);
+    }
+    Ok(Some(roles))
+}
+
+/// Sets up roles that members get or lose by reacting to a message.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "reactionroles",
+    guild_only,
+    required_permissions = "MANAGE_ROLES",
+    subcommands("list", "delete", "reversed", "roles", "maxreacts", "add", "remove"),
+    subcommand_required
+)]
+pub async fn reaction_roles(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Lists the reaction role messages of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let messages = ReactionRoleStore::open(&ctx.data().db)?.list(guild_id.get())?;
+    if messages.is_empty() {
+        ctx.say("There are no reaction role messages.").await?;
+        return Ok(());
+    }
+    let mut embed = serenity::CreateEmbed::default().title("Reaction roles").color(0x8B5CF6);
+    for m in messages.iter().take(25) {
+        let mut text = format!("[Message]({}) in <#{}>\nMode: {}", message_link(m), m.channel_id, mode_text(m.mode));
+        if m.reversed {
+            text.push_str(", reversed");
+        }
+        if let Some(max) = m.max_reacts {
+            text.push_str(&format!("\nMax reactions: {}", max));
+        }
+        if !m.required_roles.is_empty() {
+            text.push_str(&format!("\nRequires: {}", role_mentions(&m.required_roles)));
+        }
+        for e in &m.entries {
+            text.push_str(&format!("\n{} -> {}", e.emoji, role_mentions(&e.roles)));
+        }
+        embed = embed.field(format!("Message {}", m.message_id), truncate(&text, 1024), false);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Removes all reaction roles from a message.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+) -> Result<(), crate::Error> {
+    let Some((store, existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    store.remove(existing.guild_id, existing.message_id)?;
+    ctx.say("Removed the reaction roles from that message. Members keep the roles they have.").await?;
+    Ok(())
+}
+
+/// Makes reacting take the roles and unreacting give them.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn reversed(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[description = "Whether the message is reversed"] enabled: bool,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    existing.reversed = enabled;
+    store.save(&existing)?;
+    ctx.say(if enabled { "Reacting now takes the roles." } else { "Reacting now gives the roles." }).await?;
+    Ok(())
+}
+
+/// Sets the mode of a message and the roles members need to use it.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_ROLES",
+    subcommands("roles_mode", "roles_add", "roles_remove"),
+    subcommand_required
+)]
+async fn roles(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Sets how reacting and unreacting change roles.
+#[poise::command(slash_command, prefix_command, rename = "mode", guild_only, required_permissions = "MANAGE_ROLES")]
+async fn roles_mode(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[description = "Mode"] mode: ModeChoice,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    existing.mode = mode.into();
+    store.save(&existing)?;
+    ctx.say(format!("Mode set to {}.", mode_text(existing.mode))).await?;
+    Ok(())
+}
+
+/// Only members with one of these roles may use the message.
+#[poise::command(slash_command, prefix_command, rename = "add", guild_only, required_permissions = "MANAGE_ROLES")]
+async fn roles_add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[rest]
+    #[description = "Required roles"]
+    roles: String,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    let Some(roles) = roles_arg(ctx, &roles, false).await? else { return Ok(()) };
+    for r in roles {
+        if !existing.required_roles.contains(&r) {
+            existing.required_roles.push(r);
+        }
+    }
+    store.save(&existing)?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("Members now need one of {} to use that message.", role_mentions(&existing.required_roles)))
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Removes required roles, or all of them when none are given.
+#[poise::command(slash_command, prefix_command, rename = "remove", guild_only, required_permissions = "MANAGE_ROLES")]
+async fn roles_remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[rest]
+    #[description = "Roles to stop requiring (empty = all)"]
+    roles: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    match roles.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        Some(input) => {
+            let Some(roles) = roles_arg(ctx, input, false).await? else { return Ok(()) };
+            existing.required_roles.retain(|r| !roles.contains(r));
+        }
+        None => existing.required_roles.clear(),
+    }
+    store.save(&existing)?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(if existing.required_roles.is_empty() {
+                "Everyone may use that message now.".to_string()
+            } else {
+                format!("Members now need one of {} to use that message.", role_mentions(&existing.required_roles))
+            })
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Limits how many emojis of a message a member may react with.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn maxreacts(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[description = "Maximum reactions per member (empty = no limit)"]
+    #[min = 1]
+    #[max = 20]
+    number: Option<u32>,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    existing.max_reacts = number.filter(|n| *n > 0);
+    store.save(&existing)?;
+    ctx.say(match existing.max_reacts {
+        Some(n) => format!("Members may react with at most {} emoji(s).", n),
+        None => "Members may react with every emoji.".to_string(),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Makes an emoji on a message hand out roles.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[description = "Emoji"] emoji: String,
+    #[rest]
+    #[description = "Roles the emoji stands for"]
+    roles: String,
+) -> Result<(), crate::Error> {
+    let Some(reaction) = parse_emoji(&emoji) else {
+        ctx.say(format!("`{}` is not an emoji.", emoji)).await?;
+        return Ok(());
+    };
+    let Some((store, mut existing)) = load(ctx, &message, true).await? else { return Ok(()) };
+    let Some(roles) = roles_arg(ctx, &roles, true).await? else { return Ok(()) };
+    if roles.is_empty() {
+        ctx.say("Name at least one role.").await?;
+        return Ok(());
+    }
+    let key = emoji_key(&reaction);
+    match existing.entries.iter_mut().find(|e| parse_emoji(&e.emoji).is_some_and(|r| emoji_key(&r) == key)) {
+        Some(entry) => {
+            for r in &roles {
+                if !entry.roles.contains(r) {
+                    entry.roles.push(*r);
+                }
+            }
+        }
+        None => {
+            if existing.entries.len() >= MAX_EMOJIS {
+                ctx.say(format!("A message can have at most {} reaction role emojis.", MAX_EMOJIS)).await?;
+                return Ok(());
+            }
+            existing.entries.push(ReactionRoleEntry { emoji: reaction.to_string(), roles: roles.clone(), reactors: Vec::new() });
+        }
+    }
+    store.save(&existing)?;
+    if store.config(existing.guild_id)?.autoreact {
+        let _ = ctx
+            .http()
+            .create_reaction(serenity::ChannelId::new(existing.channel_id), serenity::MessageId::new(existing.message_id), &reaction)
+            .await;
+    }
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("{} now stands for {}.", reaction, role_mentions(&roles)))
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Removes roles from an emoji, or the whole emoji when no roles are given.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Message link"] message: String,
+    #[description = "Emoji"] emoji: String,
+    #[rest]
+    #[description = "Roles to remove (empty = the whole emoji)"]
+    roles: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(reaction) = parse_emoji(&emoji) else {
+        ctx.say(format!("`{}` is not an emoji.", emoji)).await?;
+        return Ok(());
+    };
+    let Some((store, mut existing)) = load(ctx, &message, false).await? else { return Ok(()) };
+    let key = emoji_key(&reaction);
+    let Some(index) = existing.entries.iter().position(|e| parse_emoji(&e.emoji).is_some_and(|r| emoji_key(&r) == key)) else {
+        ctx.say(format!("{} has no roles on that message.", reaction)).await?;
+        return Ok(());
+    };
+    let whole = match roles.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+        Some(input) => {
+            let Some(roles) = roles_arg(ctx, input, false).await? else { return Ok(()) };
+            existing.entries[index].roles.retain(|r| !roles.contains(r));
+            existing.entries[index].roles.is_empty()
+        }
+        None => true,
+    };
+    if whole {
+        existing.entries.remove(index);
+        // The bot's own reaction would otherwise invite members to use a dead emoji
+        let bot_id = ctx.cache().current_user().id;
+        let _ = ctx
+            .http()
+            .delete_reaction(
+                serenity::ChannelId::new(existing.channel_id),
+                serenity::MessageId::new(existing.message_id),
+                bot_id,
+                &reaction,
+            )
+            .await;
+    }
+    store.save(&existing)?;
+    ctx.say(if whole { format!("Removed {} from that message.", reaction) } else { format!("Updated the roles of {}.", reaction) })
+        .await?;
+    Ok(())
+}
@@ -1,2 +1,53 @@
 // Configures Reaction Roles.
-// reactionrolesconfig [autoreact]
\ No newline at end of file
+// reactionrolesconfig [autoreact]
+
+use super::reactions::parse_emoji;
+use crate::data::reaction_roles::store::ReactionRoleStore;
+use poise::serenity_prelude as serenity;
+
+/// Configures reaction roles for this server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "reactionrolesconfig",
+    guild_only,
+    required_permissions = "MANAGE_ROLES",
+    subcommands("autoreact"),
+    subcommand_required
+)]
+pub async fn reaction_roles_config(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Makes the bot add every reaction role emoji to its message.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn autoreact(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Whether the bot reacts with the configured emojis"] enabled: bool,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = ReactionRoleStore::open(&ctx.data().db)?;
+    let mut config = store.config(guild_id.get())?;
+    config.autoreact = enabled;
+    store.set_config(guild_id.get(), &config)?;
+    if !enabled {
+        ctx.say("The bot no longer adds reaction role emojis. Existing reactions stay.").await?;
+        return Ok(());
+    }
+    ctx.defer().await?;
+    let mut added = 0;
+    for message in store.list(guild_id.get())? {
+        for entry in &message.entries {
+            let Some(emoji) = parse_emoji(&entry.emoji) else { continue };
+            let result = ctx
+                .http()
+                .create_reaction(serenity::ChannelId::new(message.channel_id), serenity::MessageId::new(message.message_id), &emoji)
+                .await;
+            if result.is_ok() {
+                added += 1;
+            }
+        }
+    }
+    ctx.say(format!("The bot now adds reaction role emojis to their messages ({} reaction(s) checked).", added)).await?;
+    Ok(())
+}
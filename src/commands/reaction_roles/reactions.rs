@@ -0,0 +1,329 @@
+// Applies reaction roles when members react or unreact, and catches up on reactions that changed
+// while the bot was offline.
+
+use super::roles::update_roles;
+use crate::commands::general::features::{enabled, Feature};
+use crate::data::reaction_roles::store::{ReactionRoleEntry, ReactionRoleMessage, ReactionRoleMode, ReactionRoleStore};
+use poise::serenity_prelude as serenity;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+const REASON: &str = "Reaction role";
+// Discord error code of a deleted message
+const UNKNOWN_MESSAGE: isize = 10008;
+
+/// Reactions the bot removed itself (maxreacts, unique mode, missing required roles). Their remove
+/// event must not change roles again.
+#[derive(Default, Clone)]
+pub struct BotRemovals {
+    pending: Arc<Mutex<HashSet<(u64, u64, String)>>>,
+}
+
+impl BotRemovals {
+    fn mark(&self, message_id: u64, user_id: u64, key: &str) {
+        self.pending.lock().unwrap().insert((message_id, user_id, key.to_string()));
+    }
+
+    fn take(&self, message_id: u64, user_id: u64, key: &str) -> bool {
+        self.pending.lock().unwrap().remove(&(message_id, user_id, key.to_string()))
+    }
+}
+
+/// Compares emojis: the ID for custom emojis, the text without variation selectors for unicode ones.
+pub(crate) fn emoji_key(emoji: &serenity::ReactionType) -> String {
+    match emoji {
+        serenity::ReactionType::Custom { id, .. } => id.to_string(),
+        serenity::ReactionType::Unicode(s) => s.replace('\u{fe0f}', ""),
+        _ => String::new(),
+    }
+}
+
+/// Parses an emoji written in a message. Plain words are rejected so typos don't become "emojis".
+pub(crate) fn parse_emoji(input: &str) -> Option<serenity::ReactionType> {
+    let input = input.trim();
+    // Keycaps like 1️⃣ start with a digit, so only all-alphanumeric input counts as a word
+    if !input.starts_with('<') && input.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    serenity::ReactionType::try_from(input).ok()
+}
+
+fn entry_key(entry: &ReactionRoleEntry) -> String {
+    parse_emoji(&entry.emoji).map(|e| emoji_key(&e)).unwrap_or_default()
+}
+
+/// Whether a reaction (or its removal) gives the roles (true), takes them (false) or does nothing.
+/// Role panels use the same rules, with choosing an option as the reaction.
+pub(crate) fn role_change(mode: ReactionRoleMode, reversed: bool, reacted: bool) -> Option<bool> {
+    let give = match (mode, reacted) {
+        (ReactionRoleMode::Normal | ReactionRoleMode::Unique, r) => r,
+        (ReactionRoleMode::Verify, true) => true,
+        (ReactionRoleMode::Drop, true) => false,
+        (ReactionRoleMode::Verify | ReactionRoleMode::Drop, false) => return None,
+    };
+    Some(give != reversed)
+}
+
+async fn apply(ctx: &serenity::Context, message: &ReactionRoleMessage, roles: &[u64], user_id: serenity::UserId, reacted: bool) {
+    let guild_id = serenity::GuildId::new(message.guild_id);
+    match role_change(message.mode, message.reversed, reacted) {
+        Some(true) => {
+            update_roles(&ctx.http, guild_id, user_id, roles, &[], REASON).await;
+        }
+        Some(false) => {
+            update_roles(&ctx.http, guild_id, user_id, &[], roles, REASON).await;
+        }
+        None => {}
+    }
+}
+
+async fn remove_reaction(ctx: &serenity::Context, message: &ReactionRoleMessage, user_id: serenity::UserId, emoji: &serenity::ReactionType, removals: &BotRemovals) {
+    let key = emoji_key(emoji);
+    removals.mark(message.message_id, user_id.get(), &key);
+    let removed = ctx
+        .http
+        .delete_reaction(
+            serenity::ChannelId::new(message.channel_id),
+            serenity::MessageId::new(message.message_id),
+            user_id,
+            emoji,
+        )
+        .await;
+    // No remove event follows, and a later removal by the member must still count
+    if removed.is_err() {
+        removals.take(message.message_id, user_id.get(), &key);
+    }
+}
+
+/// Gives the roles of a new reaction, which the member was already recorded for, once it passes the
+/// required roles, Unique mode and maxreacts. Reactions that don't pass are removed again. The
+/// reactors of `message` are updated, the caller saves it.
+async fn react(
+    ctx: &serenity::Context,
+    message: &mut ReactionRoleMessage,
+    index: usize,
+    member: &serenity::Member,
+    emoji: &serenity::ReactionType,
+    removals: &BotRemovals,
+) {
+    let user_id = member.user.id;
+    if !message.required_roles.is_empty() && !member.roles.iter().any(|r| message.required_roles.contains(&r.get())) {
+        message.entries[index].reactors.retain(|u| *u != user_id.get());
+        remove_reaction(ctx, message, user_id, emoji, removals).await;
+        return;
+    }
+    let others: Vec<usize> = (0..message.entries.len())
+        .filter(|i| *i != index && message.entries[*i].reactors.contains(&user_id.get()))
+        .collect();
+    if message.mode == ReactionRoleMode::Unique {
+        // Only the newest reaction stays, the others are undone as if the member unreacted
+        for i in others {
+            message.entries[i].reactors.retain(|u| *u != user_id.get());
+            let entry = message.entries[i].clone();
+            if let Some(other) = parse_emoji(&entry.emoji) {
+                remove_reaction(ctx, message, user_id, &other, removals).await;
+            }
+            apply(ctx, message, &entry.roles, user_id, false).await;
+        }
+    } else if message.max_reacts.is_some_and(|max| others.len() as u32 >= max) {
+        message.entries[index].reactors.retain(|u| *u != user_id.get());
+        remove_reaction(ctx, message, user_id, emoji, removals).await;
+        return;
+    }
+    let roles = message.entries[index].roles.clone();
+    apply(ctx, message, &roles, user_id, true).await;
+}
+
+/// Handles a reaction being added to or removed from a reaction role message.
+pub async fn handle_reaction(ctx: &serenity::Context, reaction: &serenity::Reaction, added: bool, data: &crate::Data) -> Result<(), crate::Error> {
+    let (Some(guild_id), Some(user_id)) = (reaction.guild_id, reaction.user_id) else { return Ok(()) };
+    if user_id == ctx.cache.current_user().id || !enabled(&data.db, guild_id.get(), Feature::ReactionRoles) {
+        return Ok(());
+    }
+    let store = ReactionRoleStore::open(&data.db)?;
+    let Some(mut message) = store.get(guild_id.get(), reaction.message_id.get())? else { return Ok(()) };
+    let key = emoji_key(&reaction.emoji);
+    let Some(index) = message.entries.iter().position(|e| entry_key(e) == key) else { return Ok(()) };
+
+    let reactors = &mut message.entries[index].reactors;
+    if added {
+        if !reactors.contains(&user_id.get()) {
+            reactors.push(user_id.get());
+        }
+    } else {
+        reactors.retain(|u| *u != user_id.get());
+    }
+    store.save(&message)?;
+    if !added {
+        if !data.reaction_removals.take(message.message_id, user_id.get(), &key) {
+            let roles = message.entries[index].roles.clone();
+            apply(ctx, &message, &roles, user_id, false).await;
+        }
+        return Ok(());
+    }
+
+    let member = match &reaction.member {
+        Some(m) => m.clone(),
+        None => guild_id.member(ctx, user_id).await?,
+    };
+    if member.user.bot {
+        return Ok(());
+    }
+    react(ctx, &mut message, index, &member, &reaction.emoji, &data.reaction_removals).await;
+    store.save(&message)?;
+    Ok(())
+}
+
+/// Forgets the reactors of a message or one emoji after a moderator cleared its reactions. Roles stay
+/// as they are, clearing reactions isn't the members unreacting.
+pub async fn handle_clear(db: &sled::Db, message_id: u64, emoji: Option<&serenity::ReactionType>) -> Result<(), crate::Error> {
+    let store = ReactionRoleStore::open(db)?;
+    let Some(mut message) = store.all()?.into_iter().find(|m| m.message_id == message_id) else { return Ok(()) };
+    let key = emoji.map(emoji_key);
+    for entry in &mut message.entries {
+        if key.as_ref().is_none_or(|k| *k == entry_key(entry)) {
+            entry.reactors.clear();
+        }
+    }
+    store.save(&message)?;
+    Ok(())
+}
+
+/// Every non-bot user reacting with an emoji, fetched page by page.
+async fn reaction_users(ctx: &serenity::Context, message: &ReactionRoleMessage, emoji: &serenity::ReactionType) -> Result<(Vec<u64>, bool), serenity::Error> {
+    let mut users = Vec::new();
+    let mut bot_reacted = false;
+    let mut after = None;
+    let bot_id = ctx.cache.current_user().id;
+    loop {
+        let page = ctx
+            .http
+            .get_reaction_users(
+                serenity::ChannelId::new(message.channel_id),
+                serenity::MessageId::new(message.message_id),
+                emoji,
+                100,
+                after,
+            )
+            .await?;
+        let full = page.len() == 100;
+        after = page.last().map(|u| u.id.get());
+        for u in page {
+            if u.id == bot_id {
+                bot_reacted = true;
+            } else if !u.bot {
+                users.push(u.id.get());
+            }
+        }
+        if !full {
+            break;
+        }
+    }
+    Ok((users, bot_reacted))
+}
+
+/// Applies reactions added or removed while the bot was offline, comparing the current reactions
+/// with the reactors saved from the last events. New reactions go through the same checks as live
+/// ones. Also adds missing bot reactions when autoreact is on.
+pub async fn reconcile(ctx: serenity::Context, db: sled::Db, removals: BotRemovals) {
+    if let Err(e) = reconcile_all(&ctx, &db, &removals).await {
+        eprintln!("reaction role reconcile failed: {}", e);
+    }
+}
+
+async fn reconcile_all(ctx: &serenity::Context, db: &sled::Db, removals: &BotRemovals) -> Result<(), crate::Error> {
+    let store = ReactionRoleStore::open(db)?;
+    'messages: for mut message in store.all()? {
+        // Reactions in guilds that turned the feature off are caught up on once it is back on
+        if !enabled(db, message.guild_id, Feature::ReactionRoles) {
+            continue;
+        }
+        let autoreact = store.config(message.guild_id)?.autoreact;
+        // The current reactors of each entry, None where they couldn't be read
+        let mut current: Vec<Option<Vec<u64>>> = Vec::new();
+        for i in 0..message.entries.len() {
+            let Some(emoji) = parse_emoji(&message.entries[i].emoji) else {
+                current.push(None);
+                continue;
+            };
+            match reaction_users(ctx, &message, &emoji).await {
+                Ok((users, bot_reacted)) => {
+                    if autoreact && !bot_reacted {
+                        let _ = ctx
+                            .http
+                            .create_reaction(serenity::ChannelId::new(message.channel_id), serenity::MessageId::new(message.message_id), &emoji)
+                            .await;
+                    }
+                    current.push(Some(users));
+                }
+                Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))) if r.error.code == UNKNOWN_MESSAGE => {
+                    store.remove(message.guild_id, message.message_id)?;
+                    continue 'messages;
+                }
+                Err(e) => {
+                    eprintln!("failed to read reactions of message {}: {}", message.message_id, e);
+                    current.push(None);
+                }
+            }
+        }
+
+        let saved: Vec<Vec<u64>> = message.entries.iter().map(|e| e.reactors.clone()).collect();
+        // Removals first, so maxreacts and Unique mode only count reactions that are still there
+        let mut added: Vec<(usize, u64)> = Vec::new();
+        for (i, users) in current.iter().enumerate() {
+            let Some(users) = users else { continue };
+            let roles = message.entries[i].roles.clone();
+            let removed: Vec<u64> = message.entries[i].reactors.iter().filter(|u| !users.contains(u)).copied().collect();
+            for user in removed {
+                apply(ctx, &message, &roles, serenity::UserId::new(user), false).await;
+            }
+            message.entries[i].reactors.retain(|u| users.contains(u));
+            added.extend(users.iter().filter(|u| !message.entries[i].reactors.contains(u)).map(|u| (i, *u)));
+        }
+        let guild_id = serenity::GuildId::new(message.guild_id);
+        for (i, user) in added {
+            let Some(emoji) = parse_emoji(&message.entries[i].emoji) else { continue };
+            // Members that left are picked up again once they are back
+            let Ok(member) = guild_id.member(ctx, serenity::UserId::new(user)).await else { continue };
+            message.entries[i].reactors.push(user);
+            react(ctx, &mut message, i, &member, &emoji, removals).await;
+        }
+        if message.entries.iter().map(|e| &e.reactors).ne(saved.iter()) {
+            store.save(&message)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keycaps_are_emojis() {
+        for keycap in ["1\u{fe0f}\u{20e3}", "#\u{fe0f}\u{20e3}", "*\u{fe0f}\u{20e3}", "\u{1f51f}"] {
+            assert!(parse_emoji(keycap).is_some(), "{} was rejected", keycap);
+        }
+    }
+
+    #[test]
+    fn keycaps_match_without_variation_selector() {
+        let typed = parse_emoji("1\u{fe0f}\u{20e3}").unwrap();
+        let reacted = serenity::ReactionType::Unicode("1\u{20e3}".to_string());
+        assert_eq!(emoji_key(&typed), emoji_key(&reacted));
+        assert_ne!(emoji_key(&typed), emoji_key(&parse_emoji("2\u{fe0f}\u{20e3}").unwrap()));
+    }
+
+    #[test]
+    fn plain_words_are_rejected() {
+        for word in ["abc", "1", "Role2", ""] {
+            assert!(parse_emoji(word).is_none(), "{:?} was accepted", word);
+        }
+    }
+
+    #[test]
+    fn custom_emojis_parse() {
+        let emoji = parse_emoji("<:party:123456789012345678>").unwrap();
+        assert_eq!(emoji_key(&emoji), "123456789012345678");
+    }
+}
@@ -0,0 +1,294 @@
+// Builds role panels: messages with buttons or a select menu that hand out roles.
+// rolepanel create <buttons|select> <title> [description]
+// rolepanel <add|remove> <panel id> <role> ...
+// rolepanel <mode|reversed|maxselections> <panel id> <value>
+// rolepanel post <panel id> <channel>
+// rolepanel <list|delete> [panel id]
+
+use super::panels::{panel_message, refresh};
+use super::reaction_roles::ModeChoice;
+use super::reactions::parse_emoji;
+use super::roles::check_assignable;
+use crate::commands::moderation::cases::truncate;
+use crate::data::reaction_roles::store::ReactionRoleMode;
+use crate::data::role_panels::store::{PanelOption, PanelStyle, RolePanel, RolePanelStore};
+use poise::serenity_prelude as serenity;
+
+// Five rows of five buttons, or the options of one select menu
+const MAX_OPTIONS: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum StyleChoice {
+    Buttons,
+    Select,
+}
+
+/// Loads a panel of this guild. Replies and returns None when it doesn't exist.
+async fn load(ctx: poise::Context<'_, crate::Data, crate::Error>, id: u64) -> Result<Option<(RolePanelStore, RolePanel)>, crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(None) };
+    let store = RolePanelStore::open(&ctx.data().db)?;
+    match store.get(guild_id.get(), id)? {
+        Some(panel) => Ok(Some((store, panel))),
+        None => {
+            ctx.say(format!("Role panel #{} does not exist.", id)).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Saves a panel and updates its posted message. Returns a note when the message couldn't be updated.
+async fn save(ctx: poise::Context<'_, crate::Data, crate::Error>, store: &RolePanelStore, panel: &RolePanel) -> Result<String, crate::Error> {
+    store.save(panel)?;
+    Ok(match refresh(ctx.http(), panel).await {
+        Ok(()) => String::new(),
+        Err(_) => " The posted panel couldn't be updated, post it again.".to_string(),
+    })
+}
+
+/// Builds messages with buttons or a select menu that hand out roles.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "rolepanel",
+    guild_only,
+    required_permissions = "MANAGE_ROLES",
+    subcommands("create", "add", "remove", "mode", "reversed", "maxselections", "post", "list", "delete"),
+    subcommand_required
+)]
+pub async fn role_panel(_ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+/// Creates an empty panel. Add roles to it, then post it.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn create(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Buttons or a select menu"] style: StyleChoice,
+    #[description = "Title"] title: String,
+    #[rest]
+    #[description = "Text shown above the options"]
+    description: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let store = RolePanelStore::open(&ctx.data().db)?;
+    let panel = RolePanel {
+        id: store.next_id(guild_id.get())?,
+        guild_id: guild_id.get(),
+        title: truncate(&title, 256),
+        description: description.map(|d| truncate(&d, 4000)).filter(|d| !d.is_empty()),
+        style: match style {
+            StyleChoice::Buttons => PanelStyle::Buttons,
+            StyleChoice::Select => PanelStyle::Select,
+        },
+        options: Vec::new(),
+        mode: ReactionRoleMode::Normal,
+        reversed: false,
+        max_selections: None,
+        channel_id: None,
+        message_id: None,
+        created_by: ctx.author().id.get(),
+    };
+    store.save(&panel)?;
+    ctx.say(format!("Created role panel #{}. Add roles with `rolepanel add {} <role>`, then post it.", panel.id, panel.id))
+        .await?;
+    Ok(())
+}
+
+/// Adds a role to a panel, or updates how an existing one is shown.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn add(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Role"] role: serenity::Role,
+    #[description = "Label (defaults to the role name)"] label: Option<String>,
+    #[description = "Emoji"] emoji: Option<String>,
+    #[rest]
+    #[description = "Description, shown in select menus"]
+    description: Option<String>,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if let Err(problem) = check_assignable(ctx.serenity_context(), guild_id, &[role.id.get()]) {
+        ctx.say(problem).await?;
+        return Ok(());
+    }
+    if let Some(e) = emoji.as_deref().filter(|e| parse_emoji(e).is_none()) {
+        ctx.say(format!("`{}` is not an emoji.", e)).await?;
+        return Ok(());
+    }
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    let option = PanelOption {
+        role_id: role.id.get(),
+        label: truncate(label.as_deref().unwrap_or(&role.name), 80),
+        emoji,
+        description: description.map(|d| truncate(&d, 100)).filter(|d| !d.is_empty()),
+    };
+    match existing.options.iter().position(|o| o.role_id == option.role_id) {
+        Some(i) => existing.options[i] = option,
+        None if existing.options.len() >= MAX_OPTIONS => {
+            ctx.say(format!("A panel can have at most {} roles.", MAX_OPTIONS)).await?;
+            return Ok(());
+        }
+        None => existing.options.push(option),
+    }
+    let note = save(ctx, &store, &existing).await?;
+    ctx.send(
+        poise::CreateReply::default()
+            .content(format!("Panel #{} now offers <@&{}>.{}", existing.id, role.id, note))
+            .allowed_mentions(serenity::CreateAllowedMentions::new()),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Removes a role from a panel.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn remove(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Role"] role: serenity::Role,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    let before = existing.options.len();
+    existing.options.retain(|o| o.role_id != role.id.get());
+    if existing.options.len() == before {
+        ctx.say("That role is not on the panel.").await?;
+        return Ok(());
+    }
+    let note = save(ctx, &store, &existing).await?;
+    ctx.say(format!("Removed the role from panel #{}.{}", existing.id, note)).await?;
+    Ok(())
+}
+
+/// Sets how choosing and unchoosing options change roles, like reaction role modes.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn mode(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Mode"] mode: ModeChoice,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    existing.mode = mode.into();
+    let note = save(ctx, &store, &existing).await?;
+    ctx.say(format!("Panel #{} now uses the {:?} mode.{}", existing.id, mode, note)).await?;
+    Ok(())
+}
+
+/// Makes choosing an option take its role and unchoosing give it back.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn reversed(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Whether the panel is reversed"] enabled: bool,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    existing.reversed = enabled;
+    let note = save(ctx, &store, &existing).await?;
+    ctx.say(format!(
+        "Choosing an option on panel #{} now {} its role.{}",
+        existing.id,
+        if enabled { "takes" } else { "gives" },
+        note
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Limits how many options of a panel a member may choose.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn maxselections(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Maximum options per member (empty = no limit)"]
+    #[min = 1]
+    #[max = 25]
+    number: Option<u32>,
+) -> Result<(), crate::Error> {
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    existing.max_selections = number.filter(|n| *n > 0);
+    let note = save(ctx, &store, &existing).await?;
+    ctx.say(match existing.max_selections {
+        Some(n) => format!("Members may choose at most {} option(s) on panel #{}.{}", n, existing.id, note),
+        None => format!("Members may choose every option on panel #{}.{}", existing.id, note),
+    })
+    .await?;
+    Ok(())
+}
+
+/// Posts a panel to a channel. A panel posted elsewhere before is moved.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn post(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+    #[description = "Channel to post in"] channel: serenity::GuildChannel,
+) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    if channel.guild_id != guild_id {
+        ctx.say("Pick a channel of this server.").await?;
+        return Ok(());
+    }
+    let Some((store, mut existing)) = load(ctx, panel).await? else { return Ok(()) };
+    if existing.options.is_empty() {
+        ctx.say("Add at least one role before posting the panel.").await?;
+        return Ok(());
+    }
+    if let (Some(old_channel), Some(old_message)) = (existing.channel_id, existing.message_id) {
+        let _ = serenity::ChannelId::new(old_channel).delete_message(ctx, old_message).await;
+    }
+    let (embed, components) = panel_message(&existing);
+    let msg = channel
+        .id
+        .send_message(ctx, serenity::CreateMessage::new().embed(embed).components(components))
+        .await?;
+    existing.channel_id = Some(channel.id.get());
+    existing.message_id = Some(msg.id.get());
+    store.save(&existing)?;
+    ctx.say(format!("Posted role panel #{} in <#{}>.", existing.id, channel.id)).await?;
+    Ok(())
+}
+
+/// Lists the role panels of this server.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn list(ctx: poise::Context<'_, crate::Data, crate::Error>) -> Result<(), crate::Error> {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let panels = RolePanelStore::open(&ctx.data().db)?.list(guild_id.get())?;
+    if panels.is_empty() {
+        ctx.say("There are no role panels.").await?;
+        return Ok(());
+    }
+    let mut embed = serenity::CreateEmbed::default().title("Role panels").color(0x8B5CF6);
+    for p in panels.iter().take(25) {
+        let place = match (p.channel_id, p.message_id) {
+            (Some(c), Some(m)) => format!("[Posted](https://discord.com/channels/{}/{}/{})", p.guild_id, c, m),
+            _ => "Not posted".to_string(),
+        };
+        let mut text = format!("{} - {:?}, {:?} mode", place, p.style, p.mode);
+        if p.reversed {
+            text.push_str(", reversed");
+        }
+        if let Some(max) = p.max_selections {
+            text.push_str(&format!(", max {}", max));
+        }
+        if !p.options.is_empty() {
+            text.push('\n');
+            text.push_str(&p.options.iter().map(|o| format!("<@&{}>", o.role_id)).collect::<Vec<_>>().join(" "));
+        }
+        embed = embed.field(format!("#{} {}", p.id, truncate(&p.title, 200)), truncate(&text, 1024), false);
+    }
+    ctx.send(poise::CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Deletes a panel and its posted message. Members keep their roles.
+#[poise::command(slash_command, prefix_command, guild_only, required_permissions = "MANAGE_ROLES")]
+async fn delete(
+    ctx: poise::Context<'_, crate::Data, crate::Error>,
+    #[description = "Panel ID"] panel: u64,
+) -> Result<(), crate::Error> {
+    let Some((store, existing)) = load(ctx, panel).await? else { return Ok(()) };
+    if let (Some(channel), Some(message)) = (existing.channel_id, existing.message_id) {
+        let _ = serenity::ChannelId::new(channel).delete_message(ctx, message).await;
+    }
+    store.remove(existing.guild_id, existing.id)?;
+    ctx.say(format!("Deleted role panel #{}.", existing.id)).await?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+// Shared helpers for features that hand out roles: parsing role lists, checking the bot may assign
+// them and applying the changes.
+
+use poise::serenity_prelude as serenity;
+
+/// Extracts role IDs from mentions (`<@&id>`) and raw IDs separated by whitespace or commas.
+/// Returns the IDs in input order without duplicates, plus every token that could not be parsed.
+pub(crate) fn parse_role_ids(input: &str) -> (Vec<u64>, Vec<String>) {
+    let mut ids: Vec<u64> = Vec::new();
+    let mut invalid: Vec<String> = Vec::new();
+    for token in input.split(|c: char| c.is_whitespace() || c == ',') {
+        let t = token.trim();
+        if t.is_empty() { continue; }
+        let inner = t.strip_prefix("<@&").and_then(|r| r.strip_suffix('>')).unwrap_or(t);
+        match inner.parse::<u64>() {
+            Ok(id) => {
+                if !ids.contains(&id) { ids.push(id); }
+            }
+            Err(_) => invalid.push(t.to_string()),
+        }
+    }
+    (ids, invalid)
+}
+
+/// Checks that the bot can hand out the roles: they exist, aren't @everyone or managed by an
+/// integration, and sit below the bot's highest role. The error explains the first problem found.
+pub(crate) fn check_assignable(ctx: &serenity::Context, guild_id: serenity::GuildId, roles: &[u64]) -> Result<(), String> {
+    let bot_id = ctx.cache.current_user().id;
+    let Some(guild) = ctx.cache.guild(guild_id) else { return Err("The server isn't cached yet, try again in a moment.".to_string()) };
+    let bot_top = guild
+        .members
+        .get(&bot_id)
+        .map(|m| m.roles.iter().filter_map(|r| guild.roles.get(r)).map(|r| r.position).max().unwrap_or(0))
+        .unwrap_or(0);
+    for id in roles {
+        let Some(role) = guild.roles.get(&serenity::RoleId::new(*id)) else {
+            return Err(format!("`{}` is not a role of this server.", id));
+        };
+        if role.id.get() == guild_id.get() {
+            return Err("@everyone can't be handed out.".to_string());
+        }
+        if role.managed {
+            return Err(format!("<@&{}> is managed by an integration.", id));
+        }
+        if role.position >= bot_top {
+            return Err(format!("<@&{}> is not below my highest role.", id));
+        }
+    }
+    Ok(())
+}
+
+/// The role changes `update_roles` got through.
+#[derive(Default)]
+pub(crate) struct AppliedRoles {
+    pub added: Vec<u64>,
+    pub removed: Vec<u64>,
+}
+
+/// Adds and removes roles of a member. Failures are printed and don't stop the other changes.
+pub(crate) async fn update_roles(
+    http: &serenity::Http,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    add: &[u64],
+    remove: &[u64],
+    reason: &str,
+) -> AppliedRoles {
+    let mut applied = AppliedRoles::default();
+    for role in add {
+        match http.add_member_role(guild_id, user_id, serenity::RoleId::new(*role), Some(reason)).await {
+            Ok(()) => applied.added.push(*role),
+            Err(e) => eprintln!("failed to add role {} to {} in guild {}: {}", role, user_id, guild_id, e),
+        }
+    }
+    for role in remove {
+        match http.remove_member_role(guild_id, user_id, serenity::RoleId::new(*role), Some(reason)).await {
+            Ok(()) => applied.removed.push(*role),
+            Err(e) => eprintln!("failed to remove role {} from {} in guild {}: {}", role, user_id, guild_id, e),
+        }
+    }
+    applied
+}
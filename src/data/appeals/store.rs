@@ -0,0 +1,174 @@
+use crate::data::{next_counter, pair_key};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppealStatus {
+    Open,
+    Accepted,
+    Denied,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Appeal {
+    pub id: u64,
+    pub guild_id: u64,
+    pub user_id: u64,
+    #[serde(default)]
+    pub user_name: String,
+    #[serde(default)]
+    pub case_id: Option<u64>,
+    pub answers: Vec<(String, String)>, // (question, answer) pairs from the submission modal
+    pub status: AppealStatus,
+    pub created_at: i64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    #[serde(default)]
+    pub thread_id: Option<u64>,
+    #[serde(default)]
+    pub resolved_by: Option<u64>,
+    #[serde(default)]
+    pub resolved_at: Option<i64>,
+    #[serde(default)]
+    pub resolution_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AppealBlock {
+    pub until: Option<i64>, // None = until unblocked
+    pub reason: Option<String>,
+    pub blocked_by: u64,
+    pub blocked_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AppealConfig {
+    pub channel_id: u64,
+}
+
+/// Ban appeals, appeal blocks and the appeal channel of each guild, stored in the shared bot database.
+pub struct AppealStore {
+    appeals: sled::Tree,
+    counters: sled::Tree,
+    open_by_user: sled::Tree,
+    blocks: sled::Tree,
+    config: sled::Tree,
+}
+
+impl AppealStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            appeals: db.open_tree("appeals")?,
+            counters: db.open_tree("appeal_counters")?,
+            open_by_user: db.open_tree("appeal_open_by_user")?,
+            blocks: db.open_tree("appeal_blocks")?,
+            config: db.open_tree("appeal_config")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<Option<AppealConfig>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: Option<&AppealConfig>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match config {
+            Some(c) => { self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(c)?)?; }
+            None => { self.config.remove(guild_id.to_be_bytes())?; }
+        }
+        self.config.flush()?;
+        Ok(())
+    }
+
+    /// Guilds that have an appeal channel configured.
+    pub fn configured_guilds(&self) -> Result<Vec<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.config.iter() {
+            let (k, _) = item?;
+            out.push(u64::from_be_bytes(k.as_ref().try_into()?));
+        }
+        Ok(out)
+    }
+
+    pub fn next_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "appeal")
+    }
+
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<Appeal>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.appeals.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Inserts or replaces an appeal and keeps the open-appeal index per user in sync.
+    pub fn save(&self, appeal: &Appeal) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.appeals.insert(pair_key(appeal.guild_id, appeal.id), serde_json::to_vec(appeal)?)?;
+        let idx = pair_key(appeal.user_id, appeal.guild_id);
+        if appeal.status == AppealStatus::Open {
+            self.open_by_user.insert(idx, &appeal.id.to_be_bytes())?;
+        } else {
+            self.open_by_user.remove(idx)?;
+        }
+        self.appeals.flush()?;
+        Ok(())
+    }
+
+    pub fn list(&self, guild_id: u64) -> Result<Vec<Appeal>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.appeals.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    /// The open appeal of a user in a guild, if any.
+    pub fn open_for(&self, user_id: u64, guild_id: u64) -> Result<Option<Appeal>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.open_by_user.get(pair_key(user_id, guild_id))? {
+            Some(v) => self.get(guild_id, u64::from_be_bytes(v.as_ref().try_into()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every open appeal of a user across all guilds.
+    pub fn open_for_user(&self, user_id: u64) -> Result<Vec<Appeal>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.open_by_user.scan_prefix(user_id.to_be_bytes()) {
+            let (k, v) = item?;
+            let guild_id = u64::from_be_bytes(k[8..16].try_into()?);
+            if let Some(a) = self.get(guild_id, u64::from_be_bytes(v.as_ref().try_into()?))? {
+                out.push(a);
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn find_by_thread(&self, guild_id: u64, thread_id: u64) -> Result<Option<Appeal>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.list(guild_id)?.into_iter().find(|a| a.thread_id == Some(thread_id)))
+    }
+
+    pub fn block(&self, guild_id: u64, user_id: u64, block: &AppealBlock) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.blocks.insert(pair_key(guild_id, user_id), serde_json::to_vec(block)?)?;
+        self.blocks.flush()?;
+        Ok(())
+    }
+
+    pub fn unblock(&self, guild_id: u64, user_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.blocks.remove(pair_key(guild_id, user_id))?;
+        self.blocks.flush()?;
+        Ok(removed.is_some())
+    }
+
+    /// The active block of a user, dropping it if it has run out.
+    pub fn active_block(&self, guild_id: u64, user_id: u64) -> Result<Option<AppealBlock>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(v) = self.blocks.get(pair_key(guild_id, user_id))? else { return Ok(None) };
+        let block: AppealBlock = serde_json::from_slice(&v)?;
+        if block.until.is_some_and(|u| u <= chrono::Utc::now().timestamp()) {
+            self.unblock(guild_id, user_id)?;
+            return Ok(None);
+        }
+        Ok(Some(block))
+    }
+}
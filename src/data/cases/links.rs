@@ -0,0 +1,117 @@
+use super::store::CaseAction;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStatus {
+    Outgoing, // this guild asked the other one and waits for it to accept
+    Incoming, // the other guild asked this one
+    Active,
+}
+
+/// One side of a case view link. Both guilds keep their own record, so each can decide
+/// which action types it shares with the other.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CaseLink {
+    pub guild_id: u64,
+    pub other_guild_id: u64,
+    pub status: LinkStatus,
+    pub shared: Vec<CaseAction>, // what this guild lets the other one see
+    pub requested_by: u64,
+    pub created_at: i64,
+    #[serde(default)]
+    pub accepted_at: Option<i64>,
+}
+
+pub struct CaseLinkStore {
+    links: sled::Tree,
+}
+
+fn link_key(guild_id: u64, other: u64) -> [u8; 16] {
+    let mut k = [0u8; 16];
+    k[..8].copy_from_slice(&guild_id.to_be_bytes());
+    k[8..].copy_from_slice(&other.to_be_bytes());
+    k
+}
+
+pub const ALL_ACTIONS: [CaseAction; 4] = [CaseAction::Warn, CaseAction::Mute, CaseAction::Kick, CaseAction::Ban];
+
+impl CaseLinkStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self { links: db.open_tree("case_links")? })
+    }
+
+    pub fn get(&self, guild_id: u64, other: u64) -> Result<Option<CaseLink>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.links.get(link_key(guild_id, other))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, link: &CaseLink) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.links.insert(link_key(link.guild_id, link.other_guild_id), serde_json::to_vec(link)?)?;
+        self.links.flush()?;
+        Ok(())
+    }
+
+    pub fn list(&self, guild_id: u64) -> Result<Vec<CaseLink>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.links.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    /// Stores a pending request on both sides.
+    pub fn request(&self, from: u64, to: u64, requested_by: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let now = chrono::Utc::now().timestamp();
+        for (guild_id, other, status) in [(from, to, LinkStatus::Outgoing), (to, from, LinkStatus::Incoming)] {
+            self.save(&CaseLink {
+                guild_id,
+                other_guild_id: other,
+                status,
+                shared: ALL_ACTIONS.to_vec(),
+                requested_by,
+                created_at: now,
+                accepted_at: None,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Activates a link that `guild_id` received from `other`. Returns false if there was no such request.
+    pub fn accept(&self, guild_id: u64, other: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(mut mine), Some(mut theirs)) = (self.get(guild_id, other)?, self.get(other, guild_id)?) else {
+            return Ok(false);
+        };
+        if mine.status != LinkStatus::Incoming || theirs.status != LinkStatus::Outgoing {
+            return Ok(false);
+        }
+        let now = chrono::Utc::now().timestamp();
+        for link in [&mut mine, &mut theirs] {
+            link.status = LinkStatus::Active;
+            link.accepted_at = Some(now);
+            self.save(link)?;
+        }
+        Ok(true)
+    }
+
+    /// Removes the link (or pending request) on both sides.
+    pub fn revoke(&self, guild_id: u64, other: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let a = self.links.remove(link_key(guild_id, other))?;
+        let b = self.links.remove(link_key(other, guild_id))?;
+        self.links.flush()?;
+        Ok(a.is_some() || b.is_some())
+    }
+
+    /// The action types `owner` shares with `viewer`, or None when the two guilds are not linked.
+    pub fn shared_with(&self, viewer: u64, owner: u64) -> Result<Option<Vec<CaseAction>>, Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(v), Some(o)) = (self.get(viewer, owner)?, self.get(owner, viewer)?) else {
+            return Ok(None);
+        };
+        if v.status != LinkStatus::Active || o.status != LinkStatus::Active {
+            return Ok(None);
+        }
+        Ok(Some(o.shared))
+    }
+}
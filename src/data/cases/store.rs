@@ -0,0 +1,255 @@
+use crate::data::next_counter;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseAction {
+    Warn,
+    Mute,
+    Kick,
+    Ban,
+}
+
+impl CaseAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseAction::Warn => "warn",
+            CaseAction::Mute => "mute",
+            CaseAction::Kick => "kick",
+            CaseAction::Ban => "ban",
+        }
+    }
+
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            CaseAction::Warn => "warned",
+            CaseAction::Mute => "muted",
+            CaseAction::Kick => "kicked",
+            CaseAction::Ban => "banned",
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "warn" | "warns" => Some(CaseAction::Warn),
+            "mute" | "mutes" => Some(CaseAction::Mute),
+            "kick" | "kicks" => Some(CaseAction::Kick),
+            "ban" | "bans" => Some(CaseAction::Ban),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Case {
+    pub id: u64,
+    pub guild_id: u64,
+    pub action: CaseAction,
+    pub targets: Vec<u64>,
+    pub moderator_id: u64,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub created_at: i64,               // unix seconds
+    #[serde(default)]
+    pub expires_at: Option<i64>,       // unix seconds, None = permanent
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub closed_reason: Option<String>,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub proof: Vec<String>,            // attachment URLs and message links
+    #[serde(default)]
+    pub split_from: Option<u64>,
+    #[serde(default)]
+    pub escalated_from: Vec<u64>,      // the warn cases that triggered this escalation
+    #[serde(default)]
+    pub history: Vec<CaseEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseEventKind {
+    Created,
+    ReasonUpdated,
+    DurationUpdated,
+    ProofAdded,
+    Split,
+    Closed,
+    Expired,
+    Deleted,
+    Escalated,
+}
+
+impl CaseEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CaseEventKind::Created => "created",
+            CaseEventKind::ReasonUpdated => "reason updated",
+            CaseEventKind::DurationUpdated => "duration updated",
+            CaseEventKind::ProofAdded => "proof added",
+            CaseEventKind::Split => "split",
+            CaseEventKind::Closed => "closed",
+            CaseEventKind::Expired => "expired",
+            CaseEventKind::Deleted => "deleted",
+            CaseEventKind::Escalated => "escalated",
+        }
+    }
+}
+
+/// One entry of a case's audit history. `moderator_id` is None for changes made by the bot itself.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CaseEvent {
+    pub at: i64,
+    pub moderator_id: Option<u64>,
+    pub kind: CaseEventKind,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl Case {
+    pub fn is_mass(&self) -> bool {
+        self.targets.len() > 1
+    }
+
+    pub fn record(&mut self, moderator_id: Option<u64>, kind: CaseEventKind, detail: Option<String>) {
+        self.history.push(CaseEvent {
+            at: chrono::Utc::now().timestamp(),
+            moderator_id,
+            kind,
+            detail,
+        });
+    }
+
+    pub fn close(&mut self, moderator_id: Option<u64>, reason: Option<String>) {
+        self.closed = true;
+        self.closed_reason = reason.clone();
+        self.record(moderator_id, CaseEventKind::Closed, reason);
+    }
+}
+
+pub struct NewCase {
+    pub action: CaseAction,
+    pub targets: Vec<u64>,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub expires_at: Option<i64>,
+}
+
+/// Moderation cases for every guild, stored in the shared bot database.
+///
+/// Cases are kept as JSON so new fields can be added with `#[serde(default)]`
+/// without breaking records written by older builds.
+pub struct CaseStore {
+    cases: sled::Tree,
+    counters: sled::Tree,
+    expiries: sled::Tree,
+}
+
+fn case_key(guild_id: u64, id: u64) -> [u8; 16] {
+    let mut k = [0u8; 16];
+    k[..8].copy_from_slice(&guild_id.to_be_bytes());
+    k[8..].copy_from_slice(&id.to_be_bytes());
+    k
+}
+
+fn expiry_key(ts: i64, guild_id: u64, id: u64) -> [u8; 24] {
+    let mut k = [0u8; 24];
+    // Offset so negative timestamps still sort before positive ones
+    k[..8].copy_from_slice(&((ts as u64) ^ (1 << 63)).to_be_bytes());
+    k[8..16].copy_from_slice(&guild_id.to_be_bytes());
+    k[16..].copy_from_slice(&id.to_be_bytes());
+    k
+}
+
+impl CaseStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            cases: db.open_tree("cases")?,
+            counters: db.open_tree("case_counters")?,
+            expiries: db.open_tree("case_expiries")?,
+        })
+    }
+
+    pub fn create(&self, guild_id: u64, new: NewCase) -> Result<Case, Box<dyn std::error::Error + Send + Sync>> {
+        let id = next_counter(&self.counters, guild_id.to_be_bytes(), "case")?;
+
+        let mut case = Case {
+            id,
+            guild_id,
+            action: new.action,
+            targets: new.targets,
+            moderator_id: new.moderator_id,
+            reason: new.reason,
+            created_at: chrono::Utc::now().timestamp(),
+            expires_at: new.expires_at,
+            closed: false,
+            closed_reason: None,
+            deleted: false,
+            proof: Vec::new(),
+            split_from: None,
+            escalated_from: Vec::new(),
+            history: Vec::new(),
+        };
+        case.record(Some(case.moderator_id), CaseEventKind::Created, None);
+        self.save(&case)?;
+        Ok(case)
+    }
+
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<Case>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.cases.get(case_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Inserts or replaces a case and keeps the expiry index in sync with it.
+    pub fn save(&self, case: &Case) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let key = case_key(case.guild_id, case.id);
+        if let Some(prev) = self.cases.get(key)? {
+            let prev: Case = serde_json::from_slice(&prev)?;
+            if let Some(ts) = prev.expires_at {
+                self.expiries.remove(expiry_key(ts, prev.guild_id, prev.id))?;
+            }
+        }
+        self.cases.insert(key, serde_json::to_vec(case)?)?;
+        if let (Some(ts), false, false) = (case.expires_at, case.closed, case.deleted) {
+            self.expiries.insert(expiry_key(ts, case.guild_id, case.id), &[])?;
+        }
+        self.cases.flush()?;
+        Ok(())
+    }
+
+    /// All cases of a guild that were not deleted, newest first.
+    pub fn list(&self, guild_id: u64) -> Result<Vec<Case>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.cases.scan_prefix(guild_id.to_be_bytes()).rev() {
+            let (_, v) = item?;
+            let case: Case = serde_json::from_slice(&v)?;
+            if !case.deleted { out.push(case); }
+        }
+        Ok(out)
+    }
+
+    /// The newest case of a guild that was not deleted.
+    pub fn latest(&self, guild_id: u64) -> Result<Option<Case>, Box<dyn std::error::Error + Send + Sync>> {
+        for item in self.cases.scan_prefix(guild_id.to_be_bytes()).rev() {
+            let (_, v) = item?;
+            let case: Case = serde_json::from_slice(&v)?;
+            if !case.deleted { return Ok(Some(case)); }
+        }
+        Ok(None)
+    }
+
+    /// Open cases whose expiry is at or before `now`, as (guild id, case id) pairs.
+    pub fn due_expiries(&self, now: i64) -> Result<Vec<(u64, u64)>, Box<dyn std::error::Error + Send + Sync>> {
+        let end = expiry_key(now, u64::MAX, u64::MAX);
+        let mut out = Vec::new();
+        for item in self.expiries.range(..=end) {
+            let (k, _) = item?;
+            let guild_id = u64::from_be_bytes(k[8..16].try_into()?);
+            let id = u64::from_be_bytes(k[16..24].try_into()?);
+            out.push((guild_id, id));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+/// A feature turned off in a guild.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DisabledFeature {
+    pub feature: String,
+    /// Reply to commands of the feature instead of the default refusal.
+    #[serde(default)]
+    pub message: Option<String>,
+    pub disabled_by: u64,
+    pub disabled_at: i64,
+}
+
+/// Feature flags of a guild. Features are on unless listed here.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GuildFeatures {
+    #[serde(default)]
+    pub disabled: Vec<DisabledFeature>,
+}
+
+impl GuildFeatures {
+    pub fn disabled(&self, feature: &str) -> Option<&DisabledFeature> {
+        self.disabled.iter().find(|d| d.feature == feature)
+    }
+}
+
+/// Features turned off in each guild, stored in the shared bot database.
+pub struct FeatureStore {
+    features: sled::Tree,
+}
+
+impl FeatureStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self { features: db.open_tree("guild_features")? })
+    }
+
+    pub fn get(&self, guild_id: u64) -> Result<GuildFeatures, Box<dyn std::error::Error + Send + Sync>> {
+        match self.features.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(GuildFeatures::default()),
+        }
+    }
+
+    pub fn set(&self, guild_id: u64, features: &GuildFeatures) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if features.disabled.is_empty() {
+            self.features.remove(guild_id.to_be_bytes())?;
+        } else {
+            self.features.insert(guild_id.to_be_bytes(), serde_json::to_vec(features)?)?;
+        }
+        self.features.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::data::pair_key;
+use serde::{Deserialize, Serialize};
+
+/// Roles handed out to new members, with separate sets for humans and bots.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct JoinRoleConfig {
+    #[serde(default)]
+    pub human_roles: Vec<u64>,
+    #[serde(default)]
+    pub bot_roles: Vec<u64>,
+    /// Humans only get their roles once they passed membership screening.
+    #[serde(default)]
+    pub after_screening: bool,
+    /// Seconds to wait after the join (or after screening) before the roles are given.
+    #[serde(default)]
+    pub delay_secs: Option<u64>,
+}
+
+impl JoinRoleConfig {
+    pub fn roles(&self, bot: bool) -> &[u64] {
+        if bot { &self.bot_roles } else { &self.human_roles }
+    }
+}
+
+/// A member waiting for their join roles.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PendingJoinRoles {
+    pub guild_id: u64,
+    pub user_id: u64,
+    /// When the roles are due. None while the member hasn't passed membership screening.
+    #[serde(default)]
+    pub due_at: Option<i64>,
+}
+
+/// Join role settings and members waiting for their roles, stored in the shared bot database.
+pub struct JoinRoleStore {
+    config: sled::Tree,
+    pending: sled::Tree,
+}
+
+impl JoinRoleStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            config: db.open_tree("join_role_config")?,
+            pending: db.open_tree("join_role_pending")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<JoinRoleConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(JoinRoleConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &JoinRoleConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+
+    pub fn pending(&self, guild_id: u64, user_id: u64) -> Result<Option<PendingJoinRoles>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.pending.get(pair_key(guild_id, user_id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_pending(&self, pending: &PendingJoinRoles) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.pending.insert(pair_key(pending.guild_id, pending.user_id), serde_json::to_vec(pending)?)?;
+        self.pending.flush()?;
+        Ok(())
+    }
+
+    pub fn clear_pending(&self, guild_id: u64, user_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.pending.remove(pair_key(guild_id, user_id))?.is_some();
+        self.pending.flush()?;
+        Ok(removed)
+    }
+
+    /// Members waiting for their join roles in every guild.
+    pub fn all_pending(&self) -> Result<Vec<PendingJoinRoles>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.pending.iter() {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::data::pair_key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockSource {
+    Single,
+    LockAll,
+    Lockdown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverwriteTarget {
+    Role,
+    Member,
+}
+
+/// One permission overwrite of a channel, with the raw permission bits.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedOverwrite {
+    pub target: OverwriteTarget,
+    pub id: u64,
+    pub allow: u64,
+    pub deny: u64,
+}
+
+/// The overwrites a channel had right before it was locked, so unlocking can put them back exactly.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LockSnapshot {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub overwrites: Vec<SavedOverwrite>,
+    pub source: LockSource,
+    pub locked_by: u64,
+    pub locked_at: i64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LockConfig {
+    pub channels: Vec<u64>, // the channels lockall locks
+}
+
+/// Channel lock snapshots and the lockall channel set of each guild, stored in the shared bot database.
+pub struct LockStore {
+    snapshots: sled::Tree,
+    config: sled::Tree,
+}
+
+impl LockStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            snapshots: db.open_tree("lock_snapshots")?,
+            config: db.open_tree("lock_config")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<LockConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(LockConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &LockConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+
+    pub fn get(&self, guild_id: u64, channel_id: u64) -> Result<Option<LockSnapshot>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.snapshots.get(pair_key(guild_id, channel_id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, snapshot: &LockSnapshot) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.snapshots.insert(pair_key(snapshot.guild_id, snapshot.channel_id), serde_json::to_vec(snapshot)?)?;
+        self.snapshots.flush()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, guild_id: u64, channel_id: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.snapshots.remove(pair_key(guild_id, channel_id))?;
+        self.snapshots.flush()?;
+        Ok(())
+    }
+
+    /// Every locked channel of a guild.
+    pub fn list(&self, guild_id: u64) -> Result<Vec<LockSnapshot>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.snapshots.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,248 @@
+use crate::data::next_counter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Kinds of events that can be routed to a log channel.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogType {
+    MessageEdit,
+    MessageDelete,
+    MessageBulkDelete,
+    MemberJoin,
+    MemberLeave,
+    MemberRoles,
+    MemberNickname,
+    ChannelCreate,
+    ChannelUpdate,
+    ChannelDelete,
+    RoleCreate,
+    RoleUpdate,
+    RoleDelete,
+    Voice,
+    Ban,
+    Unban,
+    Kick,
+    InviteCreate,
+    InviteDelete,
+    SlowMode,
+}
+
+impl LogType {
+    pub const ALL: [LogType; 20] = [
+        LogType::MessageEdit,
+        LogType::MessageDelete,
+        LogType::MessageBulkDelete,
+        LogType::MemberJoin,
+        LogType::MemberLeave,
+        LogType::MemberRoles,
+        LogType::MemberNickname,
+        LogType::ChannelCreate,
+        LogType::ChannelUpdate,
+        LogType::ChannelDelete,
+        LogType::RoleCreate,
+        LogType::RoleUpdate,
+        LogType::RoleDelete,
+        LogType::Voice,
+        LogType::Ban,
+        LogType::Unban,
+        LogType::Kick,
+        LogType::InviteCreate,
+        LogType::InviteDelete,
+        LogType::SlowMode,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogType::MessageEdit => "message_edit",
+            LogType::MessageDelete => "message_delete",
+            LogType::MessageBulkDelete => "message_bulk_delete",
+            LogType::MemberJoin => "member_join",
+            LogType::MemberLeave => "member_leave",
+            LogType::MemberRoles => "member_roles",
+            LogType::MemberNickname => "member_nickname",
+            LogType::ChannelCreate => "channel_create",
+            LogType::ChannelUpdate => "channel_update",
+            LogType::ChannelDelete => "channel_delete",
+            LogType::RoleCreate => "role_create",
+            LogType::RoleUpdate => "role_update",
+            LogType::RoleDelete => "role_delete",
+            LogType::Voice => "voice",
+            LogType::Ban => "ban",
+            LogType::Unban => "unban",
+            LogType::Kick => "kick",
+            LogType::InviteCreate => "invite_create",
+            LogType::InviteDelete => "invite_delete",
+            LogType::SlowMode => "slow_mode",
+        }
+    }
+
+    pub fn parse(input: &str) -> Option<Self> {
+        let s = input.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LogConfig {
+    #[serde(default)]
+    pub channels: BTreeMap<LogType, u64>,
+    #[serde(default)]
+    pub ignored_users: Vec<u64>,
+    #[serde(default)]
+    pub ignored_roles: Vec<u64>,
+    #[serde(default)]
+    pub ignored_channels: Vec<u64>,
+    /// Whether bans and kicks done in the Discord client get a case.
+    #[serde(default)]
+    pub auto_cases: bool,
+}
+
+/// A log entry waiting to be delivered. The embed is kept as the JSON sent to Discord.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QueuedLog {
+    pub id: u64,
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub log_type: LogType,
+    pub embed: serde_json::Value,
+    pub queued_at: i64,
+}
+
+/// The webhook the bot created in a log channel.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogWebhook {
+    pub channel_id: u64,
+    pub webhook_id: u64,
+    pub token: String,
+}
+
+/// Log channel routing of each guild, the delivery queue and the log webhooks, stored in the shared
+/// bot database.
+pub struct LogStore {
+    config: sled::Tree,
+    queue: sled::Tree,
+    counters: sled::Tree,
+    webhooks: sled::Tree,
+}
+
+// Queue keys start with the channel, so each channel's entries can be read on their own
+fn queue_key(channel_id: u64, id: u64) -> [u8; 16] {
+    let mut k = [0u8; 16];
+    k[..8].copy_from_slice(&channel_id.to_be_bytes());
+    k[8..].copy_from_slice(&id.to_be_bytes());
+    k
+}
+
+impl LogStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            config: db.open_tree("log_config")?,
+            queue: db.open_tree("log_channel_queue")?,
+            counters: db.open_tree("log_counters")?,
+            webhooks: db.open_tree("log_webhooks")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<LogConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(LogConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &LogConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+
+    /// The channel a log type goes to, if it is routed anywhere.
+    pub fn channel_for(&self, guild_id: u64, log_type: LogType) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.config(guild_id)?.channels.get(&log_type).copied())
+    }
+
+    /// Adds an entry to the end of the delivery queue.
+    pub fn enqueue(
+        &self,
+        guild_id: u64,
+        channel_id: u64,
+        log_type: LogType,
+        embed: serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let id = next_counter(&self.counters, b"queue", "log queue")?;
+        let entry = QueuedLog {
+            id,
+            guild_id,
+            channel_id,
+            log_type,
+            embed,
+            queued_at: chrono::Utc::now().timestamp(),
+        };
+        self.queue.insert(queue_key(channel_id, id), serde_json::to_vec(&entry)?)?;
+        self.queue.flush()?;
+        Ok(())
+    }
+
+    /// Channels with queued entries.
+    pub fn queued_channels(&self) -> Result<Vec<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        let mut from = 0u64;
+        // Jumps from one channel's entries to the next instead of reading all of them
+        while let Some(item) = self.queue.range(from.to_be_bytes()..).next() {
+            let (k, _) = item?;
+            let channel_id = u64::from_be_bytes(<[u8; 8]>::try_from(&k[..8])?);
+            out.push(channel_id);
+            let Some(next) = channel_id.checked_add(1) else { break };
+            from = next;
+        }
+        Ok(out)
+    }
+
+    /// The oldest queued entries of a channel, at most `limit`.
+    pub fn queued(&self, channel_id: u64, limit: usize) -> Result<Vec<QueuedLog>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.queue.scan_prefix(channel_id.to_be_bytes()).take(limit) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn dequeue(&self, channel_id: u64, ids: &[u64]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for id in ids {
+            self.queue.remove(queue_key(channel_id, *id))?;
+        }
+        self.queue.flush()?;
+        Ok(())
+    }
+
+    /// Drops every queued entry of a channel.
+    pub fn clear_queue(&self, channel_id: u64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut removed = 0;
+        for key in self.queue.scan_prefix(channel_id.to_be_bytes()).keys() {
+            self.queue.remove(key?)?;
+            removed += 1;
+        }
+        self.queue.flush()?;
+        Ok(removed)
+    }
+
+    pub fn webhook(&self, channel_id: u64) -> Result<Option<LogWebhook>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.webhooks.get(channel_id.to_be_bytes())? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_webhook(&self, webhook: &LogWebhook) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.webhooks.insert(webhook.channel_id.to_be_bytes(), serde_json::to_vec(webhook)?)?;
+        self.webhooks.flush()?;
+        Ok(())
+    }
+
+    pub fn remove_webhook(&self, channel_id: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.webhooks.remove(channel_id.to_be_bytes())?;
+        self.webhooks.flush()?;
+        Ok(())
+    }
+}
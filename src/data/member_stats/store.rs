@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+/// Member numbers of a guild on one UTC day. The counts are the last ones seen that day.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DailyStats {
+    /// Days since the Unix epoch.
+    pub day: i64,
+    #[serde(default)]
+    pub members: Option<u64>,
+    /// Only known when every member was cached.
+    #[serde(default)]
+    pub humans: Option<u64>,
+    #[serde(default)]
+    pub bots: Option<u64>,
+    #[serde(default)]
+    pub joins: u64,
+    #[serde(default)]
+    pub leaves: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatKind {
+    Members,
+    Humans,
+    Bots,
+    Online,
+}
+
+/// A voice channel whose name shows a live count.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatChannel {
+    pub channel_id: u64,
+    pub kind: StatKind,
+    /// Channel name with `{count}` standing for the number.
+    pub format: String,
+    /// When the bot last renamed the channel, Unix seconds.
+    #[serde(default)]
+    pub renamed_at: i64,
+}
+
+/// Daily member statistics and stat channels of each guild, stored in the shared bot database.
+pub struct MemberStatsStore {
+    daily: sled::Tree,
+    channels: sled::Tree,
+}
+
+fn day_key(guild_id: u64, day: i64) -> [u8; 16] {
+    let mut k = [0u8; 16];
+    k[..8].copy_from_slice(&guild_id.to_be_bytes());
+    k[8..].copy_from_slice(&day.to_be_bytes());
+    k
+}
+
+impl MemberStatsStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            daily: db.open_tree("member_stats_daily")?,
+            channels: db.open_tree("member_stats_channels")?,
+        })
+    }
+
+    /// Changes the stats of a day in place, creating them if needed.
+    pub fn update_day(
+        &self,
+        guild_id: u64,
+        day: i64,
+        f: impl Fn(&mut DailyStats),
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut failed = None;
+        self.daily.update_and_fetch(day_key(guild_id, day), |old| {
+            let mut stats = old
+                .and_then(|v| serde_json::from_slice::<DailyStats>(v).ok())
+                .unwrap_or(DailyStats { day, ..Default::default() });
+            f(&mut stats);
+            match serde_json::to_vec(&stats) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    failed = Some(e);
+                    old.map(|v| v.to_vec())
+                }
+            }
+        })?;
+        if let Some(e) = failed {
+            return Err(e.into());
+        }
+        self.daily.flush()?;
+        Ok(())
+    }
+
+    /// Stats of the days from `from` up to and including `to`, oldest first. Days the bot wasn't
+    /// running are missing.
+    pub fn days(&self, guild_id: u64, from: i64, to: i64) -> Result<Vec<DailyStats>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.daily.range(day_key(guild_id, from.max(0))..=day_key(guild_id, to.max(0))) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn channels(&self, guild_id: u64) -> Result<Vec<StatChannel>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.channels.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn set_channels(&self, guild_id: u64, channels: &[StatChannel]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if channels.is_empty() {
+            self.channels.remove(guild_id.to_be_bytes())?;
+        } else {
+            self.channels.insert(guild_id.to_be_bytes(), serde_json::to_vec(channels)?)?;
+        }
+        self.channels.flush()?;
+        Ok(())
+    }
+
+    /// Guilds with stat channels.
+    pub fn channel_guilds(&self) -> Result<Vec<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for key in self.channels.iter().keys() {
+            let k = key?;
+            let mut id = [0u8; 8];
+            id.copy_from_slice(&k[..8]);
+            out.push(u64::from_be_bytes(id));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::data::{next_counter, pair_key};
+use serde::{Deserialize, Serialize};
+
+/// A file attached to a note. Discord attachment URLs expire, so the message hosting the file is kept too.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NoteAttachment {
+    pub filename: String,
+    pub url: String,
+    pub message_link: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteChange {
+    Edited,
+    Deleted,
+}
+
+/// One change to a note, keeping the text it had before.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NoteRevision {
+    pub at: i64,
+    pub by: u64,
+    pub change: NoteChange,
+    pub previous: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserNote {
+    pub id: u64,
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub author_id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub attachments: Vec<NoteAttachment>,
+    pub created_at: i64,
+    #[serde(default)]
+    pub edited_at: Option<i64>,
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub history: Vec<NoteRevision>,
+}
+
+/// Where files attached to notes are uploaded, so they don't show up next to the command.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NoteConfig {
+    pub file_channel_id: u64,
+}
+
+/// Moderator notes about users of each guild, stored in the shared bot database.
+pub struct NoteStore {
+    notes: sled::Tree,
+    counters: sled::Tree,
+    config: sled::Tree,
+}
+
+impl NoteStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            notes: db.open_tree("user_notes")?,
+            counters: db.open_tree("user_note_counters")?,
+            config: db.open_tree("user_note_config")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<Option<NoteConfig>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: Option<&NoteConfig>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match config {
+            Some(c) => { self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(c)?)?; }
+            None => { self.config.remove(guild_id.to_be_bytes())?; }
+        }
+        self.config.flush()?;
+        Ok(())
+    }
+
+    pub fn next_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "note")
+    }
+
+    /// A note by id, including deleted ones so their history stays viewable.
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<UserNote>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.notes.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, note: &UserNote) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.notes.insert(pair_key(note.guild_id, note.id), serde_json::to_vec(note)?)?;
+        self.notes.flush()?;
+        Ok(())
+    }
+
+    /// All notes of a guild that were not deleted, newest first.
+    pub fn list(&self, guild_id: u64) -> Result<Vec<UserNote>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.notes.scan_prefix(guild_id.to_be_bytes()).rev() {
+            let (_, v) = item?;
+            let note: UserNote = serde_json::from_slice(&v)?;
+            if !note.deleted { out.push(note); }
+        }
+        Ok(out)
+    }
+
+    /// The notes about one user, newest first.
+    pub fn for_user(&self, guild_id: u64, user_id: u64) -> Result<Vec<UserNote>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.list(guild_id)?.into_iter().filter(|n| n.user_id == user_id).collect())
+    }
+}
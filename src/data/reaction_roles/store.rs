@@ -0,0 +1,120 @@
+use crate::data::pair_key;
+use serde::{Deserialize, Serialize};
+
+/// How reacting and unreacting change roles.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ReactionRoleMode {
+    /// Reacting gives the roles, unreacting takes them.
+    #[default]
+    Normal,
+    /// Like normal, but only one reaction (and its roles) at a time.
+    Unique,
+    /// Reacting gives the roles, unreacting does nothing.
+    Verify,
+    /// Reacting takes the roles, unreacting does nothing.
+    Drop,
+}
+
+/// One emoji of a reaction role message and the roles it stands for.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReactionRoleEntry {
+    /// The emoji as it is written in a message, `<:name:id>` for custom ones.
+    pub emoji: String,
+    pub roles: Vec<u64>,
+    /// Users reacting with this emoji as of the last event, used to catch up after downtime.
+    #[serde(default)]
+    pub reactors: Vec<u64>,
+}
+
+/// A message whose reactions hand out roles.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReactionRoleMessage {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    #[serde(default)]
+    pub entries: Vec<ReactionRoleEntry>,
+    #[serde(default)]
+    pub mode: ReactionRoleMode,
+    /// Reacting takes the roles and unreacting gives them.
+    #[serde(default)]
+    pub reversed: bool,
+    #[serde(default)]
+    pub max_reacts: Option<u32>,
+    /// Members need one of these roles to use the message. Empty means everyone may.
+    #[serde(default)]
+    pub required_roles: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ReactionRoleConfig {
+    /// Whether the bot adds every configured emoji to the message itself.
+    #[serde(default)]
+    pub autoreact: bool,
+}
+
+/// Reaction role messages and settings of each guild, stored in the shared bot database.
+pub struct ReactionRoleStore {
+    messages: sled::Tree,
+    config: sled::Tree,
+}
+
+impl ReactionRoleStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            messages: db.open_tree("reaction_role_messages")?,
+            config: db.open_tree("reaction_role_config")?,
+        })
+    }
+
+    pub fn get(&self, guild_id: u64, message_id: u64) -> Result<Option<ReactionRoleMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.messages.get(pair_key(guild_id, message_id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, message: &ReactionRoleMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.messages.insert(pair_key(message.guild_id, message.message_id), serde_json::to_vec(message)?)?;
+        self.messages.flush()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, guild_id: u64, message_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.messages.remove(pair_key(guild_id, message_id))?.is_some();
+        self.messages.flush()?;
+        Ok(removed)
+    }
+
+    pub fn list(&self, guild_id: u64) -> Result<Vec<ReactionRoleMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.messages.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    /// Reaction role messages of every guild.
+    pub fn all(&self) -> Result<Vec<ReactionRoleMessage>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.messages.iter() {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<ReactionRoleConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(ReactionRoleConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &ReactionRoleConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+}
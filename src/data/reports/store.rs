@@ -0,0 +1,158 @@
+use crate::data::{next_counter, pair_key};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportStatus {
+    Open,
+    Claimed,
+    Actioned,
+    Ignored,
+}
+
+impl ReportStatus {
+    /// Claimed reports still wait for a decision.
+    pub fn is_open(&self) -> bool {
+        matches!(self, ReportStatus::Open | ReportStatus::Claimed)
+    }
+}
+
+/// Snapshot of a reported message, kept so the report stays useful after the message is deleted.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportedMessage {
+    pub channel_id: u64,
+    pub message_id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub attachments: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Report {
+    pub id: u64,
+    pub guild_id: u64,
+    pub reporter_id: u64,
+    pub target_id: u64,
+    #[serde(default)]
+    pub target_name: String,
+    #[serde(default)]
+    pub message: Option<ReportedMessage>,
+    pub reason: String,
+    #[serde(default)]
+    pub comment: Option<String>,
+    pub status: ReportStatus,
+    pub created_at: i64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    #[serde(default)]
+    pub claimed_by: Option<u64>,
+    #[serde(default)]
+    pub resolved_by: Option<u64>,
+    #[serde(default)]
+    pub resolved_at: Option<i64>,
+    #[serde(default)]
+    pub resolution: Option<String>, // what was done, e.g. "ban (case #12)"
+    #[serde(default)]
+    pub case_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportConfig {
+    pub channel_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlacklistEntry {
+    pub added_by: u64,
+    pub added_at: i64,
+}
+
+/// User reports, the report channel and the report blacklist of each guild, stored in the shared bot database.
+pub struct ReportStore {
+    reports: sled::Tree,
+    counters: sled::Tree,
+    blacklist: sled::Tree,
+    config: sled::Tree,
+}
+
+impl ReportStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            reports: db.open_tree("reports")?,
+            counters: db.open_tree("report_counters")?,
+            blacklist: db.open_tree("report_blacklist")?,
+            config: db.open_tree("report_config")?,
+        })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<Option<ReportConfig>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: Option<&ReportConfig>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match config {
+            Some(c) => { self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(c)?)?; }
+            None => { self.config.remove(guild_id.to_be_bytes())?; }
+        }
+        self.config.flush()?;
+        Ok(())
+    }
+
+    pub fn next_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "report")
+    }
+
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<Report>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.reports.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, report: &Report) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.reports.insert(pair_key(report.guild_id, report.id), serde_json::to_vec(report)?)?;
+        self.reports.flush()?;
+        Ok(())
+    }
+
+    /// All reports of a guild, oldest first.
+    pub fn list(&self, guild_id: u64) -> Result<Vec<Report>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.reports.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn list_open(&self, guild_id: u64) -> Result<Vec<Report>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.list(guild_id)?.into_iter().filter(|r| r.status.is_open()).collect())
+    }
+
+    pub fn blacklist_add(&self, guild_id: u64, user_id: u64, entry: &BlacklistEntry) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.blacklist.insert(pair_key(guild_id, user_id), serde_json::to_vec(entry)?)?;
+        self.blacklist.flush()?;
+        Ok(())
+    }
+
+    pub fn blacklist_remove(&self, guild_id: u64, user_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.blacklist.remove(pair_key(guild_id, user_id))?;
+        self.blacklist.flush()?;
+        Ok(removed.is_some())
+    }
+
+    pub fn is_blacklisted(&self, guild_id: u64, user_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.blacklist.contains_key(pair_key(guild_id, user_id))?)
+    }
+
+    pub fn blacklist(&self, guild_id: u64) -> Result<Vec<(u64, BlacklistEntry)>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.blacklist.scan_prefix(guild_id.to_be_bytes()) {
+            let (k, v) = item?;
+            out.push((u64::from_be_bytes(k[8..16].try_into()?), serde_json::from_slice(&v)?));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::data::{next_counter, pair_key};
+use crate::data::reaction_roles::store::ReactionRoleMode;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanelStyle {
+    Buttons,
+    Select,
+}
+
+/// One role offered by a panel.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PanelOption {
+    pub role_id: u64,
+    pub label: String,
+    #[serde(default)]
+    pub emoji: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A message with buttons or a select menu that hands out roles. It follows the same modes as
+/// reaction roles, with choosing an option in place of reacting.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RolePanel {
+    pub id: u64,
+    pub guild_id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub style: PanelStyle,
+    #[serde(default)]
+    pub options: Vec<PanelOption>,
+    #[serde(default)]
+    pub mode: ReactionRoleMode,
+    #[serde(default)]
+    pub reversed: bool,
+    #[serde(default)]
+    pub max_selections: Option<u32>,
+    /// Where the panel is posted, None while it is a draft.
+    #[serde(default)]
+    pub channel_id: Option<u64>,
+    #[serde(default)]
+    pub message_id: Option<u64>,
+    pub created_by: u64,
+}
+
+/// Role panels of each guild, stored in the shared bot database.
+pub struct RolePanelStore {
+    panels: sled::Tree,
+    counters: sled::Tree,
+}
+
+impl RolePanelStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            panels: db.open_tree("role_panels")?,
+            counters: db.open_tree("role_panel_counters")?,
+        })
+    }
+
+    pub fn next_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "panel")
+    }
+
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<RolePanel>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.panels.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, panel: &RolePanel) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.panels.insert(pair_key(panel.guild_id, panel.id), serde_json::to_vec(panel)?)?;
+        self.panels.flush()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, guild_id: u64, id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.panels.remove(pair_key(guild_id, id))?.is_some();
+        self.panels.flush()?;
+        Ok(removed)
+    }
+
+    pub fn list(&self, guild_id: u64) -> Result<Vec<RolePanel>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.panels.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+}
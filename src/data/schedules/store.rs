@@ -0,0 +1,138 @@
+use crate::data::{next_counter, pair_key};
+use serde::{Deserialize, Serialize};
+
+/// What a scheduled message sends.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleContent {
+    Text(String),
+    /// The name of a message template, rendered when the message is sent.
+    Template(String),
+}
+
+/// When a scheduled message is sent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleTiming {
+    /// Once, at `next_run`.
+    Once,
+    /// Every `secs` seconds after the first run.
+    Interval { secs: u64 },
+    /// Whenever a five field cron expression matches, in the guild's timezone.
+    Cron { expr: String },
+}
+
+/// What happens to runs that were missed while the bot was offline.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CatchUp {
+    /// Missed runs are dropped, the schedule continues with its next run.
+    Skip,
+    /// Missed runs are sent as a single message.
+    #[default]
+    Once,
+    /// Every missed run is sent, up to a limit.
+    All,
+}
+
+/// A one-off or recurring message of a guild.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Schedule {
+    pub id: u64,
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub content: ScheduleContent,
+    pub timing: ScheduleTiming,
+    /// Unix time of the next run.
+    pub next_run: i64,
+    #[serde(default)]
+    pub last_run: Option<i64>,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub catch_up: CatchUp,
+    pub created_by: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ScheduleConfig {
+    /// IANA name of the guild's timezone (like `Europe/Berlin`), used for dates and cron expressions.
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl ScheduleConfig {
+    /// The guild's timezone, UTC when none is set.
+    pub fn tz(&self) -> chrono_tz::Tz {
+        self.timezone.as_deref().and_then(|t| t.parse().ok()).unwrap_or(chrono_tz::UTC)
+    }
+}
+
+/// Scheduled messages and timezone settings of each guild, stored in the shared bot database.
+pub struct ScheduleStore {
+    schedules: sled::Tree,
+    counters: sled::Tree,
+    config: sled::Tree,
+}
+
+impl ScheduleStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            schedules: db.open_tree("schedules")?,
+            counters: db.open_tree("schedule_counters")?,
+            config: db.open_tree("schedule_config")?,
+        })
+    }
+
+    pub fn next_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "schedule")
+    }
+
+    pub fn get(&self, guild_id: u64, id: u64) -> Result<Option<Schedule>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.schedules.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, schedule: &Schedule) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.schedules.insert(pair_key(schedule.guild_id, schedule.id), serde_json::to_vec(schedule)?)?;
+        self.schedules.flush()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, guild_id: u64, id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.schedules.remove(pair_key(guild_id, id))?.is_some();
+        self.schedules.flush()?;
+        Ok(removed)
+    }
+
+    pub fn list(&self, guild_id: u64) -> Result<Vec<Schedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.schedules.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    /// Scheduled messages of every guild.
+    pub fn all(&self) -> Result<Vec<Schedule>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.schedules.iter() {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<ScheduleConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(ScheduleConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &ScheduleConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::data::{next_counter, pair_key};
+use serde::{Deserialize, Serialize};
+
+/// Auto slow mode settings of one channel.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutoSlowMode {
+    pub threshold: f64, // messages per second that trigger a raise
+    pub max_delay: u16, // seconds
+    pub decay_secs: u32, // calm time before stepping back down one level
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlowModeSource {
+    Auto,
+    Window,
+}
+
+/// A slow mode the bot applied on its own, with the delay the channel had before so it can be put back.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RaisedSlowMode {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub base: u16,
+    pub current: u16,
+    pub source: SlowModeSource,
+    pub changed_at: i64,
+    #[serde(default)]
+    pub window_id: Option<u64>,
+}
+
+/// A scheduled slow mode window, e.g. for an event.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SlowModeWindow {
+    pub id: u64,
+    pub guild_id: u64,
+    pub channel_id: u64,
+    pub delay: u16,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub created_by: u64,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub started: bool,
+}
+
+/// Auto slow mode settings, bot-applied slow modes and scheduled windows of each guild, stored in the shared bot database.
+pub struct SlowModeStore {
+    auto: sled::Tree,
+    raised: sled::Tree,
+    windows: sled::Tree,
+    counters: sled::Tree,
+}
+
+impl SlowModeStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self {
+            auto: db.open_tree("slowmode_auto")?,
+            raised: db.open_tree("slowmode_raised")?,
+            windows: db.open_tree("slowmode_windows")?,
+            counters: db.open_tree("slowmode_counters")?,
+        })
+    }
+
+    pub fn auto(&self, guild_id: u64, channel_id: u64) -> Result<Option<AutoSlowMode>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.auto.get(pair_key(guild_id, channel_id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_auto(&self, guild_id: u64, channel_id: u64, config: Option<&AutoSlowMode>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match config {
+            Some(c) => { self.auto.insert(pair_key(guild_id, channel_id), serde_json::to_vec(c)?)?; }
+            None => { self.auto.remove(pair_key(guild_id, channel_id))?; }
+        }
+        self.auto.flush()?;
+        Ok(())
+    }
+
+    /// Every channel of a guild with auto slow mode, as (channel, settings).
+    pub fn auto_list(&self, guild_id: u64) -> Result<Vec<(u64, AutoSlowMode)>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.auto.scan_prefix(guild_id.to_be_bytes()) {
+            let (k, v) = item?;
+            out.push((u64::from_be_bytes(k[8..16].try_into()?), serde_json::from_slice(&v)?));
+        }
+        Ok(out)
+    }
+
+    pub fn raised(&self, guild_id: u64, channel_id: u64) -> Result<Option<RaisedSlowMode>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.raised.get(pair_key(guild_id, channel_id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_raised(&self, raised: &RaisedSlowMode) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.raised.insert(pair_key(raised.guild_id, raised.channel_id), serde_json::to_vec(raised)?)?;
+        self.raised.flush()?;
+        Ok(())
+    }
+
+    pub fn clear_raised(&self, guild_id: u64, channel_id: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.raised.remove(pair_key(guild_id, channel_id))?;
+        self.raised.flush()?;
+        Ok(())
+    }
+
+    /// Every bot-applied slow mode across all guilds.
+    pub fn all_raised(&self) -> Result<Vec<RaisedSlowMode>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.raised.iter() {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    pub fn next_window_id(&self, guild_id: u64) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        next_counter(&self.counters, guild_id.to_be_bytes(), "slow mode window")
+    }
+
+    pub fn window(&self, guild_id: u64, id: u64) -> Result<Option<SlowModeWindow>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.windows.get(pair_key(guild_id, id))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save_window(&self, window: &SlowModeWindow) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.windows.insert(pair_key(window.guild_id, window.id), serde_json::to_vec(window)?)?;
+        self.windows.flush()?;
+        Ok(())
+    }
+
+    pub fn remove_window(&self, guild_id: u64, id: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.windows.remove(pair_key(guild_id, id))?;
+        self.windows.flush()?;
+        Ok(())
+    }
+
+    pub fn windows(&self, guild_id: u64) -> Result<Vec<SlowModeWindow>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.windows.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+
+    /// Every scheduled window across all guilds.
+    pub fn all_windows(&self) -> Result<Vec<SlowModeWindow>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.windows.iter() {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// A reusable message: content, embeds and components in Discord's message JSON format.
+/// Strings may contain variables like `{user}` that are filled in when the template is sent.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MessageTemplate {
+    pub guild_id: u64,
+    pub name: String,
+    pub body: serde_json::Value,
+    pub created_by: u64,
+    pub updated_at: i64,
+    /// Messages sent from this template, kept so they can be edited when the template changes.
+    #[serde(default)]
+    pub deployments: Vec<Deployment>,
+}
+
+/// A message sent from a template.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Deployment {
+    pub channel_id: u64,
+    pub message_id: u64,
+    /// ID and token of the webhook that sent the message. None for messages sent by the bot itself.
+    #[serde(default)]
+    pub webhook: Option<(u64, String)>,
+    /// The user whose values filled in `{user}` and `{user_name}`.
+    pub sent_by: u64,
+    pub sent_at: i64,
+    /// The message body as it was last sent or edited, compared against when re-syncing.
+    pub rendered: serde_json::Value,
+}
+
+/// Message templates of each guild by name, stored in the shared bot database.
+pub struct TemplateStore {
+    templates: sled::Tree,
+}
+
+fn name_key(guild_id: u64, name: &str) -> Vec<u8> {
+    let mut k = guild_id.to_be_bytes().to_vec();
+    k.extend_from_slice(name.as_bytes());
+    k
+}
+
+impl TemplateStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self { templates: db.open_tree("message_templates")? })
+    }
+
+    pub fn get(&self, guild_id: u64, name: &str) -> Result<Option<MessageTemplate>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.templates.get(name_key(guild_id, name))? {
+            Some(v) => Ok(Some(serde_json::from_slice(&v)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, template: &MessageTemplate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.templates.insert(name_key(template.guild_id, &template.name), serde_json::to_vec(template)?)?;
+        self.templates.flush()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, guild_id: u64, name: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self.templates.remove(name_key(guild_id, name))?.is_some();
+        self.templates.flush()?;
+        Ok(removed)
+    }
+
+    /// Templates of a guild, sorted by name.
+    pub fn list(&self, guild_id: u64) -> Result<Vec<MessageTemplate>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut out = Vec::new();
+        for item in self.templates.scan_prefix(guild_id.to_be_bytes()) {
+            let (_, v) = item?;
+            out.push(serde_json::from_slice(&v)?);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::data::cases::store::CaseAction;
+use serde::{Deserialize, Serialize};
+
+/// What happens once a user reaches a number of active warns.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EscalationRule {
+    pub count: u32,
+    pub action: CaseAction, // never Warn
+    #[serde(default)]
+    pub duration_secs: Option<u64>, // None = permanent (mutes fall back to the 28 day maximum)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WarnConfig {
+    #[serde(default)]
+    pub decay_secs: Option<u64>, // how long warns without their own duration stay active, None = forever
+    #[serde(default)]
+    pub rules: Vec<EscalationRule>, // sorted by count
+}
+
+/// Warn decay and escalation rules of each guild, stored in the shared bot database.
+pub struct WarnStore {
+    config: sled::Tree,
+}
+
+impl WarnStore {
+    pub fn open(db: &sled::Db) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self { config: db.open_tree("warn_config")? })
+    }
+
+    pub fn config(&self, guild_id: u64) -> Result<WarnConfig, Box<dyn std::error::Error + Send + Sync>> {
+        match self.config.get(guild_id.to_be_bytes())? {
+            Some(v) => Ok(serde_json::from_slice(&v)?),
+            None => Ok(WarnConfig::default()),
+        }
+    }
+
+    pub fn set_config(&self, guild_id: u64, config: &WarnConfig) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.config.insert(guild_id.to_be_bytes(), serde_json::to_vec(config)?)?;
+        self.config.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use crate::commands;
+use poise::serenity_prelude as serenity;
+use std::fmt::Display;
+
+/// The custom_id of a component on a persistent message, written as `prefix:action:arg:arg...`.
+/// The prefix names the feature that handles it, so routing keeps working across restarts.
+pub struct ComponentId {
+    pub prefix: String,
+    pub action: String,
+    pub args: Vec<String>,
+}
+
+impl ComponentId {
+    pub fn parse(custom_id: &str) -> Option<Self> {
+        let mut parts = custom_id.split(':');
+        Some(Self {
+            prefix: parts.next()?.to_string(),
+            action: parts.next()?.to_string(),
+            args: parts.map(str::to_string).collect(),
+        })
+    }
+
+    /// An argument parsed into a number or other type, None when missing or malformed.
+    pub fn arg<T: std::str::FromStr>(&self, index: usize) -> Option<T> {
+        self.args.get(index)?.parse().ok()
+    }
+}
+
+/// Builds a custom_id that [`ComponentId::parse`] reads back.
+pub fn component_id<T: Display>(prefix: &str, action: &str, args: impl IntoIterator<Item = T>) -> String {
+    let mut id = format!("{}:{}", prefix, action);
+    for arg in args {
+        id.push_str(&format!(":{}", arg));
+    }
+    id
+}
+
+/// Hands component and modal interactions to the feature named by their custom_id prefix.
+/// Collector-driven prompts use ids without a known prefix and are left alone.
+pub async fn route(ctx: &serenity::Context, interaction: &serenity::Interaction, data: &crate::Data) -> Result<(), crate::Error> {
+    let custom_id = match interaction {
+        serenity::Interaction::Component(mci) => &mci.data.custom_id,
+        serenity::Interaction::Modal(modal) => &modal.data.custom_id,
+        _ => return Ok(()),
+    };
+    let Some(id) = ComponentId::parse(custom_id) else { return Ok(()) };
+    match id.prefix.as_str() {
+        commands::moderation::appeals::PREFIX => commands::moderation::appeals::handle_interaction(ctx, interaction, data).await,
+        commands::moderation::reports::PREFIX => commands::moderation::reports::handle_interaction(ctx, interaction, data).await,
+        commands::reaction_roles::panels::PREFIX => commands::reaction_roles::panels::handle_interaction(ctx, interaction, data).await,
+        _ => Ok(()),
+    }
+}
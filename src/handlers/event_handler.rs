@@ -1,6 +1,14 @@
+use crate::commands;
 use poise::serenity_prelude as serenity;
 use std::time::Duration;
 
+/// Logs the error of a handler, so the other handlers of the event still run.
+fn report(handler: &str, result: Result<(), crate::Error>) {
+    if let Err(e) = result {
+        eprintln!("{} failed: {}", handler, e);
+    }
+}
+
 pub async fn handle_event<'a>(
     ctx: &serenity::Context,
     event: &serenity::FullEvent,
@@ -88,8 +96,40 @@ pub async fn handle_event<'a>(
             println!("{}", table_hline);
             println!("{}", hline);
 
+            tokio::spawn(commands::reaction_roles::reactions::reconcile(ctx.clone(), data.db.clone(), data.reaction_removals.clone()));
+        }
+        serenity::FullEvent::InteractionCreate { interaction } => {
+            report("component_router::route", super::component_router::route(ctx, interaction, data).await);
+        }
+        serenity::FullEvent::Message { new_message } => {
+            report("appeals::relay_dm", commands::moderation::appeals::relay_dm(ctx, new_message, data).await);
+            report("slowmode::track_message", commands::moderation::slowmode::track_message(ctx, new_message, data).await);
+        }
+        serenity::FullEvent::GuildMemberAddition { new_member } => {
+            report("join::handle_join", commands::join_roles::join::handle_join(ctx, new_member, data).await);
+            report("member_stats::handle_join", commands::information::member_stats::handle_join(new_member, data).await);
+        }
+        serenity::FullEvent::GuildMemberUpdate { event, .. } => {
+            report("join::handle_update", commands::join_roles::join::handle_update(ctx, event, data).await);
+        }
+        serenity::FullEvent::GuildMemberRemoval { guild_id, user, .. } => {
+            report("join::handle_leave", commands::join_roles::join::handle_leave(*guild_id, user, data).await);
+            report("member_stats::handle_leave", commands::information::member_stats::handle_leave(*guild_id, data).await);
+        }
+        serenity::FullEvent::ReactionAdd { add_reaction } => {
+            report("reactions::handle_reaction", commands::reaction_roles::reactions::handle_reaction(ctx, add_reaction, true, data).await);
+        }
+        serenity::FullEvent::ReactionRemove { removed_reaction } => {
+            report("reactions::handle_reaction", commands::reaction_roles::reactions::handle_reaction(ctx, removed_reaction, false, data).await);
+        }
+        serenity::FullEvent::ReactionRemoveAll { removed_from_message_id, .. } => {
+            report("reactions::handle_clear", commands::reaction_roles::reactions::handle_clear(&data.db, removed_from_message_id.get(), None).await);
+        }
+        serenity::FullEvent::ReactionRemoveEmoji { removed_reactions } => {
+            report("reactions::handle_clear", commands::reaction_roles::reactions::handle_clear(&data.db, removed_reactions.message_id.get(), Some(&removed_reactions.emoji)).await);
         }
         _ => {}
     }
-    Ok(())
+    // Runs no matter how the handlers above went, so a failing handler doesn't cost the log entry
+    commands::logging::events::log_event(ctx, event, data).await
 }
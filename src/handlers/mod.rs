@@ -1,2 +1,3 @@
 pub mod command_handler;
+pub mod component_router;
 pub mod event_handler;
@@ -10,6 +10,11 @@ pub struct Data {
     pub started_at: Instant,
     pub commands_check_duration: Duration,
     pub command_statuses: Vec<CommandStatus>,
+    pub db: sled::Db,
+    pub message_rates: commands::moderation::slowmode::RateTracker,
+    pub message_cache: commands::logging::cache::MessageCache,
+    pub reaction_removals: commands::reaction_roles::reactions::BotRemovals,
+    pub join_role_syncs: commands::join_roles::join::SyncJobs,
 }
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -17,6 +22,7 @@ pub type Error = Box<dyn std::error::Error + Send + Sync>;
 mod handlers;
 pub mod commands;
 pub mod data;
+mod tasks;
 
 #[tokio::main]
 async fn main() {
@@ -24,11 +30,29 @@ async fn main() {
     let program_started = Instant::now();
 
     let token = std::env::var("TOKEN").expect("missing TOKEN");
-    let intents = serenity::GatewayIntents::privileged();
+    // Members (join roles, member stats), presences (online counts) and message content (prefix commands)
+    let intents = serenity::GatewayIntents::privileged()
+        // Roles and channels are cached for hierarchy and permission checks, and logged
+        | serenity::GatewayIntents::GUILDS
+        // Ban logs
+        | serenity::GatewayIntents::GUILD_MODERATION
+        // Prefix commands, slow mode and message logs
+        | serenity::GatewayIntents::GUILD_MESSAGES
+        // Ban appeals are relayed from DMs
+        | serenity::GatewayIntents::DIRECT_MESSAGES
+        // Reaction roles
+        | serenity::GatewayIntents::GUILD_MESSAGE_REACTIONS
+        // Voice and invite logs
+        | serenity::GatewayIntents::GUILD_VOICE_STATES
+        | serenity::GatewayIntents::GUILD_INVITES
+        // Keeps cached emojis and stickers current for the info commands
+        | serenity::GatewayIntents::GUILD_EMOJIS_AND_STICKERS;
+    let db = sled::open("db").expect("failed to open database");
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             commands: handlers::command_handler::commands(),
+            command_check: Some(|ctx| Box::pin(commands::general::features::command_check(ctx))),
             event_handler: |ctx, event, framework, data| {
                 Box::pin(async move {
                     handlers::event_handler::handle_event(ctx, event, framework, data).await
@@ -67,10 +91,22 @@ async fn main() {
                     }
                 }
 
+                tokio::spawn(tasks::case_expiry::run(ctx.clone(), db.clone()));
+                tokio::spawn(tasks::slow_mode::run(ctx.clone(), db.clone()));
+                tokio::spawn(tasks::log_delivery::run(ctx.clone(), db.clone()));
+                tokio::spawn(tasks::join_roles::run(ctx.clone(), db.clone()));
+                tokio::spawn(tasks::schedules::run(ctx.clone(), db.clone()));
+                tokio::spawn(tasks::member_stats::run(ctx.clone(), db.clone()));
+
                 Ok(Data {
                     started_at: program_started,
                     commands_check_duration,
                     command_statuses: statuses,
+                    db,
+                    message_rates: Default::default(),
+                    message_cache: Default::default(),
+                    reaction_removals: Default::default(),
+                    join_role_syncs: Default::default(),
                 })
             })
         })
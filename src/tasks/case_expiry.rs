@@ -0,0 +1,49 @@
+use crate::commands::general::features::{enabled, Feature};
+use crate::data::cases::store::{CaseAction, CaseEventKind, CaseStore};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(30);
+
+/// Closes cases whose duration has run out and lifts temporary bans.
+/// Timeouts are lifted by Discord itself, so mutes only need their case closed.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db).await {
+            eprintln!("case expiry failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db) -> Result<(), crate::Error> {
+    let store = CaseStore::open(db)?;
+    let now = chrono::Utc::now().timestamp();
+
+    for (guild_id, id) in store.due_expiries(now)? {
+        // Stays due while moderation is disabled and expires once it's back on
+        if !enabled(db, guild_id, Feature::Moderation) {
+            continue;
+        }
+        let Some(mut case) = store.get(guild_id, id)? else { continue };
+        if !case.closed && case.action == CaseAction::Ban {
+            let reason = format!("Temporary ban expired (case #{})", case.id);
+            for target in &case.targets {
+                // The user may already have been unbanned by hand
+                let _ = ctx
+                    .http
+                    .remove_ban(serenity::GuildId::new(guild_id), serenity::UserId::new(*target), Some(&reason))
+                    .await;
+            }
+        }
+        if !case.closed {
+            case.closed = true;
+            case.closed_reason = Some("Expired".to_string());
+            case.record(None, CaseEventKind::Expired, None);
+        }
+        // Saving also drops the case from the expiry index
+        store.save(&case)?;
+    }
+    Ok(())
+}
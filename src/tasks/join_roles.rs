@@ -0,0 +1,72 @@
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::join_roles::join::give_roles;
+use crate::data::join_roles::store::JoinRoleStore;
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(15);
+// Discord error code of a member who left the guild
+const UNKNOWN_MEMBER: isize = 10007;
+
+/// Gives join roles to members whose delay ran out. Also notices members who passed screening while
+/// the bot was offline, since their update event was missed.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db).await {
+            eprintln!("join roles task failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db) -> Result<(), crate::Error> {
+    let store = JoinRoleStore::open(db)?;
+    let now = chrono::Utc::now().timestamp();
+
+    for mut pending in store.all_pending()? {
+        // Kept queued while join roles are disabled
+        if !enabled(db, pending.guild_id, Feature::JoinRoles) {
+            continue;
+        }
+        let guild_id = serenity::GuildId::new(pending.guild_id);
+        let user_id = serenity::UserId::new(pending.user_id);
+        match pending.due_at {
+            Some(due) if due > now => continue,
+            Some(_) => {}
+            None => {
+                let passed = ctx
+                    .cache
+                    .guild(guild_id)
+                    .and_then(|g| g.members.get(&user_id).map(|m| !m.pending))
+                    .unwrap_or(false);
+                if !passed {
+                    continue;
+                }
+                let delay = store.config(pending.guild_id)?.delay_secs;
+                if let Some(delay) = delay {
+                    pending.due_at = Some(now + delay as i64);
+                    store.set_pending(&pending)?;
+                    continue;
+                }
+            }
+        }
+
+        let member = match guild_id.member(ctx, user_id).await {
+            Ok(m) => m,
+            Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))) if r.error.code == UNKNOWN_MEMBER => {
+                store.clear_pending(pending.guild_id, pending.user_id)?;
+                continue;
+            }
+            // Stays queued, so temporary failures are retried on the next tick
+            Err(e) => {
+                eprintln!("join roles for {} in guild {} failed: {}", user_id, guild_id, e);
+                continue;
+            }
+        };
+        let config = store.config(pending.guild_id)?;
+        give_roles(&ctx.http, guild_id, &member, config.roles(member.user.bot)).await;
+        store.clear_pending(pending.guild_id, pending.user_id)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,319 @@
+use crate::data::logging::store::{LogStore, LogWebhook, QueuedLog};
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const TICK: Duration = Duration::from_secs(2);
+// Entries of one channel looked at per tick, the rest waits for the next one
+const CHANNEL_LIMIT: usize = 100;
+const MAX_EMBEDS: usize = 10;
+// Discord's limit on the combined text of all embeds in one message
+const MAX_EMBED_CHARS: usize = 6000;
+// Entries that couldn't be delivered for this long are dropped
+const MAX_AGE_SECS: i64 = 3 * 24 * 60 * 60;
+const WEBHOOK_NAME: &str = "Logs";
+// A failing channel waits this long, doubled after every further failure up to the maximum
+const BACKOFF_START: Duration = Duration::from_secs(10);
+const BACKOFF_MAX: Duration = Duration::from_secs(60 * 60);
+// How long the bot assumes it still can't manage a channel's webhooks
+const NO_WEBHOOK_RECHECK: Duration = Duration::from_secs(60 * 60);
+
+// Discord error codes
+const UNKNOWN_CHANNEL: isize = 10003;
+const UNKNOWN_WEBHOOK: isize = 10015;
+const MISSING_ACCESS: isize = 50001;
+const MISSING_PERMISSIONS: isize = 50013;
+
+enum Failure {
+    /// The log channel no longer exists, its entries are dropped.
+    ChannelGone,
+    /// The bot may not post in the channel, its entries are dropped until that changes.
+    Forbidden(serenity::Error),
+    /// Delivery failed for now, the entries stay queued.
+    Retry(serenity::Error),
+}
+
+struct Backoff {
+    until: Instant,
+    delay: Duration,
+}
+
+/// What the task remembers between ticks about channels that failed.
+#[derive(Default)]
+struct State {
+    backoff: HashMap<u64, Backoff>,
+    /// Channels where the bot lacks Manage Webhooks, and when that was noticed.
+    no_webhook: HashMap<u64, Instant>,
+}
+
+impl State {
+    fn waiting(&self, channel_id: u64) -> bool {
+        self.backoff.get(&channel_id).is_some_and(|b| b.until > Instant::now())
+    }
+
+    fn fail(&mut self, channel_id: u64) {
+        let delay = self.backoff.get(&channel_id).map_or(BACKOFF_START, |b| (b.delay * 2).min(BACKOFF_MAX));
+        self.backoff.insert(channel_id, Backoff { until: Instant::now() + delay, delay });
+    }
+
+    fn can_manage_webhooks(&mut self, channel_id: u64) -> bool {
+        match self.no_webhook.get(&channel_id) {
+            Some(since) if since.elapsed() < NO_WEBHOOK_RECHECK => false,
+            Some(_) => {
+                self.no_webhook.remove(&channel_id);
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+/// Sends queued log entries through a webhook in each log channel, up to ten embeds per message.
+/// Entries stay queued until Discord accepted them, so outages and restarts don't lose logs. Each
+/// channel is read and retried on its own, so one failing channel doesn't hold up the others.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    let mut state = State::default();
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db, &mut state).await {
+            eprintln!("log delivery failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db, state: &mut State) -> Result<(), crate::Error> {
+    let store = LogStore::open(db)?;
+    let cutoff = chrono::Utc::now().timestamp() - MAX_AGE_SECS;
+
+    for channel_id in store.queued_channels()? {
+        if state.waiting(channel_id) {
+            continue;
+        }
+        let queued = store.queued(channel_id, CHANNEL_LIMIT)?;
+        let (stale, queued): (Vec<QueuedLog>, Vec<QueuedLog>) = queued.into_iter().partition(|q| q.queued_at < cutoff);
+        if !stale.is_empty() {
+            eprintln!("dropping {} log entries for channel {} that could not be delivered in time", stale.len(), channel_id);
+            store.dequeue(channel_id, &stale.iter().map(|q| q.id).collect::<Vec<_>>())?;
+        }
+
+        for batch in batches(&queued) {
+            let ids: Vec<u64> = batch.iter().map(|q| q.id).collect();
+            let embeds: Vec<&serde_json::Value> = batch.iter().map(|q| &q.embed).collect();
+            match deliver(ctx, &store, state, channel_id, &embeds).await {
+                Ok(()) => {
+                    store.dequeue(channel_id, &ids)?;
+                    state.backoff.remove(&channel_id);
+                }
+                Err(Failure::ChannelGone) => {
+                    store.remove_webhook(channel_id)?;
+                    store.clear_queue(channel_id)?;
+                    state.backoff.remove(&channel_id);
+                    break;
+                }
+                Err(Failure::Forbidden(e)) => {
+                    let dropped = store.clear_queue(channel_id)?;
+                    eprintln!("dropping {} log entries, the bot may not post in channel {}: {}", dropped, channel_id, e);
+                    state.fail(channel_id);
+                    break;
+                }
+                Err(Failure::Retry(e)) => {
+                    // Later batches of this channel wait too, so entries stay in order
+                    eprintln!("failed to deliver logs to channel {}: {}", channel_id, e);
+                    state.fail(channel_id);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits entries into messages of at most ten embeds and 6000 characters.
+fn batches(entries: &[QueuedLog]) -> Vec<&[QueuedLog]> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (i, q) in entries.iter().enumerate() {
+        let len = embed_chars(&q.embed);
+        if i > start && (i - start == MAX_EMBEDS || chars + len > MAX_EMBED_CHARS) {
+            out.push(&entries[start..i]);
+            start = i;
+            chars = 0;
+        }
+        chars += len;
+    }
+    if start < entries.len() {
+        out.push(&entries[start..]);
+    }
+    out
+}
+
+/// The characters of an embed that count towards Discord's per-message limit.
+fn embed_chars(embed: &serde_json::Value) -> usize {
+    let text = |v: &serde_json::Value| v.as_str().map_or(0, |s| s.chars().count());
+    let mut n = text(&embed["title"]) + text(&embed["description"]) + text(&embed["footer"]["text"]) + text(&embed["author"]["name"]);
+    if let Some(fields) = embed["fields"].as_array() {
+        n += fields.iter().map(|f| text(&f["name"]) + text(&f["value"])).sum::<usize>();
+    }
+    n
+}
+
+fn error_code(e: &serenity::Error) -> Option<isize> {
+    match e {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r)) => Some(r.error.code),
+        _ => None,
+    }
+}
+
+fn is_forbidden(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r)) if r.status_code == serenity::StatusCode::FORBIDDEN
+    ) || matches!(error_code(e), Some(MISSING_ACCESS | MISSING_PERMISSIONS))
+}
+
+async fn deliver(
+    ctx: &serenity::Context,
+    store: &LogStore,
+    state: &mut State,
+    channel_id: u64,
+    embeds: &[&serde_json::Value],
+) -> Result<(), Failure> {
+    let (name, avatar) = {
+        let me = ctx.cache.current_user();
+        (me.name.clone(), me.face())
+    };
+    let webhook = match store.webhook(channel_id) {
+        Ok(Some(w)) => Some(w),
+        _ if state.can_manage_webhooks(channel_id) => {
+            let webhook = webhook_for(ctx, store, channel_id).await?;
+            if webhook.is_none() {
+                state.no_webhook.insert(channel_id, Instant::now());
+            }
+            webhook
+        }
+        _ => None,
+    };
+
+    if let Some(w) = webhook {
+        let body = serde_json::json!({ "embeds": embeds, "username": name, "avatar_url": avatar });
+        let result = ctx
+            .http
+            .execute_webhook(serenity::WebhookId::new(w.webhook_id), None, &w.token, false, vec![], &body)
+            .await;
+        return match result {
+            Ok(_) => Ok(()),
+            Err(e) if error_code(&e) == Some(UNKNOWN_WEBHOOK) => {
+                // Deleted by hand, a new one is made on the next try
+                let _ = store.remove_webhook(channel_id);
+                Err(Failure::Retry(e))
+            }
+            Err(e) if error_code(&e) == Some(UNKNOWN_CHANNEL) => Err(Failure::ChannelGone),
+            Err(e) if is_forbidden(&e) => Err(Failure::Forbidden(e)),
+            Err(e) => Err(Failure::Retry(e)),
+        };
+    }
+
+    // Without Manage Webhooks the entries still go out as normal bot messages
+    let body = serde_json::json!({ "embeds": embeds });
+    match ctx.http.send_message(serenity::ChannelId::new(channel_id), vec![], &body).await {
+        Ok(_) => Ok(()),
+        Err(e) if error_code(&e) == Some(UNKNOWN_CHANNEL) => Err(Failure::ChannelGone),
+        Err(e) if is_forbidden(&e) => Err(Failure::Forbidden(e)),
+        Err(e) => Err(Failure::Retry(e)),
+    }
+}
+
+/// Finds the bot's webhook in a log channel or creates one. None when the bot may not manage webhooks.
+async fn webhook_for(ctx: &serenity::Context, store: &LogStore, channel_id: u64) -> Result<Option<LogWebhook>, Failure> {
+    let channel = serenity::ChannelId::new(channel_id);
+    let bot_id = ctx.cache.current_user().id;
+    let existing = match channel.webhooks(&ctx.http).await {
+        Ok(hooks) => hooks.into_iter().find(|w| w.user.as_ref().is_some_and(|u| u.id == bot_id) && w.token.is_some()),
+        Err(e) if is_forbidden(&e) => return Ok(None),
+        Err(e) if error_code(&e) == Some(UNKNOWN_CHANNEL) => return Err(Failure::ChannelGone),
+        Err(e) => return Err(Failure::Retry(e)),
+    };
+    let webhook = match existing {
+        Some(w) => w,
+        None => match channel.create_webhook(&ctx.http, serenity::CreateWebhook::new(WEBHOOK_NAME)).await {
+            Ok(w) => w,
+            Err(e) if is_forbidden(&e) => return Ok(None),
+            Err(e) => return Err(Failure::Retry(e)),
+        },
+    };
+    // The token is only exposed through the webhook URL, which ends with it
+    let Some(token) = webhook.url().ok().and_then(|u| u.rsplit('/').next().map(str::to_string)) else { return Ok(None) };
+    let entry = LogWebhook { channel_id, webhook_id: webhook.id.get(), token };
+    if let Err(e) = store.set_webhook(&entry) {
+        eprintln!("failed to save log webhook for channel {}: {}", channel_id, e);
+    }
+    Ok(Some(entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::logging::store::LogType;
+
+    fn entry(id: u64, description_chars: usize) -> QueuedLog {
+        QueuedLog {
+            id,
+            guild_id: 1,
+            channel_id: 2,
+            log_type: LogType::MessageEdit,
+            embed: serde_json::json!({ "description": "x".repeat(description_chars) }),
+            queued_at: 0,
+        }
+    }
+
+    fn sizes(entries: &[QueuedLog]) -> Vec<usize> {
+        batches(entries).iter().map(|b| b.len()).collect()
+    }
+
+    #[test]
+    fn no_entries_no_batches() {
+        assert!(batches(&[]).is_empty());
+    }
+
+    #[test]
+    fn at_most_ten_embeds_per_batch() {
+        let entries: Vec<QueuedLog> = (0..25).map(|i| entry(i, 10)).collect();
+        assert_eq!(sizes(&entries), vec![10, 10, 5]);
+    }
+
+    #[test]
+    fn batches_stay_under_the_character_limit() {
+        let entries: Vec<QueuedLog> = (0..3).map(|i| entry(i, 2500)).collect();
+        assert_eq!(sizes(&entries), vec![2, 1]);
+        let entries: Vec<QueuedLog> = (0..2).map(|i| entry(i, 3000)).collect();
+        assert_eq!(sizes(&entries), vec![2]);
+    }
+
+    #[test]
+    fn oversized_entry_gets_its_own_batch() {
+        let entries = vec![entry(0, 100), entry(1, 7000), entry(2, 100)];
+        assert_eq!(sizes(&entries), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn batches_keep_the_queue_order() {
+        let entries: Vec<QueuedLog> = (0..12).map(|i| entry(i, 10)).collect();
+        let ids: Vec<u64> = batches(&entries).concat().iter().map(|q| q.id).collect();
+        assert_eq!(ids, (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn all_embed_text_counts() {
+        let embed = serde_json::json!({
+            "title": "ab",
+            "description": "cde",
+            "footer": { "text": "f" },
+            "author": { "name": "gh" },
+            "fields": [{ "name": "i", "value": "jk" }],
+            "color": 123,
+        });
+        assert_eq!(embed_chars(&embed), 11);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::information::member_stats::{channel_name, record_counts, MemberCounts};
+use crate::commands::information::stat_channels::RENAME_EVERY;
+use crate::data::member_stats::store::MemberStatsStore;
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(60);
+// Discord error code of a deleted channel
+const UNKNOWN_CHANNEL: isize = 10003;
+
+/// Saves today's member counts of every cached guild and renames stat channels whose count changed,
+/// at most once per `RENAME_EVERY` seconds each.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db).await {
+            eprintln!("member stats task failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db) -> Result<(), crate::Error> {
+    let store = MemberStatsStore::open(db)?;
+    for guild_id in ctx.cache.guilds() {
+        if let Some(counts) = MemberCounts::cached(&ctx.cache, guild_id) {
+            record_counts(&store, guild_id.get(), &counts)?;
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    for guild in store.channel_guilds()? {
+        let guild_id = serenity::GuildId::new(guild);
+        // Guilds the bot left aren't cached, their channels are kept in case it comes back
+        let Some(counts) = MemberCounts::cached(&ctx.cache, guild_id) else { continue };
+        if !enabled(db, guild, Feature::Information) {
+            continue;
+        }
+        let mut renamed = Vec::new();
+        let mut deleted = Vec::new();
+        for stat in store.channels(guild)? {
+            if now - stat.renamed_at < RENAME_EVERY {
+                continue;
+            }
+            let Some(name) = channel_name(&stat, &counts) else { continue };
+            let channel_id = serenity::ChannelId::new(stat.channel_id);
+            let current = ctx.cache.guild(guild_id).and_then(|g| g.channels.get(&channel_id).map(|c| c.name.clone()));
+            if current.as_deref() == Some(name.as_str()) {
+                continue;
+            }
+            match channel_id.edit(&ctx.http, serenity::EditChannel::new().name(name)).await {
+                Ok(_) => renamed.push(stat.channel_id),
+                Err(serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(r))) if r.error.code == UNKNOWN_CHANNEL => {
+                    deleted.push(stat.channel_id);
+                }
+                Err(e) => eprintln!("renaming stat channel {} failed: {}", channel_id, e),
+            }
+        }
+        if renamed.is_empty() && deleted.is_empty() {
+            continue;
+        }
+        // Reloaded since the channels may have been changed by a command during the renames
+        let mut channels = store.channels(guild)?;
+        channels.retain(|c| !deleted.contains(&c.channel_id));
+        for stat in channels.iter_mut().filter(|c| renamed.contains(&c.channel_id)) {
+            stat.renamed_at = now;
+        }
+        store.set_channels(guild, &channels)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+pub mod case_expiry;
+pub mod join_roles;
+pub mod log_delivery;
+pub mod member_stats;
+pub mod schedules;
+pub mod slow_mode;
@@ -0,0 +1,78 @@
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::messages::schedule::{next_run, send};
+use crate::data::schedules::store::{CatchUp, ScheduleStore, ScheduleTiming};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(20);
+// Runs this late still count as on time rather than missed
+const GRACE_SECS: i64 = 5 * 60;
+// Most missed runs sent at once with the `all` catch-up policy
+const MAX_CATCH_UP: usize = 10;
+
+/// Sends scheduled messages that are due. Runs missed while the bot was offline are skipped, sent
+/// once or all sent, following each schedule's catch-up policy.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db).await {
+            eprintln!("schedule task failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db) -> Result<(), crate::Error> {
+    let store = ScheduleStore::open(db)?;
+    let now = chrono::Utc::now().timestamp();
+
+    for mut schedule in store.all()? {
+        // Missed runs of a disabled feature are handled by the catch-up policy once it's back
+        if schedule.paused || schedule.next_run > now || !enabled(db, schedule.guild_id, Feature::Messages) {
+            continue;
+        }
+        let tz = store.config(schedule.guild_id)?.tz();
+        let mut due = vec![schedule.next_run];
+        let mut next = next_run(&schedule.timing, schedule.next_run, tz);
+        while let Some(n) = next
+            && n <= now
+        {
+            if due.len() < MAX_CATCH_UP {
+                due.push(n);
+            }
+            next = match schedule.timing {
+                // Jumps straight past long downtimes instead of stepping through every interval
+                ScheduleTiming::Interval { secs } if due.len() >= MAX_CATCH_UP => {
+                    let secs = secs.max(1) as i64;
+                    Some(n + ((now - n) / secs + 1) * secs)
+                }
+                _ => next_run(&schedule.timing, n, tz),
+            };
+        }
+        let on_time = due.iter().any(|t| now - t <= GRACE_SECS);
+        let sends = match schedule.catch_up {
+            CatchUp::Skip => usize::from(on_time),
+            CatchUp::Once => 1,
+            CatchUp::All => due.len(),
+        };
+
+        // Saved before sending so a crash doesn't send the same run twice
+        match next {
+            Some(n) => {
+                schedule.next_run = n;
+                schedule.last_run = Some(now);
+                store.save(&schedule)?;
+            }
+            None => {
+                store.remove(schedule.guild_id, schedule.id)?;
+            }
+        }
+        for _ in 0..sends {
+            if let Err(e) = send(ctx, db, &schedule).await {
+                eprintln!("schedule {} in guild {} failed: {}", schedule.id, schedule.guild_id, e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
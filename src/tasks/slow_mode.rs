@@ -0,0 +1,117 @@
+use crate::commands::general::features::{enabled, Feature};
+use crate::commands::moderation::slowmode::{current_delay, format_delay, level_below, set_delay};
+use crate::data::slowmode::store::{RaisedSlowMode, SlowModeSource, SlowModeStore};
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(15);
+
+/// Starts and ends scheduled slow mode windows and steps auto slow mode back down once channels calm down.
+pub async fn run(ctx: serenity::Context, db: sled::Db) {
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&ctx, &db).await {
+            eprintln!("slow mode task failed: {}", e);
+        }
+    }
+}
+
+async fn tick(ctx: &serenity::Context, db: &sled::Db) -> Result<(), crate::Error> {
+    let store = SlowModeStore::open(db)?;
+    let now = chrono::Utc::now().timestamp();
+
+    for mut window in store.all_windows()? {
+        let label = window.label.clone().unwrap_or_else(|| format!("window #{}", window.id));
+        if !window.started && window.starts_at <= now {
+            if window.ends_at <= now {
+                // The bot was offline for the whole window
+                store.remove_window(window.guild_id, window.id)?;
+                continue;
+            }
+            // Windows wait while moderation is disabled, ending ones still restore their channel
+            if !enabled(db, window.guild_id, Feature::Moderation) {
+                continue;
+            }
+            // A channel raised by auto slow mode goes back to its own delay once the window ends
+            let base = match store.raised(window.guild_id, window.channel_id)? {
+                Some(r) => r.base,
+                None => match current_delay(&ctx.http, window.channel_id).await {
+                    Ok(d) => d,
+                    Err(e) => {
+                        eprintln!("slow mode window {} in guild {} failed: {}", window.id, window.guild_id, e);
+                        store.remove_window(window.guild_id, window.id)?;
+                        continue;
+                    }
+                },
+            };
+            let cause = format!("Scheduled slow mode started: {}", label);
+            if let Err(e) = set_delay(ctx, db, window.guild_id, window.channel_id, base, window.delay, &cause, Some(window.created_by)).await {
+                eprintln!("slow mode window {} in guild {} failed: {}", window.id, window.guild_id, e);
+                store.remove_window(window.guild_id, window.id)?;
+                continue;
+            }
+            store.set_raised(&RaisedSlowMode {
+                guild_id: window.guild_id,
+                channel_id: window.channel_id,
+                base,
+                current: window.delay,
+                source: SlowModeSource::Window,
+                changed_at: now,
+                window_id: Some(window.id),
+            })?;
+            window.started = true;
+            store.save_window(&window)?;
+        } else if window.started && window.ends_at <= now {
+            end_window(ctx, db, &store, window.guild_id, window.channel_id, window.id, &format!("Scheduled slow mode ended: {}", label)).await?;
+        }
+    }
+
+    for mut raised in store.all_raised()? {
+        if raised.source != SlowModeSource::Auto {
+            continue;
+        }
+        let next = match store.auto(raised.guild_id, raised.channel_id)? {
+            Some(auto) if now - raised.changed_at < auto.decay_secs as i64 => continue,
+            Some(_) => level_below(raised.current).max(raised.base),
+            // Auto slow mode was turned off while the delay was raised
+            None => raised.base,
+        };
+        let cause = format!("Auto slow mode decay: back to {}", format_delay(next));
+        if let Err(e) = set_delay(ctx, db, raised.guild_id, raised.channel_id, raised.current, next, &cause, None).await {
+            eprintln!("slow mode decay in channel {} failed: {}", raised.channel_id, e);
+            store.clear_raised(raised.guild_id, raised.channel_id)?;
+            continue;
+        }
+        if next <= raised.base {
+            store.clear_raised(raised.guild_id, raised.channel_id)?;
+        } else {
+            raised.current = next;
+            raised.changed_at = now;
+            store.set_raised(&raised)?;
+        }
+    }
+    Ok(())
+}
+
+/// Ends a running window: the channel gets its earlier delay back unless someone changed it by hand meanwhile.
+pub(crate) async fn end_window(
+    ctx: &serenity::Context,
+    db: &sled::Db,
+    store: &SlowModeStore,
+    guild_id: u64,
+    channel_id: u64,
+    window_id: u64,
+    cause: &str,
+) -> Result<(), crate::Error> {
+    if let Some(r) = store.raised(guild_id, channel_id)?
+        && r.window_id == Some(window_id)
+    {
+        store.clear_raised(guild_id, channel_id)?;
+        if let Err(e) = set_delay(ctx, db, guild_id, channel_id, r.current, r.base, cause, None).await {
+            eprintln!("ending slow mode window {} in guild {} failed: {}", window_id, guild_id, e);
+        }
+    }
+    store.remove_window(guild_id, window_id)?;
+    Ok(())
+}